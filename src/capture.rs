@@ -0,0 +1,163 @@
+use anyhow::Result;
+use std::path::{ Path, PathBuf };
+use std::io::BufWriter;
+use std::fs::File;
+use std::time::{ SystemTime, UNIX_EPOCH };
+
+
+
+/// 每个像素的字节数(RGBA8)
+pub const BYTES_PER_PIXEL: u32 = 4;
+
+/// 将连续的画面以PNG格式逐帧保存到目录中，可以用ffmpeg等工具合成视频
+///
+/// 文件名为`frame-000000.png`、`frame-000001.png`……，每次调用`record`保存一帧，
+/// 因此帧率与调用`record`的频率相同
+#[derive(Debug)]
+pub struct Recorder {
+    dir: PathBuf,
+
+    /// 已保存的帧数
+    frames: usize,
+}
+
+/// 读回的一帧画面
+#[derive(Debug, Clone, PartialEq)]
+pub struct Screenshot {
+    pub width: u32,
+    pub height: u32,
+
+    /// 按行从上到下排列的RGBA像素，每行`width * 4`字节，没有填充
+    pub rgba: Vec<u8>,
+}
+
+
+
+impl Screenshot {
+    /// 以PNG格式保存到文件
+    pub fn save_png(&self, path: &Path)-> Result<()> {
+        let writer = BufWriter::new(File::create(path)?);
+        let mut encoder = png::Encoder::new(writer, self.width, self.height);
+        encoder.set_color(png::ColorType::Rgba);
+        encoder.set_depth(png::BitDepth::Eight);
+
+        let mut writer = encoder.write_header()?;
+        writer.write_image_data(&self.rgba)?;
+        Ok(())
+    }
+}
+
+impl Recorder {
+    /// 创建录制到`dir`的录制器，目录不存在时创建
+    pub fn new(dir: impl Into<PathBuf>)-> Result<Self> {
+        let dir = dir.into();
+        std::fs::create_dir_all(&dir)?;
+
+        Ok(Self {
+            dir,
+            frames: 0,
+        })
+    }
+
+    /// 保存一帧，返回保存到的文件
+    pub fn record(&mut self, frame: &Screenshot)-> Result<PathBuf> {
+        let path = self.dir.join(format!("frame-{:06}.png", self.frames));
+        frame.save_png(&path)?;
+        self.frames += 1;
+        Ok(path)
+    }
+
+    /// 已保存的帧数
+    pub fn frame_count(&self)-> usize {
+        self.frames
+    }
+
+    /// 保存帧的目录
+    pub fn dir(&self)-> &Path {
+        &self.dir
+    }
+}
+
+/// 纹理复制到缓冲时每行实际占用的字节数
+///
+/// wgpu要求`bytes_per_row`为`COPY_BYTES_PER_ROW_ALIGNMENT`(256)的倍数，不足的部分在行尾填充
+pub fn padded_bytes_per_row(width: u32)-> u32 {
+    let unpadded = width * BYTES_PER_PIXEL;
+    let align = wgpu::COPY_BYTES_PER_ROW_ALIGNMENT;
+    unpadded.div_ceil(align) * align
+}
+
+/// 去掉每行行尾的填充，返回紧密排列的像素数据
+///
+/// `data`的每行长度为`padded_bytes_per_row(width)`，共`height`行
+pub fn unpad_rows(data: &[u8], width: u32, height: u32)-> Vec<u8> {
+    let unpadded = (width * BYTES_PER_PIXEL) as usize;
+    let padded = padded_bytes_per_row(width) as usize;
+
+    data.chunks(padded)
+        .take(height as usize)
+        .flat_map(|row| &row[..unpadded])
+        .copied()
+        .collect()
+}
+
+/// 在当前目录下以当前时间命名截图文件，如`screenshot-1700000000123.png`
+pub fn screenshot_path()-> PathBuf {
+    PathBuf::from(format!("screenshot-{}.png", unix_millis()))
+}
+
+/// 在当前目录下以当前时间命名录制使用的目录，如`recording-1700000000123`
+pub fn recording_dir()-> PathBuf {
+    PathBuf::from(format!("recording-{}", unix_millis()))
+}
+
+fn unix_millis()-> u128 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_millis())
+        .unwrap_or(0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn rows_are_padded_to_256_bytes() {
+        assert_eq!(padded_bytes_per_row(1), 256);
+        assert_eq!(padded_bytes_per_row(64), 256);
+        assert_eq!(padded_bytes_per_row(65), 512);
+        assert_eq!(padded_bytes_per_row(1280), 5120);
+    }
+
+    #[test]
+    fn unpad_rows_drops_row_padding() {
+        // 宽3像素，每行12字节像素数据后跟244字节填充
+        let (width, height) = (3, 2);
+        let mut data = Vec::new();
+        for row in 0..height {
+            data.extend((0..12).map(|i| row as u8 * 100 + i));
+            data.resize(padded_bytes_per_row(width) as usize * (row as usize + 1), 0xff);
+        }
+
+        let rows = unpad_rows(&data, width, height);
+        let expected: Vec<u8> = (0..12).chain(100..112).collect();
+        assert_eq!(rows, expected);
+    }
+
+    #[test]
+    fn recorder_saves_one_file_per_frame() {
+        let dir = std::env::temp_dir().join(format!("recording-test-{}", unix_millis()));
+        let mut recorder = Recorder::new(&dir).unwrap();
+        let frame = Screenshot { width: 2, height: 2, rgba: vec![0x80; 16] };
+
+        let paths: Vec<PathBuf> = (0..3).map(|_| recorder.record(&frame).unwrap()).collect();
+        let mut files: Vec<PathBuf> = std::fs::read_dir(&dir).unwrap().map(|e| e.unwrap().path()).collect();
+        files.sort();
+        std::fs::remove_dir_all(&dir).unwrap();
+
+        assert_eq!(recorder.frame_count(), 3);
+        assert_eq!(files, paths);
+        assert_eq!(paths[2], dir.join("frame-000002.png"));
+    }
+}
@@ -1,6 +1,8 @@
 struct CircleData {
     center: vec3<f32>,
     radius: f32,
+    outline_color: vec4<f32>,
+    outline_width: f32,
 }
 
 struct BasicUniform {
@@ -13,7 +15,7 @@ struct BasicUniform {
 
 @group(1) @binding(0) var<uniform> circle: CircleData;
 
-fn distance(p1: vec2<f32>, p2: vec2<f32>)-> f32 {
+fn screen_distance(p1: vec2<f32>, p2: vec2<f32>)-> f32 {
     return sqrt(pow(p1.x - p2.x, 2.0) + pow((p1.y - p2.y) / d.aspect_ratio, 2.0));
 }
 
@@ -30,9 +32,21 @@ fn circle_fs(
 )-> @location(0) vec4<f32> {
     let c = d.camera_coord * d.scale;
     let center = vec2<f32>(circle.center.x + c.x, (circle.center.y - c.y) * d.aspect_ratio) * d.scale;
-    if distance(center, in.coord.xy) <= circle.radius * d.scale {
-        return in.color;
-    } else {
-        return vec4<f32>(1.0, 1.0, 1.0, 0.0);
+    // 圆外的片段直接丢弃，避免写入深度缓冲遮挡后面的物体
+    let dist = screen_distance(center, in.coord.xy);
+    if dist > circle.radius * d.scale {
+        discard;
     }
+
+    // 距圆周`outline_width`以内的部分绘制为轮廓
+    if circle.outline_width > 0.0 && dist > (circle.radius - circle.outline_width) * d.scale {
+        return circle.outline_color;
+    }
+
+    // 填充色完全透明时丢弃轮廓以内的片段，使其成为圆环且不遮挡后面的物体
+    if in.color.a == 0.0 {
+        discard;
+    }
+
+    return in.color;
 }
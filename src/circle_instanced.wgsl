@@ -0,0 +1,65 @@
+struct BasicUniform {
+    aspect_ratio: f32,
+    scale: f32,
+    camera_coord: vec3<f32>,
+}
+
+@group(0) @binding(0) var<uniform> d: BasicUniform;
+
+
+
+struct CircleOutput {
+    @builtin(position) position: vec4<f32>,
+    @location(0) color: vec4<f32>,
+    // 片段相对圆心的偏移，以半径为单位
+    @location(1) offset: vec2<f32>,
+    @location(2) outline_color: vec4<f32>,
+    // 轮廓内侧距圆心的距离，以半径为单位，大于等于1时不绘制轮廓
+    @location(3) outline_start: f32,
+}
+
+@vertex
+fn vs_main(
+    @location(0) corner: vec2<f32>,
+    @location(1) center: vec3<f32>,
+    @location(2) radius: f32,
+    @location(3) color: vec4<f32>,
+    @location(4) outline_color: vec4<f32>,
+    @location(5) outline_width: f32,
+)-> CircleOutput {
+    let c = d.camera_coord * d.scale;
+    let pos = center + vec3<f32>(corner * radius, 0.0);
+    let final_pos = vec3<f32>(pos.x + c.x, (pos.y - c.y) * d.aspect_ratio, pos.z + c.z) * d.scale;
+
+    var out: CircleOutput;
+    // z越大离观察者越近，映射到深度缓冲的[0, 1]区间，越近深度越小
+    out.position = vec4<f32>(final_pos.xy, clamp(0.5 - final_pos.z * 0.5, 0.0, 1.0), 1.0);
+    out.color = color;
+    out.offset = corner;
+    out.outline_color = outline_color;
+    out.outline_start = 1.0;
+    if outline_width > 0.0 && radius > 0.0 {
+        out.outline_start = 1.0 - outline_width / radius;
+    }
+
+    return out;
+}
+
+@fragment
+fn fs_main(in: CircleOutput)-> @location(0) vec4<f32> {
+    let dist = length(in.offset);
+    if dist > 1.0 {
+        discard;
+    }
+
+    if dist > in.outline_start {
+        return in.outline_color;
+    }
+
+    // 填充色完全透明时丢弃轮廓以内的片段，使其成为圆环且不遮挡后面的物体
+    if in.color.a == 0.0 {
+        discard;
+    }
+
+    return in.color;
+}
@@ -22,7 +22,8 @@ fn vs_main(
     var out: VertexOutput;
     let final_pos = vec3<f32>(pos.x + c.x, (pos.y - c.y) * d.aspect_ratio, pos.z + c.z) * d.scale;
 
-    out.position = vec4<f32>(final_pos, 1.0);
+    // z越大离观察者越近，映射到深度缓冲的[0, 1]区间，越近深度越小
+    out.position = vec4<f32>(final_pos.xy, clamp(0.5 - final_pos.z * 0.5, 0.0, 1.0), 1.0);
     out.color = color;
     out.coord = final_pos;
 
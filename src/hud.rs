@@ -0,0 +1,434 @@
+use crate::render::{ Vertex, Camera };
+use crate::rng::Xorshift64;
+use std::collections::VecDeque;
+use std::time::{ Duration, Instant };
+
+
+
+/// 字形的宽度，以字形像素为单位
+pub const GLYPH_WIDTH: usize = 5;
+
+/// 字形的高度，以字形像素为单位
+pub const GLYPH_HEIGHT: usize = 7;
+
+/// 相邻字符之间的间距，以字形像素为单位
+const GLYPH_SPACING: usize = 1;
+
+/// 相邻行之间的间距，以字形像素为单位
+const LINE_SPACING: usize = 3;
+
+/// 天体标签中每个字形像素对应的屏幕像素数
+pub const LABEL_PIXEL_SIZE: f32 = 2.0;
+
+/// 未收录的字符使用的字形(问号)
+const UNKNOWN_GLYPH: [u8; GLYPH_HEIGHT] = [0x0E, 0x11, 0x01, 0x02, 0x04, 0x00, 0x04];
+
+/// 在窗口中绘制的一段文字
+///
+/// 使用内置的5x7点阵字体，每个点绘制为一个正方形，小写字母以大写字母显示
+#[derive(Debug, Clone)]
+pub struct HudText {
+    pub lines: Vec<String>,
+
+    /// 文字左上角距窗口左上角的距离，以屏幕像素为单位
+    pub origin: (f32, f32),
+
+    /// 每个字形像素对应的屏幕像素数
+    pub pixel_size: f32,
+    pub color: [f32; 4],
+}
+
+/// 作为背景的星空，由固定位置与亮度的点组成
+///
+/// 星星的坐标为标准化设备坐标，不随相机移动或缩放
+#[derive(Debug, Clone, PartialEq)]
+pub struct Starfield {
+    /// 每颗星星的坐标`(x, y)`与亮度
+    pub stars: Vec<([f32; 2], f32)>,
+
+    /// 每颗星星绘制为边长为多少屏幕像素的正方形
+    pub pixel_size: f32,
+}
+
+/// 左上角调试信息中显示的运行状态
+///
+/// 由主程序每帧更新，`enabled`为`false`时不显示
+#[derive(Debug, Clone, PartialEq)]
+pub struct DebugInfo {
+    pub enabled: bool,
+    pub timewrap: f64,
+
+    /// 物理计算每一步的基础时长
+    pub base_step: Duration,
+
+    /// 物理计算每一步的时长是否取实际经过的时间
+    pub real_time: bool,
+    pub paused: bool,
+
+    /// 绘制的帧率
+    pub fps: f64,
+
+    /// 物理计算每秒执行的步数
+    pub steps_per_second: u32,
+}
+
+/// 统计事件发生频率的计数器
+///
+/// 记录最近一段时间内每次事件发生的时刻，取其滑动平均值，用于计算帧率与物理计算的步率
+#[derive(Debug, Clone)]
+pub struct RateCounter {
+    timestamps: VecDeque<Instant>,
+
+    /// 参与平均的时间窗口长度
+    pub window: Duration,
+}
+
+
+
+impl RateCounter {
+    pub fn new(window: Duration)-> Self {
+        Self {
+            timestamps: VecDeque::new(),
+            window,
+        }
+    }
+
+    /// 记录一次发生在`now`的事件，并丢弃时间窗口以外的记录
+    pub fn tick(&mut self, now: Instant) {
+        self.timestamps.push_back(now);
+
+        while let Some(first) = self.timestamps.front() {
+            if now.duration_since(*first) > self.window {
+                self.timestamps.pop_front();
+            } else {
+                break;
+            }
+        }
+    }
+
+    /// 时间窗口内每秒发生的事件数
+    ///
+    /// 记录少于两次时无法计算，返回0
+    pub fn rate(&self)-> f64 {
+        let (Some(first), Some(last)) = (self.timestamps.front(), self.timestamps.back()) else {
+            return 0.0;
+        };
+
+        let elapsed = last.duration_since(*first).as_secs_f64();
+        if elapsed == 0.0 {
+            0.0
+        } else {
+            (self.timestamps.len() - 1) as f64 / elapsed
+        }
+    }
+}
+
+impl DebugInfo {
+    /// 切换是否显示调试信息，返回切换后的状态
+    pub fn toggle(&mut self)-> bool {
+        self.enabled = !self.enabled;
+        self.enabled
+    }
+
+    /// 调试信息的各行文字，`enabled`为`false`时返回`None`
+    pub fn lines(&self, camera: &Camera)-> Option<Vec<String>> {
+        if !self.enabled {
+            return None;
+        }
+
+        let cam = &camera.coord;
+        Some(vec![
+            format!("Camera: ({:.3}, {:.3}, {:.3})", cam[0], cam[1], cam[2]),
+            format!("Scale: {:.4e}", camera.scale.to_f64()),
+            format!("Timewrap: {}", self.timewrap),
+            format!("Base step: {:.3} ms", self.base_step.as_secs_f64() * 1000.0),
+            format!("Real time: {}", self.real_time),
+            format!("Paused: {}", self.paused),
+            format!("FPS: {:.1}", self.fps),
+            format!("Steps/s: {}", self.steps_per_second),
+        ])
+    }
+}
+
+impl Default for DebugInfo {
+    fn default()-> Self {
+        Self {
+            enabled: false,
+            timewrap: 1.0,
+            base_step: Duration::ZERO,
+            real_time: false,
+            paused: false,
+            fps: 0.0,
+            steps_per_second: 0,
+        }
+    }
+}
+
+impl Default for RateCounter {
+    fn default()-> Self {
+        Self::new(Duration::from_secs(1))
+    }
+}
+
+impl Starfield {
+    /// 使用种子`seed`生成`count`颗星星
+    ///
+    /// 相同的种子总是生成相同的星空，因此只需生成一次即可在每一帧保持不变
+    pub fn generate(seed: u64, count: usize)-> Self {
+        let mut rng = Xorshift64::new(seed);
+        let mut next = move || rng.next_f32();
+
+        let stars = (0..count)
+            .map(|_| {
+                let x = next() * 2.0 - 1.0;
+                let y = next() * 2.0 - 1.0;
+                let brightness = 0.3 + next() * 0.7;
+                ([x, y], brightness)
+            })
+            .collect();
+
+        Self {
+            stars,
+            pixel_size: 2.0,
+        }
+    }
+
+    /// 生成绘制星空所需的顶点与索引，顶点坐标为标准化设备坐标
+    ///
+    /// `size`为窗口的大小，以屏幕像素为单位
+    pub fn vertices(&self, size: (u32, u32))-> (Vec<Vertex>, Vec<u32>) {
+        let mut vertices = Vec::with_capacity(self.stars.len() * 4);
+        let mut indices = Vec::with_capacity(self.stars.len() * 6);
+
+        let w = self.pixel_size * 2.0 / size.0 as f32;
+        let h = self.pixel_size * 2.0 / size.1 as f32;
+
+        for ([x, y], brightness) in &self.stars {
+            let color = [*brightness, *brightness, *brightness, 1.0];
+
+            let base = vertices.len() as u32;
+            for (vx, vy) in [(*x, *y), (*x, y - h), (x + w, y - h), (x + w, *y)] {
+                vertices.push(Vertex {
+                    position: [vx, vy, 0.0],
+                    color,
+                });
+            }
+            indices.extend_from_slice(&[base, base + 1, base + 2, base + 2, base + 3, base]);
+        }
+
+        (vertices, indices)
+    }
+}
+
+impl HudText {
+    pub fn new(lines: Vec<String>, color: [f32; 4])-> Self {
+        Self {
+            lines,
+            origin: (8.0, 8.0),
+            pixel_size: 2.0,
+            color,
+        }
+    }
+
+    /// 创建一行标签，`anchor`为标签左边缘中点的位置，以屏幕像素为单位
+    ///
+    /// 标签的大小不随缩放比例变化，始终为`LABEL_PIXEL_SIZE`
+    pub fn label(text: &str, anchor: (f32, f32), color: [f32; 4])-> Self {
+        Self {
+            lines: vec![text.to_string()],
+            origin: (anchor.0, anchor.1 - GLYPH_HEIGHT as f32 * LABEL_PIXEL_SIZE / 2.0),
+            pixel_size: LABEL_PIXEL_SIZE,
+            color,
+        }
+    }
+
+    /// 生成绘制文字所需的顶点与索引，顶点坐标为标准化设备坐标
+    ///
+    /// `size`为窗口的大小，以屏幕像素为单位
+    pub fn vertices(&self, size: (u32, u32))-> (Vec<Vertex>, Vec<u32>) {
+        let mut vertices = Vec::new();
+        let mut indices = Vec::new();
+
+        // 屏幕像素 -> 标准化设备坐标
+        let sx = 2.0 / size.0 as f32;
+        let sy = 2.0 / size.1 as f32;
+        let p = self.pixel_size;
+
+        for (row, line) in self.lines.iter().enumerate() {
+            let top = self.origin.1 + (row * (GLYPH_HEIGHT + LINE_SPACING)) as f32 * p;
+
+            for (col, c) in line.chars().enumerate() {
+                let left = self.origin.0 + (col * (GLYPH_WIDTH + GLYPH_SPACING)) as f32 * p;
+
+                for (y, bits) in glyph(c).iter().enumerate() {
+                    for x in 0..GLYPH_WIDTH {
+                        if bits & (1 << (GLYPH_WIDTH - 1 - x)) == 0 {
+                            continue;
+                        }
+
+                        let x0 = (left + x as f32 * p) * sx - 1.0;
+                        let x1 = x0 + p * sx;
+                        let y0 = 1.0 - (top + y as f32 * p) * sy;
+                        let y1 = y0 - p * sy;
+
+                        let base = vertices.len() as u32;
+                        for (vx, vy) in [(x0, y0), (x0, y1), (x1, y1), (x1, y0)] {
+                            vertices.push(Vertex {
+                                position: [vx, vy, 0.0],
+                                color: self.color,
+                            });
+                        }
+                        indices.extend_from_slice(&[base, base + 1, base + 2, base + 2, base + 3, base]);
+                    }
+                }
+            }
+        }
+
+        (vertices, indices)
+    }
+}
+
+
+
+/// 获取字符的点阵字形
+///
+/// 每个元素为一行，低5位从高到低依次为从左到右的像素
+pub fn glyph(c: char)-> [u8; GLYPH_HEIGHT] {
+    match c.to_ascii_uppercase() {
+        ' ' => [0x00; GLYPH_HEIGHT],
+        '0' => [0x0E, 0x11, 0x13, 0x15, 0x19, 0x11, 0x0E],
+        '1' => [0x04, 0x0C, 0x04, 0x04, 0x04, 0x04, 0x0E],
+        '2' => [0x0E, 0x11, 0x01, 0x02, 0x04, 0x08, 0x1F],
+        '3' => [0x1F, 0x02, 0x04, 0x02, 0x01, 0x11, 0x0E],
+        '4' => [0x02, 0x06, 0x0A, 0x12, 0x1F, 0x02, 0x02],
+        '5' => [0x1F, 0x10, 0x1E, 0x01, 0x01, 0x11, 0x0E],
+        '6' => [0x06, 0x08, 0x10, 0x1E, 0x11, 0x11, 0x0E],
+        '7' => [0x1F, 0x01, 0x02, 0x04, 0x08, 0x08, 0x08],
+        '8' => [0x0E, 0x11, 0x11, 0x0E, 0x11, 0x11, 0x0E],
+        '9' => [0x0E, 0x11, 0x11, 0x0F, 0x01, 0x02, 0x0C],
+        'A' => [0x0E, 0x11, 0x11, 0x1F, 0x11, 0x11, 0x11],
+        'B' => [0x1E, 0x11, 0x11, 0x1E, 0x11, 0x11, 0x1E],
+        'C' => [0x0E, 0x11, 0x10, 0x10, 0x10, 0x11, 0x0E],
+        'D' => [0x1C, 0x12, 0x11, 0x11, 0x11, 0x12, 0x1C],
+        'E' => [0x1F, 0x10, 0x10, 0x1E, 0x10, 0x10, 0x1F],
+        'F' => [0x1F, 0x10, 0x10, 0x1E, 0x10, 0x10, 0x10],
+        'G' => [0x0E, 0x11, 0x10, 0x17, 0x11, 0x11, 0x0F],
+        'H' => [0x11, 0x11, 0x11, 0x1F, 0x11, 0x11, 0x11],
+        'I' => [0x0E, 0x04, 0x04, 0x04, 0x04, 0x04, 0x0E],
+        'J' => [0x07, 0x02, 0x02, 0x02, 0x02, 0x12, 0x0C],
+        'K' => [0x11, 0x12, 0x14, 0x18, 0x14, 0x12, 0x11],
+        'L' => [0x10, 0x10, 0x10, 0x10, 0x10, 0x10, 0x1F],
+        'M' => [0x11, 0x1B, 0x15, 0x15, 0x11, 0x11, 0x11],
+        'N' => [0x11, 0x11, 0x19, 0x15, 0x13, 0x11, 0x11],
+        'O' => [0x0E, 0x11, 0x11, 0x11, 0x11, 0x11, 0x0E],
+        'P' => [0x1E, 0x11, 0x11, 0x1E, 0x10, 0x10, 0x10],
+        'Q' => [0x0E, 0x11, 0x11, 0x11, 0x15, 0x12, 0x0D],
+        'R' => [0x1E, 0x11, 0x11, 0x1E, 0x14, 0x12, 0x11],
+        'S' => [0x0F, 0x10, 0x10, 0x0E, 0x01, 0x01, 0x1E],
+        'T' => [0x1F, 0x04, 0x04, 0x04, 0x04, 0x04, 0x04],
+        'U' => [0x11, 0x11, 0x11, 0x11, 0x11, 0x11, 0x0E],
+        'V' => [0x11, 0x11, 0x11, 0x11, 0x11, 0x0A, 0x04],
+        'W' => [0x11, 0x11, 0x11, 0x15, 0x15, 0x15, 0x0A],
+        'X' => [0x11, 0x11, 0x0A, 0x04, 0x0A, 0x11, 0x11],
+        'Y' => [0x11, 0x11, 0x11, 0x0A, 0x04, 0x04, 0x04],
+        'Z' => [0x1F, 0x01, 0x02, 0x04, 0x08, 0x10, 0x1F],
+        ':' => [0x00, 0x0C, 0x0C, 0x00, 0x0C, 0x0C, 0x00],
+        '.' => [0x00, 0x00, 0x00, 0x00, 0x00, 0x0C, 0x0C],
+        ',' => [0x00, 0x00, 0x00, 0x00, 0x0C, 0x04, 0x08],
+        '-' => [0x00, 0x00, 0x00, 0x1F, 0x00, 0x00, 0x00],
+        '+' => [0x00, 0x04, 0x04, 0x1F, 0x04, 0x04, 0x00],
+        '=' => [0x00, 0x00, 0x1F, 0x00, 0x1F, 0x00, 0x00],
+        '/' => [0x00, 0x01, 0x02, 0x04, 0x08, 0x10, 0x00],
+        '(' => [0x02, 0x04, 0x08, 0x08, 0x08, 0x04, 0x02],
+        ')' => [0x08, 0x04, 0x02, 0x02, 0x02, 0x04, 0x08],
+        _ => UNKNOWN_GLYPH,
+    }
+}
+
+/// 将模拟时间格式化为便于阅读的字符串
+///
+/// 根据时长选择秒、分钟、小时、天或年作为单位，一年按365.25天计算
+pub fn format_duration(time: Duration)-> String {
+    const MINUTE: f64 = 60.0;
+    const HOUR: f64 = 60.0 * MINUTE;
+    const DAY: f64 = 24.0 * HOUR;
+    const YEAR: f64 = 365.25 * DAY;
+
+    let secs = time.as_secs_f64();
+    if secs < MINUTE {
+        format!("{:.1} s", secs)
+    } else if secs < HOUR {
+        format!("{:.1} min", secs / MINUTE)
+    } else if secs < DAY {
+        format!("{:.1} h", secs / HOUR)
+    } else if secs < YEAR {
+        format!("{:.1} d", secs / DAY)
+    } else {
+        format!("{:.2} y", secs / YEAR)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use num_bigfloat::BigFloat;
+
+    #[test]
+    fn rate_counter_averages_over_window() {
+        let start = Instant::now();
+        let mut counter = RateCounter::new(Duration::from_secs(1));
+        assert_eq!(counter.rate(), 0.0);
+
+        // 每10ms一次，共持续2s，只有最近1s内的记录参与平均
+        for i in 0..=200 {
+            counter.tick(start + Duration::from_millis(10 * i));
+        }
+        assert!((counter.rate() - 100.0).abs() < 1e-9, "rate {}", counter.rate());
+        assert_eq!(counter.timestamps.len(), 101);
+    }
+
+    #[test]
+    fn starfield_is_deterministic_for_a_seed() {
+        let a = Starfield::generate(42, 100);
+        let b = Starfield::generate(42, 100);
+        let c = Starfield::generate(43, 100);
+
+        assert_eq!(a.stars, b.stars);
+        assert_ne!(a.stars, c.stars);
+        assert!(a.stars.iter().all(|([x, y], brightness)| {
+            x.abs() <= 1.0 && y.abs() <= 1.0 && (0.3..=1.0).contains(brightness)
+        }));
+    }
+
+    #[test]
+    fn format_duration_picks_unit() {
+        assert_eq!(format_duration(Duration::from_millis(1500)), "1.5 s");
+        assert_eq!(format_duration(Duration::from_secs(90)), "1.5 min");
+        assert_eq!(format_duration(Duration::from_secs(3 * 3600)), "3.0 h");
+        assert_eq!(format_duration(Duration::from_secs(27 * 86400 + 43200)), "27.5 d");
+        assert_eq!(format_duration(Duration::from_secs_f64(2.0 * 365.25 * 86400.0)), "2.00 y");
+    }
+
+    #[test]
+    fn toggling_debug_info_hides_lines() {
+        let camera = Camera {
+            scale_base: BigFloat::from(1),
+            scale: BigFloat::from(2),
+            size: (100, 100),
+            coord: [0.5, 0.0, 0.0],
+        };
+        let mut info = DebugInfo { enabled: true, paused: true, ..DebugInfo::default() };
+
+        let lines = info.lines(&camera).unwrap();
+        assert_eq!(lines[0], "Camera: (0.500, 0.000, 0.000)");
+        assert!(lines.contains(&String::from("Paused: true")));
+
+        // 关闭后不再产生任何文字
+        assert!(!info.toggle());
+        assert!(!info.enabled);
+        assert_eq!(info.lines(&camera), None);
+
+        assert!(info.toggle());
+        assert_eq!(info.lines(&camera).map(|l| l.len()), Some(8));
+    }
+}
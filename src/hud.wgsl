@@ -0,0 +1,22 @@
+struct VertexOutput {
+    @builtin(position) position: vec4<f32>,
+    @location(0) color: vec4<f32>,
+}
+
+// 顶点坐标已经是标准化设备坐标，不受相机与缩放影响
+@vertex
+fn vs_main(
+    @location(0) pos: vec3<f32>,
+    @location(1) color: vec4<f32>
+)-> VertexOutput {
+    var out: VertexOutput;
+    out.position = vec4<f32>(pos.xy, 0.0, 1.0);
+    out.color = color;
+
+    return out;
+}
+
+@fragment
+fn fs_main(in: VertexOutput)-> @location(0) vec4<f32> {
+    return in.color;
+}
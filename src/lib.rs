@@ -0,0 +1,65 @@
+//! 引力模拟器的库部分，包含物理计算、世界、绘制与无窗口运行，不依赖窗口与事件循环
+//!
+//! 在其他程序中直接使用物理计算：
+//!
+//! ```no_run
+//! use gravitation_simulator::physics::*;
+//! use gravitation_simulator::World;
+//! use gravitation_simulator::world::EarthMoonWorld;
+//!
+//! let mut world = EarthMoonWorld::default();
+//! for _ in 0..1000 {
+//!     world.execute(TimeStep::from(std::time::Duration::from_secs(60)));
+//! }
+//! ```
+
+pub mod render;
+pub mod physics;
+pub mod world;
+pub mod hud;
+pub mod sim;
+pub mod capture;
+pub mod rng;
+#[cfg(any(test, feature = "testing"))]
+pub mod testing;
+
+pub use crate::world::World;
+
+
+
+#[cfg(test)]
+mod tests {
+    use crate::physics::*;
+    use num_bigfloat::{ BigFloat, ONE, ZERO };
+    use uuid::Uuid;
+
+    #[test]
+    fn space_executor_runs_through_public_api() {
+        // 两个质量为1、相距2的静止物体，G = 1时各受0.25 N的引力
+        let mut state: Vec<BodySnapshot> = [-1.0, 1.0]
+            .into_iter()
+            .map(|x| BodySnapshot {
+                uid: Uuid::new_v4(),
+                attributes: PhysicalAttributes::new(
+                    Point::from_f64_array([x, 0.0, 0.0]),
+                    Vector::ZERO,
+                    ONE,
+                    ZERO,
+                ).unwrap(),
+            })
+            .collect();
+        let mut objects = Objects::new(state.iter_mut().map(|s| s as &mut dyn PhysicalObject).collect());
+
+        let mut executor = SpaceExecutor::with_g(ONE);
+        executor.step(&mut objects, TimeStep::from_millis_f64(100.0));
+        drop(objects);
+
+        // v = a * t = 0.025，x = a * t^2 / 2 = 0.00125
+        let close = |a: BigFloat, b: f64| (a.to_f64() - b).abs() < 1e-12;
+        assert!(close(state[0].attributes.force.x, 0.25));
+        assert!(close(state[0].attributes.velocity.x, 0.025));
+        assert!(close(state[0].attributes.center.x, -1.0 + 0.00125));
+        assert!(close(state[1].attributes.velocity.x, -0.025));
+        assert!(close(state[1].attributes.center.x, 1.0 - 0.00125));
+    }
+}
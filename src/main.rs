@@ -1,85 +1,194 @@
-mod render;
-mod physics;
-
-use crate::physics::*;
-use crate::render::*;
-use anyhow::Result;
+use gravitation_simulator::render::*;
+use gravitation_simulator::world::*;
+use gravitation_simulator::hud::{ HudText, RateCounter, format_duration };
+use gravitation_simulator::sim::*;
+use gravitation_simulator::capture::{ Recorder, screenshot_path, recording_dir };
+use gravitation_simulator::physics::{ Point, Vector, PhysicalObject, PhysicalAttributes, TimeStep, BodySnapshot, is_bound, tidal_field };
+use anyhow::{ Result, anyhow };
+use uuid::Uuid;
 use winit::{
     window::{ Window, WindowBuilder },
     event_loop::{ EventLoop, ControlFlow },
-    event::{ WindowEvent, Event },
-    dpi::PhysicalPosition,
+    event::{ WindowEvent, Event, ModifiersState },
+    dpi::{ PhysicalPosition, PhysicalSize },
 };
 use pollster::FutureExt;
-use uuid::Uuid;
 use wgpu::*;
-use num_bigfloat::{ BigFloat, ZERO };
-use std::sync::{ Arc, Mutex, atomic::{ AtomicBool, Ordering::* } };
+use num_bigfloat::BigFloat;
+use std::sync::{ Arc, Mutex, RwLock, Condvar };
+use std::sync::atomic::{ AtomicU32, AtomicBool, Ordering };
 use std::time::{ Instant, Duration };
+use std::collections::HashMap;
+use std::path::Path;
 
 type Context = WinitContext;
 
-trait World {
-    /// 返回可绘制的所有物体
-    fn get_drawable_items<'items, 'this: 'items>(&'this self)-> Vec<&'items dyn Drawable>;
+/// 多重采样抗锯齿的采样数，可取1、2、4或8
+const SAMPLE_COUNT: u32 = 4;
 
-    /// 执行物理计算
-    fn execute(&mut self, time: Duration);
+/// 无窗口模式下录制画面的大小，以像素为单位
+const RECORD_SIZE: (u32, u32) = (1280, 720);
 
-    /// 获取默认显示比例的底
-    fn get_default_scale_base(&self)-> BigFloat {
-        "4.0e8".parse().unwrap()
-    }
-}
+/// 按一次WASD时画面平移的距离，以窗口半宽为单位
+const PAN_FRACTION: f64 = 0.1;
+
+/// 按住Shift平移时移动距离的倍数
+const PAN_FAST_FACTOR: f32 = 5.0;
+
+/// 以网格绘制圆形时每个圆形的三角形数
+const CIRCLE_SEGMENTS: u16 = 64;
+
+/// 时间流逝速度为1时物理计算每一步的时长
+const DEFAULT_BASE_STEP: Duration = Duration::from_millis(30);
+
+/// 按实际经过的时间计算步长时，一步最长为基础时长的多少倍(时间流逝速度为1时)
+///
+/// 卡顿(如拖动窗口、在调试器中中断)之后的一步不超过该时长，避免步长过大使数值积分失稳；
+/// 上限随基础时长变化，因此[与]键在两种模式下都能调整精度
+const MAX_WALL_CLOCK_FACTOR: u32 = 4;
+
+/// 时间流逝速度的最大绝对值，避免反复加倍后步长溢出
+const MAX_TIMEWRAP: f64 = 1e9;
+
+/// 速度箭头的长度为天体以当前速度运动该时长(以s为单位)所经过的距离
+const VELOCITY_ARROW_SCALE: &str = "7.0e4";
+
+/// 受力箭头中最长的一个在屏幕上的长度，以窗口半宽为单位
+const FORCE_ARROW_LENGTH: f64 = 0.3;
+
+/// 潮汐力箭头中最长的一个在屏幕上的长度，以窗口半宽为单位
+const TIDAL_ARROW_LENGTH: f64 = 0.1;
+
+/// 在卫星边缘上绘制潮汐力箭头的采样点数
+const TIDAL_SAMPLES: usize = 8;
+
+/// 轨迹最多记录的位置数
+const TRAIL_LENGTH: usize = 2048;
+
+/// 轨迹中相邻记录点之间的最小距离与显示比例的底之比
+const TRAIL_MIN_DISTANCE_RATIO: f64 = 0.0025;
+
+/// 预测轨迹的步数
+const PREDICTION_STEPS: usize = 1000;
+
+/// 预测轨迹时每一步的时长，以s为单位
+const PREDICTION_STEP_SECONDS: u64 = 3600;
+
+/// 拖动天体时，松开后天体获得的速度为在该时长(以s为单位)内走完拖动距离的速度
+const FLING_SECONDS: u64 = 86400;
+
+/// 选中天体时的最小判定半径，以窗口半宽为单位，使很小的天体也容易选中
+const PICK_RADIUS: f64 = 0.03;
+
+/// 数字键4对应的随机星团的天体数与种子
+const CLUSTER_SIZE: usize = 200;
+const CLUSTER_SEED: u64 = 1;
 
+/// 右键生成的天体的质量，以kg为单位
+const SPAWN_MASS: &str = "7.35e22";
 
+/// 右键生成的天体的物理半径，以m为单位
+const SPAWN_RADIUS: &str = "1.737e6";
+
+/// 按下Ctrl+S/Ctrl+L键时保存与读取世界所使用的文件
+#[cfg(feature = "serde")]
+const SAVE_FILE: &str = "world.json";
+
+
+
+/// 创建装箱世界的函数
+type WorldFactory = Box<dyn Fn()-> Box<dyn World + Send>>;
 
 struct WinitContext {
     pub event_loop: EventLoop<()>,
     pub window: Window,
 }
 
-struct Earth {
-    uid: Uuid,
-    phyattr: PhysicalAttributes,
+/// 控制物理线程的运行、暂停与单步执行
+struct StepControl {
+    state: Mutex<StepState>,
+    condvar: Condvar,
+}
+
+#[derive(Default)]
+struct StepState {
+    paused: bool,
+    stopped: bool,
+    pending_steps: usize,
 }
 
-struct Moon {
-    uid: Uuid,
-    phyattr: PhysicalAttributes,
+/// 获取下一帧失败后的处理方式
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum SurfaceRecovery {
+    /// 按窗口当前大小重新配置surface
+    Reconfigure,
+
+    /// 跳过该帧，下一次重绘时重试
+    SkipFrame,
+
+    /// 退出程序
+    Exit,
 }
 
 struct Application {
     renderer: Renderer,
     ctx: Context,
-}
 
-struct EarthMoonWorld {
-    executor: SpaceExecutor,
-    earth: Earth,
-    moon: Moon,
+    /// 创建世界的函数，启动与按R键重置时调用
+    world_factory: WorldFactory,
+
+    /// 预设的场景，按数字键1-9切换到对应的场景
+    scenes: Vec<WorldFactory>,
+
+    /// 按C键保存的世界状态，按X键恢复到该状态
+    snapshot: Option<Vec<BodySnapshot>>,
+
+    /// 相机跟随的天体，每一帧绘制前将相机移动到该天体处
+    follow: Option<Uuid>,
 }
 
+/// 构建`Application`，可以设置窗口标题、初始大小、是否可见以及使用的世界
+///
+/// 只保存配置，调用`build`时才创建窗口
+struct ApplicationBuilder {
+    title: String,
+
+    /// 窗口的初始大小，为`None`时取显示器大小的一半
+    size: Option<(u32, u32)>,
+
+    visible: bool,
+
+    world_factory: WorldFactory,
+
+    scenes: Vec<WorldFactory>,
+}
 
 
 impl WinitContext {
-    pub fn new()-> Result<Self> {
+    pub fn new(title: &str, size: Option<(u32, u32)>, visible: bool)-> Result<Self> {
         let event_loop = EventLoop::new();
         let window = WindowBuilder::new()
-            .with_title("Gravitation Simulator")
+            .with_title(title)
             .with_visible(false)
             .build(&event_loop)?;
 
-        #[cfg(not(any(target_os = "android", target_arch = "wasm")))]
-        {
-            let monitor = window.current_monitor().unwrap();
-            let mut size = monitor.size();
-            size.width /= 2;
-            size.height /= 2;
-            window.set_inner_size(size);
+        match size {
+            Some((width, height)) => window.set_inner_size(PhysicalSize::new(width, height)),
+
+            #[cfg(not(any(target_os = "android", target_arch = "wasm32")))]
+            None => {
+                let monitor = window.current_monitor().unwrap();
+                let mut size = monitor.size();
+                size.width /= 2;
+                size.height /= 2;
+                window.set_inner_size(size);
+            },
+
+            #[cfg(any(target_os = "android", target_arch = "wasm32"))]
+            None => {},
         }
 
-        window.set_visible(true);
+        window.set_visible(visible);
 
         Ok(Self {
             event_loop,
@@ -88,56 +197,247 @@ impl WinitContext {
     }
 }
 
-impl Application {
-    pub async fn new()-> Self {
-        let ctx = WinitContext::new().expect("Unable to build a window");
-        let wsize = ctx.window.inner_size();
+impl StepControl {
+    pub fn new()-> Self {
+        Self {
+            state: Mutex::new(StepState::default()),
+            condvar: Condvar::new(),
+        }
+    }
+
+    /// 切换暂停状态，返回切换后是否处于暂停
+    pub fn toggle_pause(&self)-> bool {
+        let mut state = self.state.lock().unwrap();
+        state.paused = !state.paused;
+        state.pending_steps = 0;
+        self.condvar.notify_all();
+        state.paused
+    }
+
+    /// 请求在暂停时执行一步，未暂停时忽略
+    pub fn request_step(&self) {
+        let mut state = self.state.lock().unwrap();
+        if state.paused {
+            state.pending_steps += 1;
+            self.condvar.notify_all();
+        }
+    }
+
+    /// 是否处于暂停
+    pub fn is_paused(&self)-> bool {
+        self.state.lock().unwrap().paused
+    }
+
+    /// 通知物理线程退出
+    pub fn stop(&self) {
+        self.state.lock().unwrap().stopped = true;
+        self.condvar.notify_all();
+    }
+
+    /// 等待直到可以执行下一步
+    ///
+    /// 暂停且没有单步请求时阻塞，返回`false`表示物理线程应当退出
+    pub fn wait_for_tick(&self)-> bool {
+        let mut state = self.state.lock().unwrap();
+        loop {
+            if state.stopped {
+                return false;
+            }
+
+            if !state.paused {
+                return true;
+            }
+
+            if state.pending_steps > 0 {
+                state.pending_steps -= 1;
+                return true;
+            }
+
+            state = self.condvar.wait(state).unwrap();
+        }
+    }
+}
+
+impl ApplicationBuilder {
+    pub fn new()-> Self {
         Self {
-            renderer: Renderer::new(&ctx.window, (wsize.width, wsize.height)).await,
+            title: String::from("Gravitation Simulator"),
+            size: None,
+            visible: true,
+            world_factory: Box::new(|| Box::new(EarthMoonWorld::default())),
+            scenes: Vec::new(),
+        }
+    }
+
+    /// 设置窗口标题
+    pub fn title(mut self, title: impl Into<String>)-> Self {
+        self.title = title.into();
+        self
+    }
+
+    /// 设置窗口的初始大小，以物理像素为单位
+    pub fn size(mut self, width: u32, height: u32)-> Self {
+        self.size = Some((width, height));
+        self
+    }
+
+    /// 设置窗口创建后是否可见
+    pub fn visible(mut self, visible: bool)-> Self {
+        self.visible = visible;
+        self
+    }
+
+    /// 设置创建世界的函数，启动与按R键重置时调用
+    pub fn world<W, F>(self, factory: F)-> Self
+        where W: World + Send + 'static,
+              F: Fn()-> W + 'static,
+    {
+        self.boxed_world(Box::new(move || Box::new(factory())))
+    }
+
+    /// 设置创建装箱世界的函数，可以在运行时决定创建哪一种世界
+    pub fn boxed_world(mut self, factory: WorldFactory)-> Self {
+        self.world_factory = factory;
+        self
+    }
+
+    /// 添加一个预设场景，第n个场景可以按数字键n切换，最多9个
+    pub fn scene<W, F>(mut self, factory: F)-> Self
+        where W: World + Send + 'static,
+              F: Fn()-> W + 'static,
+    {
+        self.scenes.push(Box::new(move || Box::new(factory())));
+        self
+    }
+
+    /// 创建窗口与渲染器
+    pub async fn build(self)-> Application {
+        let ctx = WinitContext::new(&self.title, self.size, self.visible).expect("Unable to build a window");
+        let wsize = ctx.window.inner_size();
+        Application {
+            renderer: Renderer::new(&ctx.window, (wsize.width, wsize.height), SAMPLE_COUNT).await,
             ctx,
+            world_factory: self.world_factory,
+            scenes: self.scenes,
+            snapshot: None,
+            follow: None,
         }
     }
+}
 
+impl Application {
     pub async fn run(mut self) {
         const FRAME_TIME: Duration = Duration::from_micros(33333);
 
-        let world_factory = || {
-            EarthMoonWorld::default()
-        };
+        let world_factory = self.world_factory;
+        let scenes = self.scenes;
+        // 当前的场景在`scenes`中的下标，为`None`时使用`world_factory`创建的世界
+        let mut scene = None::<usize>;
 
         let world = Arc::new(Mutex::new(world_factory()));
-        let run_flag = Arc::new(AtomicBool::new(true));
+        // 物理线程在每一步之后写入绘制所需的状态，绘制时只读取该状态而不锁住世界
+        let frames = Arc::new(RwLock::new(FrameHistory::new(world.lock().unwrap().draw_frame())));
+        // 为`true`时绘制在最近两步的状态之间插值后的画面
+        let mut interpolate = true;
+        let control = Arc::new(StepControl::new());
         let timewrap = Arc::new(Mutex::new(1.0f64));
+        // 物理计算每一步的基础时长，每一步实际的时长为其与时间流逝速度的乘积
+        let base_step = Arc::new(Mutex::new(DEFAULT_BASE_STEP));
+        // 物理线程每秒执行的步数，由物理线程写入，绘制HUD时读取
+        let steps_per_second = Arc::new(AtomicU32::new(0));
+        // 为`true`时物理线程每`FRAME_TIME`最多执行一步，为`false`时尽可能快地执行
+        let limit_steps = Arc::new(AtomicBool::new(true));
+        // 为`true`时每一步的时长取距上一步实际经过的时间，使模拟时间与现实时间同步；
+        // 为`false`(默认)时每一步固定为`base_step`
+        let real_time = Arc::new(AtomicBool::new(false));
+        // 调试构建中监视着色器源码，修改后在下一帧重新加载
+        #[cfg(all(debug_assertions, feature = "hot-reload"))]
+        let shader_watcher = ShaderWatcher::new()
+            .map_err(|e| log::warn!("Unable to watch shaders: {}", e))
+            .ok();
+        let mut frame_counter = RateCounter::default();
+        let mut take_screenshot = false;
+        // 录制中时每绘制一帧保存一帧画面
+        let mut recorder = None::<Recorder>;
         let mut y_accumulate = 0.0;
         let mut last_pos = PhysicalPosition::<f64> {
             x: 0.0,
             y: 0.0,
         };
         let mut drag = None::<(PhysicalPosition<f64>, [f32; 3])>;
+        // 左键按在天体上时被拖动的天体与按下位置，松开时据此施加冲量
+        let mut fling = None::<(Uuid, Point)>;
+        // 最近一次左键点击选中的天体，按Delete键时移除
+        let mut selected = None::<Uuid>;
+        // 按N键设为潮汐力主星的天体与当时世界的引力常数，选中其他天体时在其上绘制该主星产生的潮汐力
+        let mut tidal_primary = None::<(Uuid, BigFloat)>;
+        // 当前按在屏幕上的触摸点，以`touch.id`为键
+        let mut touches = HashMap::<u64, PhysicalPosition<f64>>::new();
+        // 双指缩放开始时两指间的距离与缩放比例
+        let mut pinch = None::<(f64, BigFloat)>;
+        let mut show_velocity = false;
+        let mut show_force = false;
+        let mut show_potential = false;
+        let mut show_grid = false;
+        // 为`true`时按质量为天体着色，而不使用天体自身的颜色
+        let mut color_by_mass = false;
+        let mut modifiers = ModifiersState::empty();
+        // 各天体的轨迹，以天体的UID为键
+        let mut trails = HashMap::<Uuid, OrbitTrail>::new();
+        // 开启轨迹预测时，各天体的预测轨迹
+        let mut predictions = None::<Vec<OrbitTrail>>;
+        // 上一次设置的窗口标题，标题不变时不重复设置
+        let mut title = String::new();
 
         self.renderer.scale_base = world.lock().unwrap().get_default_scale_base();
-        self.renderer.debug = true;
+        self.renderer.debug.base_step = DEFAULT_BASE_STEP;
+        self.renderer.debug.real_time = real_time.load(Ordering::Relaxed);
+        self.renderer.debug.enabled = true;
 
         std::thread::Builder::new()
             .name("Physics Executor".to_owned())
             .spawn({
                 let world = Arc::clone(&world);
-                let run_flag = Arc::clone(&run_flag);
+                let frames = Arc::clone(&frames);
+                let control = Arc::clone(&control);
                 let timewrap = Arc::clone(&timewrap);
+                let base_step = Arc::clone(&base_step);
+                let steps_per_second = Arc::clone(&steps_per_second);
+                let limit_steps = Arc::clone(&limit_steps);
+                let real_time = Arc::clone(&real_time);
                 move || {
-                    while run_flag.load(Acquire) {
+                    let mut step_counter = RateCounter::default();
+                    // 上一步开始的时刻，暂停后为`None`
+                    let mut last_step = None::<Instant>;
+
+                    // 暂停时阻塞在此处，恢复后从暂停前的状态继续运行
+                    while control.wait_for_tick() {
                         let t1 = Instant::now();
 
-                        world.lock().unwrap().execute(
-                            Duration::from_millis(
-                                (30.0 * (*timewrap.lock().unwrap())) as u64
-                            )
-                        );
+                        // 暂停中单步执行以及恢复后的第一步没有可参考的上一步，使用基础时长
+                        let step = match last_step {
+                            Some(last) if real_time.load(Ordering::Relaxed) => {
+                                wall_clock_step(t1 - last, *base_step.lock().unwrap())
+                            },
+                            _ => *base_step.lock().unwrap(),
+                        };
+                        last_step = (!control.is_paused()).then_some(t1);
+
+                        let frame = {
+                            let mut world = world.lock().unwrap();
+                            world.execute(TimeStep::scaled(step, *timewrap.lock().unwrap()));
+                            world.draw_frame()
+                        };
+                        frames.write().unwrap().push(frame, Instant::now());
 
                         let t = t1.elapsed();
 
-                        if t < FRAME_TIME {
-                            //std::thread::sleep(FRAME_TIME - t);
+                        step_counter.tick(Instant::now());
+                        steps_per_second.store(step_counter.rate().round() as u32, Ordering::Relaxed);
+
+                        // 限制步率时，提前完成的步等待到该步的时长用完，避免空转占满一个CPU核心
+                        if limit_steps.load(Ordering::Relaxed) {
+                            sleep_accurately(limiter_delay(t, FRAME_TIME));
                         }
                     }
                 }
@@ -149,56 +449,176 @@ impl Application {
                 Event::RedrawRequested(id)
                     if id == self.ctx.window.id()
                 => {
-                    match self.renderer.surface.get_current_texture() {
+                    #[cfg(all(debug_assertions, feature = "hot-reload"))]
+                    if shader_watcher.as_ref().is_some_and(|w| w.take_changed()) {
+                        match self.renderer.reload_shaders() {
+                            Ok(()) => log::info!("Shaders reloaded"),
+                            Err(e) => log::error!("{}", e),
+                        }
+                    }
+
+                    match self.renderer.get_current_texture() {
                         Ok(surface_texture) => {
                             let view = surface_texture.texture.create_view(&TextureViewDescriptor::default());
-                            let mut encoder = self.renderer.device.create_command_encoder(&Default::default());
-                            let _render_pass = encoder.begin_render_pass(&RenderPassDescriptor {
-                                label: Some("Earth render pass"),
-                                color_attachments: &[Some(RenderPassColorAttachment {
-                                    view: &view,
-                                    resolve_target: None,
-                                    ops: Operations {
-                                        load: LoadOp::Clear(Color {
-                                            r: 0.05,
-                                            g: 0.05,
-                                            b: 0.05,
-                                            a: 1.00,
-                                        }),
-                                        store: true,
-                                    },
-                                })],
-                                depth_stencil_attachment: None,
-                            });
-
-                            drop(_render_pass);
-                            self.renderer.queue.submit(std::iter::once(encoder.finish()));
-
-                            world
-                                .lock()
-                                .unwrap()
-                                .get_drawable_items()
-                                .into_iter()
-                                .for_each(|i| {
-                                    i.draw(RenderContext {
-                                        view: &view,
-                                        renderer: &self.renderer,
-                                        encoder: Some(self.renderer.device.create_command_encoder(&CommandEncoderDescriptor::default())),
-                                    });
+
+                            // 复制后立即释放读锁，避免阻塞物理线程写入
+                            let mut frame = {
+                                let frames = frames.read().unwrap();
+                                if interpolate {
+                                    frames.interpolated(Instant::now())
+                                } else {
+                                    frames.current.clone()
+                                }
+                            };
+                            if color_by_mass {
+                                frame.color_by_mass();
+                            }
+
+                            // 跟随的天体被合并或移除后停止跟随
+                            if let Some(uid) = self.follow {
+                                match frame.bodies.iter().find(|b| b.uid == uid) {
+                                    Some(body) => self.renderer.center_on(body.center),
+                                    None => self.follow = None,
+                                }
+                            }
+
+                            update_trails(&mut trails, &frame.bodies, self.renderer.scale_base);
+
+                            // 开启时以引力势作为背景，在其他物体之前绘制
+                            let potential = show_potential
+                                .then(|| PotentialField::new(frame.bodies.iter().map(|b| (b.center, b.mass))));
+
+                            let grid = show_grid.then(|| Grid::new([0.5, 0.5, 0.5, 0.35]));
+
+                            let mut items: Vec<&dyn Drawable> = potential.iter().map(|p| p as &dyn Drawable).collect();
+                            items.extend(grid.iter().map(|g| g as &dyn Drawable));
+                            items.extend(trails.values().map(|t| t as &dyn Drawable));
+                            items.extend(frame.bodies.iter().map(|b| b as &dyn Drawable));
+
+                            // 在选中的天体外绘制一个圆环
+                            let selection = selected
+                                .and_then(|uid| frame.bodies.iter().find(|b| b.uid == uid))
+                                .map(|b| {
+                                    let r = self.renderer.scale_length(b.radius);
+                                    Circle::ring(self.renderer.scale_from_point(b.center), r * 1.3, r * 1.6, [0.9, 0.9, 0.9, 0.8])
                                 });
+                            items.extend(selection.iter().map(|c| c as &dyn Drawable));
+
+                            // 在潮汐力的主星外绘制一个橙色圆环
+                            let primary_ring = tidal_primary
+                                .and_then(|(uid, _)| frame.bodies.iter().find(|b| b.uid == uid))
+                                .map(|b| {
+                                    let r = self.renderer.scale_length(b.radius);
+                                    Circle::ring(self.renderer.scale_from_point(b.center), r * 1.3, r * 1.6, [0.95, 0.6, 0.2, 0.8])
+                                });
+                            items.extend(primary_ring.iter().map(|c| c as &dyn Drawable));
+
+                            // 在有名称的天体右侧显示名称，重叠时不做处理
+                            let labels: Vec<_> = frame.bodies
+                                .iter()
+                                .filter_map(|b| b.label.as_ref().map(|text| HudText::label(
+                                    text,
+                                    self.renderer.label_anchor(b.center, b.radius),
+                                    [0.85, 0.85, 0.85, 0.9],
+                                )))
+                                .collect();
+                            items.extend(labels.iter().map(|l| l as &dyn Drawable));
+
+                            // 在窗口标题中显示已经过的模拟时间
+                            let new_title = format!("Gravitation Simulator - T+{}", format_duration(frame.elapsed));
+                            if new_title != title {
+                                self.ctx.window.set_title(&new_title);
+                                title = new_title;
+                            }
 
-                            surface_texture.present();
-                        },
+                            let mut arrows = Vec::new();
 
-                        Err(SurfaceError::Lost) => {
-                            self.renderer.resize(self.renderer.size);
-                        },
+                            // 开启时在每个天体上绘制速度箭头
+                            if show_velocity {
+                                arrows.extend(frame.bodies.iter().map(|b| Arrow::new(
+                                    b.center,
+                                    b.velocity,
+                                    VELOCITY_ARROW_SCALE.parse().unwrap(),
+                                    [0.9, 0.9, 0.2, 1.0],
+                                )));
+                            }
 
-                        Err(SurfaceError::OutOfMemory) => {
-                            *control_flow = ControlFlow::Exit;
+                            // 开启时在每个天体上绘制受力箭头
+                            // 不同天体受力的数量级相差很大，按最大的受力归一化，使最长的箭头为固定的屏幕长度
+                            if show_force {
+                                let forces: Vec<_> = frame.bodies.iter().map(|b| b.force).collect();
+                                // 着色器中坐标会再乘一次缩放比例，因此屏幕上的长度与缩放比例的平方成正比
+                                let length = self.renderer.scale_base / self.renderer.scale.pow(&BigFloat::from(2))
+                                    * BigFloat::from(FORCE_ARROW_LENGTH);
+
+                                if let Some(scale) = Arrow::normalized_scale(&forces, length) {
+                                    arrows.extend(frame.bodies.iter().zip(forces).map(|(b, f)| Arrow::new(
+                                        b.center,
+                                        f,
+                                        scale,
+                                        [0.9, 0.3, 0.3, 1.0],
+                                    )));
+                                }
+                            }
+
+                            // 选中了潮汐力的主星之外的天体时，在其边缘上绘制主星产生的潮汐力
+                            if let Some((uid, g)) = tidal_primary {
+                                let primary = frame.bodies.iter().find(|b| b.uid == uid);
+                                let satellite = selected
+                                    .filter(|s| *s != uid)
+                                    .and_then(|s| frame.bodies.iter().find(|b| b.uid == s));
+
+                                if let (Some(primary), Some(satellite)) = (primary, satellite) {
+                                    let length = self.renderer.scale_base / self.renderer.scale.pow(&BigFloat::from(2))
+                                        * BigFloat::from(TIDAL_ARROW_LENGTH);
+                                    arrows.extend(tidal_arrows(primary, satellite, g, length));
+                                }
+                            }
+
+                            items.extend(arrows.iter().map(|a| a as &dyn Drawable));
+                            if let Some(predictions) = predictions.as_ref() {
+                                items.extend(predictions.iter().map(|p| p as &dyn Drawable));
+                            }
+
+                            frame_counter.tick(Instant::now());
+                            self.renderer.debug.fps = frame_counter.rate();
+                            self.renderer.debug.steps_per_second = steps_per_second.load(Ordering::Relaxed);
+                            self.renderer.render_frame(&view, &items);
+
+                            // 按F12后在绘制下一帧时保存截图
+                            if take_screenshot {
+                                take_screenshot = false;
+                                let path = screenshot_path();
+                                match self.renderer.capture_frame(&items).and_then(|s| s.save_png(&path)) {
+                                    Ok(()) => log::info!("Screenshot saved to {}", path.display()),
+                                    Err(e) => log::error!("Failed to save screenshot: {}", e),
+                                }
+                            }
+
+                            if let Some(active) = recorder.as_mut() {
+                                if let Err(e) = self.renderer.capture_frame(&items).and_then(|s| active.record(&s)) {
+                                    log::error!("Recording stopped: {}", e);
+                                    recorder = None;
+                                }
+                            }
+                            drop(items);
+
+                            surface_texture.present();
                         },
 
-                        Err(_) => (),
+                        Err(e) => match surface_recovery(&e) {
+                            SurfaceRecovery::Reconfigure => {
+                                let size = self.ctx.window.inner_size();
+                                self.renderer.resize((size.width, size.height));
+                            },
+                            SurfaceRecovery::SkipFrame => {
+                                log::warn!("Skipping frame: {}", e);
+                            },
+                            SurfaceRecovery::Exit => {
+                                log::error!("Exiting: {}", e);
+                                *control_flow = ControlFlow::Exit;
+                            },
+                        },
                     }
                 },
 
@@ -210,6 +630,7 @@ impl Application {
                         MouseScrollDelta,
                         MouseButton,
                         ElementState,
+                        TouchPhase,
                         VirtualKeyCode,
                         KeyboardInput,
                     };
@@ -254,72 +675,432 @@ impl Application {
                             }
                         },
 
-                        // 
-                        WindowEvent::MouseInput { state, button, .. }
-                            if button == MouseButton::Left
-                        => {
+                        // 鼠标左键按在天体上时拖动以抛出该天体，否则拖动以移动视角
+                        WindowEvent::MouseInput { state, button: MouseButton::Left, .. } => {
                             match state {
-                                ElementState::Pressed => drag = Some((last_pos, self.renderer.basic_bind_group_data.camera_coord)),
-                                ElementState::Released => drag = None,
+                                ElementState::Pressed => {
+                                    let point = self.renderer.point_from_screen(last_pos);
+                                    let min_radius = self.renderer.scale_base / self.renderer.scale * BigFloat::from(PICK_RADIUS);
+                                    selected = body_at(&frames.read().unwrap().current.bodies, point, min_radius);
+                                    match selected {
+                                        Some(uid) => fling = Some((uid, point)),
+                                        None => drag = Some((last_pos, self.renderer.basic_bind_group_data.camera_coord)),
+                                    }
+                                },
+
+                                ElementState::Released => {
+                                    drag = None;
+
+                                    // 拖动的向量除以`FLING_SECONDS`即为速度的改变量
+                                    if let Some((uid, start)) = fling.take() {
+                                        let delta_v = (self.renderer.point_from_screen(last_pos) - start)
+                                            / BigFloat::from(FLING_SECONDS);
+
+                                        let mut world_ref = world.lock().unwrap();
+                                        let mass = world_ref.bodies()
+                                            .iter()
+                                            .find(|b| b.get_uid() == uid)
+                                            .map(|b| b.get_physical_attributes().mass);
+                                        if let Some(mass) = mass {
+                                            world_ref.apply_impulse(uid, delta_v * mass);
+                                            frames.write().unwrap().reset(world_ref.draw_frame());
+                                            warn_if_escaping(&*world_ref, uid);
+
+                                            if predictions.is_some() {
+                                                predictions = Some(predicted_trails(&*world_ref));
+                                            }
+                                        }
+                                    }
+                                },
+                            }
+                        },
+
+                        // 鼠标右键在光标处生成一个新的天体
+                        WindowEvent::MouseInput { state: ElementState::Pressed, button: MouseButton::Right, .. } => {
+                            let center = self.renderer.point_from_screen(last_pos);
+                            let mut body = Body::new(
+                                SPAWN_MASS.parse().unwrap(),
+                                center,
+                                Vector::ZERO,
+                                SPAWN_RADIUS.parse().unwrap(),
+                                [0.9, 0.5, 0.2, 1.0],
+                            ).unwrap();
+                            // 显示半径随当前视野大小变化，保证新天体总是可见
+                            body.radius = self.renderer.scale_base / self.renderer.scale * BigFloat::from(0.05);
+
+                            let mut world_ref = world.lock().unwrap();
+                            world_ref.bodies_mut().push(body);
+                            frames.write().unwrap().reset(world_ref.draw_frame());
+
+                            // 显示预测轨迹时同时预测新天体的轨迹
+                            if predictions.is_some() {
+                                predictions = Some(predicted_trails(&*world_ref));
                             }
                         },
 
                         WindowEvent::CursorLeft {..} => {
                             drag = None;
+                            fling = None;
                         },
 
                         WindowEvent::CursorMoved { position, .. } => {
                             // 这里检测按下拖动时调整视角
                             if let Some((drag, cc)) = drag.as_ref() {
-                                let x = ((position.x - drag.x) / 100.0 / self.renderer.scale.to_f64()) as f32;
-                                let y = ((position.y - drag.y) / 100.0 / self.renderer.scale.to_f64()) as f32;
-                                self.renderer.move_camera([cc[0] + x, cc[1] + y, 0.0]);
+                                drag_camera(&mut self.renderer, *drag, position, *cc);
                             }
                             last_pos = position;
                         },
 
+                        // 单指拖动移动视角，双指捏合调整缩放比例
                         WindowEvent::Touch(touch) => {
-                            // TODO 添加对触摸的支持
-                            //dbg!(touch);
+                            let count_changed = match touch.phase {
+                                TouchPhase::Started => {
+                                    touches.insert(touch.id, touch.location);
+                                    true
+                                },
+
+                                TouchPhase::Moved => {
+                                    touches.insert(touch.id, touch.location);
+
+                                    if let Some((start, cc)) = drag.as_ref() {
+                                        drag_camera(&mut self.renderer, *start, touch.location, *cc);
+                                    } else if let Some((start_distance, start_scale)) = pinch {
+                                        if let Some(distance) = pinch_distance(&touches) {
+                                            self.renderer.scale(start_scale * BigFloat::from(distance / start_distance));
+                                        }
+                                    }
+                                    false
+                                },
+
+                                TouchPhase::Ended | TouchPhase::Cancelled => {
+                                    touches.remove(&touch.id);
+                                    true
+                                },
+                            };
+
+                            // 触摸点数量变化时，以剩余的触摸点重新开始拖动或缩放
+                            if count_changed {
+                                drag = None;
+                                pinch = None;
+                                match touches.len() {
+                                    1 => {
+                                        let start = *touches.values().next().unwrap();
+                                        drag = Some((start, self.renderer.basic_bind_group_data.camera_coord));
+                                    },
+
+                                    2 => {
+                                        pinch = pinch_distance(&touches)
+                                            .filter(|d| *d > 0.0)
+                                            .map(|d| (d, self.renderer.scale));
+                                    },
+
+                                    _ => {},
+                                }
+                            }
+                        },
+
+                        WindowEvent::ModifiersChanged(state) => {
+                            modifiers = state;
                         },
 
                         WindowEvent::KeyboardInput {
                             input: KeyboardInput {
-                                state,
-                                virtual_keycode,
+                                state: ElementState::Pressed,
+                                virtual_keycode: Some(k),
                                 ..
                             },
                             ..
-                        } if state == ElementState::Pressed => {
-                            if let Some(k) = virtual_keycode {
-                                match k {
-                                    // 按下上键提高时间流逝速度(每次乘2)
-                                    VirtualKeyCode::Up => {
-                                        let mut tw = timewrap.lock().unwrap();
-                                        *tw *= 2.0;
-                                        self.renderer.timewrap = *tw;
-                                        self.renderer.print_msg();
-                                    },
+                        } => {
+                            match k {
+                                // 按下上键提高时间流逝速度(每次乘2，不超过`MAX_TIMEWRAP`)
+                                VirtualKeyCode::Up => {
+                                    let mut tw = timewrap.lock().unwrap();
+                                    *tw = doubled_timewrap(*tw);
+                                    self.renderer.debug.timewrap = *tw;
+                                },
 
-                                    // 按下下键降低时间流逝速度(每次除以2)
-                                    VirtualKeyCode::Down => {
-                                        let mut tw = timewrap.lock().unwrap();
-                                        *tw /= 2.0;
-                                        self.renderer.timewrap = *tw;
-                                        self.renderer.print_msg();
-                                    },
+                                // 按下下键降低时间流逝速度(每次除以2)
+                                VirtualKeyCode::Down => {
+                                    let mut tw = timewrap.lock().unwrap();
+                                    *tw /= 2.0;
+                                    self.renderer.debug.timewrap = *tw;
+                                },
+
+                                // 按下]键增大每一步的基础时长(每次乘2)
+                                VirtualKeyCode::RBracket => {
+                                    let mut step = base_step.lock().unwrap();
+                                    *step = doubled_step(*step);
+                                    self.renderer.debug.base_step = *step;
+                                },
+
+                                // 按下[键减小每一步的基础时长(每次除以2)，用于提高精度
+                                VirtualKeyCode::LBracket => {
+                                    let mut step = base_step.lock().unwrap();
+                                    *step = (*step / 2).max(Duration::from_nanos(1));
+                                    self.renderer.debug.base_step = *step;
+                                },
+
+                                // 按下U键切换是否限制物理计算的步率
+                                VirtualKeyCode::U => {
+                                    let limited = !limit_steps.load(Ordering::Relaxed);
+                                    limit_steps.store(limited, Ordering::Relaxed);
+                                    log::info!("Step rate limiter {}", if limited { "enabled" } else { "disabled" });
+                                },
+
+                                // 按下J键切换每一步的时长取实际经过的时间还是固定的基础时长
+                                VirtualKeyCode::J => {
+                                    let enabled = !real_time.load(Ordering::Relaxed);
+                                    real_time.store(enabled, Ordering::Relaxed);
+                                    self.renderer.debug.real_time = enabled;
+                                },
+
+                                // 按下T键使时间倒流或恢复正向流逝
+                                VirtualKeyCode::T => {
+                                    let mut tw = timewrap.lock().unwrap();
+                                    *tw = -*tw;
+                                    self.renderer.debug.timewrap = *tw;
+                                },
+
+                                // 按下M键切换以网格或以正方形绘制圆形
+                                VirtualKeyCode::M => {
+                                    self.renderer.circle_segments = match self.renderer.circle_segments {
+                                        Some(_) => None,
+                                        None => Some(CIRCLE_SEGMENTS),
+                                    };
+                                },
+
+                                // 按下B键显示或隐藏星空背景
+                                VirtualKeyCode::B => {
+                                    self.renderer.starfield = !self.renderer.starfield;
+                                },
+
+                                // 按下E键以背景色显示或隐藏引力势
+                                VirtualKeyCode::E => {
+                                    show_potential = !show_potential;
+                                },
+
+                                // 按下O键显示或隐藏参考网格与坐标轴
+                                VirtualKeyCode::O => {
+                                    show_grid = !show_grid;
+                                },
+
+                                // 按下K键切换是否按质量为天体着色
+                                VirtualKeyCode::K => {
+                                    color_by_mass = !color_by_mass;
+                                },
+
+                                // 按下H键显示或隐藏左上角的调试信息
+                                VirtualKeyCode::H => {
+                                    self.renderer.debug.toggle();
+                                },
+
+                                // 按下I键切换是否在最近两步的状态之间插值绘制
+                                VirtualKeyCode::I => {
+                                    interpolate = !interpolate;
+                                },
 
-                                    // 按下R重置世界
-                                    VirtualKeyCode::R => {
+                                // 按下P键预测各天体之后的轨迹，再次按下时隐藏
+                                VirtualKeyCode::P => {
+                                    predictions = match predictions {
+                                        Some(_) => None,
+                                        None => Some(predicted_trails(&*world.lock().unwrap())),
+                                    };
+                                },
+
+                                // 按下Tab使相机依次跟随各个天体
+                                VirtualKeyCode::Tab => {
+                                    self.follow = next_follow_target(self.follow, &frames.read().unwrap().current.bodies);
+                                },
+
+                                // 按下Esc停止跟随
+                                VirtualKeyCode::Escape => {
+                                    self.follow = None;
+                                },
+
+                                // 按下Delete移除选中的天体
+                                VirtualKeyCode::Delete => {
+                                    if let Some(uid) = selected.take() {
                                         let mut world_ref = world.lock().unwrap();
-                                        *world_ref = world_factory();
-                                        self.renderer.scale(BigFloat::from(1.0));
-                                        self.renderer.move_camera([0.0, 0.0, 0.0]);
-                                        self.renderer.scale_base = world_ref.get_default_scale_base();
-                                    },
+                                        if world_ref.remove(uid) {
+                                            frames.write().unwrap().reset(world_ref.draw_frame());
+                                            if self.follow == Some(uid) {
+                                                self.follow = None;
+                                            }
+                                            if tidal_primary.is_some_and(|(p, _)| p == uid) {
+                                                tidal_primary = None;
+                                            }
+
+                                            if predictions.is_some() {
+                                                predictions = Some(predicted_trails(&*world_ref));
+                                            }
+                                        }
+                                    }
+                                },
 
-                                    _ => {},
-                                }
+                                // 按下N键将选中的天体设为潮汐力的主星，没有选中天体时取消
+                                VirtualKeyCode::N => {
+                                    tidal_primary = selected.map(|uid| (uid, world.lock().unwrap().executor().g));
+                                },
+
+                                // 按下空格暂停或继续
+                                VirtualKeyCode::Space => {
+                                    self.renderer.debug.paused = control.toggle_pause();
+                                },
+
+                                // 暂停时按下右键前进一步
+                                VirtualKeyCode::Right => {
+                                    control.request_step();
+                                },
+
+                                // 按下R重置世界与视角，并停止跟随天体
+                                VirtualKeyCode::R => {
+                                    let mut world_ref = world.lock().unwrap();
+                                    *world_ref = reset_world(scene, &world_factory, &scenes);
+                                    frames.write().unwrap().reset(world_ref.draw_frame());
+                                    trails.clear();
+                                    if predictions.is_some() {
+                                        predictions = Some(predicted_trails(&*world_ref));
+                                    }
+                                    self.renderer.set_camera(reset_camera(self.renderer.camera(), &*world_ref));
+                                    self.follow = None;
+                                },
+
+                                // 按下Home只重置视角，不影响世界
+                                VirtualKeyCode::Home => {
+                                    self.renderer.set_camera(reset_camera(self.renderer.camera(), &*world.lock().unwrap()));
+                                    self.follow = None;
+                                },
+
+                                // 按下C保存当前的世界状态
+                                VirtualKeyCode::C => {
+                                    self.snapshot = Some(world.lock().unwrap().snapshot());
+                                    log::info!("Snapshot captured");
+                                },
+
+                                // 按下X恢复到按C时保存的世界状态
+                                VirtualKeyCode::X => {
+                                    match &self.snapshot {
+                                        Some(snapshot) => {
+                                            let mut world_ref = world.lock().unwrap();
+                                            world_ref.restore(snapshot.clone());
+                                            frames.write().unwrap().reset(world_ref.draw_frame());
+                                            trails.clear();
+                                            if predictions.is_some() {
+                                                predictions = Some(predicted_trails(&*world_ref));
+                                            }
+                                        },
+                                        None => log::warn!("No snapshot captured"),
+                                    }
+                                },
+
+                                // 按下F调整视角使所有天体都可见
+                                VirtualKeyCode::F => {
+                                    if let Some((min, max)) = world.lock().unwrap().bounds() {
+                                        self.renderer.fit_bounds(min, max);
+                                    }
+                                },
+
+                                // 按下V显示或隐藏速度箭头
+                                VirtualKeyCode::V => {
+                                    show_velocity = !show_velocity;
+                                },
+
+                                // 按下G显示或隐藏受力箭头
+                                VirtualKeyCode::G => {
+                                    show_force = !show_force;
+                                },
+
+                                // 按下Ctrl+S保存世界
+                                #[cfg(feature = "serde")]
+                                VirtualKeyCode::S if modifiers.ctrl() => {
+                                    match world.lock().unwrap().save_to(Path::new(SAVE_FILE)) {
+                                        Ok(()) => log::info!("World saved to {}", SAVE_FILE),
+                                        Err(e) => log::error!("Failed to save world: {}", e),
+                                    }
+                                },
+
+                                // 按下Ctrl+L读取世界
+                                #[cfg(feature = "serde")]
+                                VirtualKeyCode::L if modifiers.ctrl() => {
+                                    let mut world_ref = world.lock().unwrap();
+                                    match world_ref.load_from(Path::new(SAVE_FILE)) {
+                                        Ok(()) => log::info!("World loaded from {}", SAVE_FILE),
+                                        Err(e) => log::error!("Failed to load world: {}", e),
+                                    }
+                                    frames.write().unwrap().reset(world_ref.draw_frame());
+                                    trails.clear();
+                                    if predictions.is_some() {
+                                        predictions = Some(predicted_trails(&*world_ref));
+                                    }
+                                },
+
+                                // 按下WASD平移视角，同时按住Shift时移动距离更大，平移时停止跟随天体
+                                VirtualKeyCode::W | VirtualKeyCode::A | VirtualKeyCode::S | VirtualKeyCode::D => {
+                                    let (dx, dy) = match k {
+                                        VirtualKeyCode::W => (0.0, 1.0),
+                                        VirtualKeyCode::S => (0.0, -1.0),
+                                        VirtualKeyCode::A => (-1.0, 0.0),
+                                        _ => (1.0, 0.0),
+                                    };
+                                    let mut step = pan_step(self.renderer.scale.to_f64());
+                                    if modifiers.shift() {
+                                        step *= PAN_FAST_FACTOR;
+                                    }
+                                    let [x, y, z] = self.renderer.basic_bind_group_data.camera_coord;
+                                    self.renderer.move_camera([x - dx * step, y + dy * step, z]);
+                                    self.follow = None;
+                                },
+
+                                // 按下F12保存截图
+                                VirtualKeyCode::F12 => {
+                                    take_screenshot = true;
+                                },
+
+                                // 按下F10开始或停止录制
+                                VirtualKeyCode::F10 => {
+                                    match recorder.take() {
+                                        Some(stopped) => log::info!(
+                                            "Recorded {} frames to {}",
+                                            stopped.frame_count(),
+                                            stopped.dir().display(),
+                                        ),
+
+                                        None => match Recorder::new(recording_dir()) {
+                                            Ok(started) => {
+                                                log::info!("Recording to {}", started.dir().display());
+                                                recorder = Some(started);
+                                            },
+                                            Err(e) => log::error!("Failed to start recording: {}", e),
+                                        },
+                                    }
+                                },
+
+                                // 按下数字键1-9切换到对应的预设场景
+                                VirtualKeyCode::Key1 | VirtualKeyCode::Key2 | VirtualKeyCode::Key3 |
+                                VirtualKeyCode::Key4 | VirtualKeyCode::Key5 | VirtualKeyCode::Key6 |
+                                VirtualKeyCode::Key7 | VirtualKeyCode::Key8 | VirtualKeyCode::Key9 => {
+                                    let index = scene_index(k);
+                                    match scenes.get(index) {
+                                        Some(factory) => {
+                                            let mut world_ref = world.lock().unwrap();
+                                            *world_ref = factory();
+                                            frames.write().unwrap().reset(world_ref.draw_frame());
+                                            self.renderer.set_camera(reset_camera(self.renderer.camera(), &*world_ref));
+                                            if predictions.is_some() {
+                                                predictions = Some(predicted_trails(&*world_ref));
+                                            }
+
+                                            // 快照与跟随的天体属于之前的场景
+                                            scene = Some(index);
+                                            trails.clear();
+                                            self.snapshot = None;
+                                            self.follow = None;
+                                        },
+                                        None => log::warn!("No scene bound to key {}", index + 1),
+                                    }
+                                },
+
+                                _ => {},
                             }
                         },
 
@@ -335,143 +1116,360 @@ impl Application {
             }
 
             if *control_flow == ControlFlow::Exit {
-                run_flag.store(false, Release);
+                control.stop();
             }
         });
     }
 }
 
-impl Earth {
-    pub fn new(center: Point, velocity: Vector)-> Self {
-        let uid = Uuid::new_v4();
-        Self {
-            phyattr: PhysicalAttributes {
-                center,
-                velocity,
-                force: Vector::ZERO,
-                mass: "5.965e24".parse().unwrap(),
-            },
-            uid,
-        }
-    }
+/// 从`from`拖动到`to`时，以拖动开始时的相机坐标`camera`为基准移动相机
+fn drag_camera(renderer: &mut Renderer, from: PhysicalPosition<f64>, to: PhysicalPosition<f64>, camera: [f32; 3]) {
+    let x = ((to.x - from.x) / 100.0 / renderer.scale.to_f64()) as f32;
+    let y = ((to.y - from.y) / 100.0 / renderer.scale.to_f64()) as f32;
+    renderer.move_camera([camera[0] + x, camera[1] + y, 0.0]);
 }
 
-impl PhysicalObject for Earth {
-    fn get_uid(&self)-> Uuid {
-        self.uid
-    }
+/// 预测世界中各天体之后的轨迹，生成用于绘制的半透明轨迹
+///
+/// 越远的未来越透明
+fn predicted_trails(world: &dyn World)-> Vec<OrbitTrail> {
+    let mut paths = world.predict(PREDICTION_STEPS, Duration::from_secs(PREDICTION_STEP_SECONDS).into());
+
+    world.bodies()
+        .iter()
+        .filter_map(|body| {
+            let path = paths.remove(&body.get_uid())?;
+            let [r, g, b, _] = body.color;
+            let mut trail = OrbitTrail::new(path.len(), BigFloat::from(0), [r, g, b, 0.4]);
+            // 轨迹中越早记录的点越透明，因此从最远的未来开始记录
+            path.into_iter().rev().for_each(|p| trail.push(p));
+            Some(trail)
+        })
+        .collect()
+}
 
-    fn get_physical_attributes(&self)-> &PhysicalAttributes {
-        &self.phyattr
-    }
+/// 将时间流逝速度加倍，绝对值不超过`MAX_TIMEWRAP`并保留符号
+fn doubled_timewrap(timewrap: f64)-> f64 {
+    (timewrap * 2.0).clamp(-MAX_TIMEWRAP, MAX_TIMEWRAP)
+}
 
-    fn get_physical_attributes_mut(&mut self)-> &mut PhysicalAttributes {
-        &mut self.phyattr
-    }
+/// 将每一步的基础时长加倍，溢出时保持原值
+fn doubled_step(step: Duration)-> Duration {
+    step.checked_mul(2).unwrap_or(step)
 }
 
-impl Drawable for Earth {
-    fn draw(&self, ctx: RenderContext) {
-        Circle {
-            center: ctx.renderer.scale_from_point(self.phyattr.center.clone()),
-            radius: 0.2 * ctx.renderer.scale.to_f32(),
-            fill_color: [0.1, 0.1, 0.95, 1.0],
-        }.draw(ctx)
+/// 根据获取下一帧时的错误决定处理方式
+///
+/// surface丢失或与窗口不再匹配(如DPI变化、切换显示器)时重新配置，超时时跳过该帧，内存不足时退出
+fn surface_recovery(error: &SurfaceError)-> SurfaceRecovery {
+    match error {
+        SurfaceError::Lost | SurfaceError::Outdated => SurfaceRecovery::Reconfigure,
+        SurfaceError::Timeout => SurfaceRecovery::SkipFrame,
+        SurfaceError::OutOfMemory => SurfaceRecovery::Exit,
     }
 }
 
-impl Moon {
-    pub fn new(center: Point, velocity: Vector)-> Self {
-        let uid = Uuid::new_v4();
-        Self {
-            phyattr: PhysicalAttributes {
-                center,
-                velocity,
-                force: Vector::ZERO,
-                mass: "7.35e22".parse().unwrap(),
-            },
-            uid,
-        }
+/// 按实际经过的时间`elapsed`计算物理计算一步的时长(时间流逝速度为1时)
+///
+/// 最长为基础时长`base_step`的`MAX_WALL_CLOCK_FACTOR`倍
+fn wall_clock_step(elapsed: Duration, base_step: Duration)-> Duration {
+    elapsed.min(base_step.saturating_mul(MAX_WALL_CLOCK_FACTOR))
+}
+
+/// 在`satellite`边缘上均匀分布的`TIDAL_SAMPLES`个点处绘制`primary`产生的潮汐力箭头
+///
+/// 第一个采样点位于朝向主星的一侧，使两天体连线上的两点总在采样点中。
+/// 箭头按最大的潮汐力归一化，最长的箭头长度为`length`(以m为单位)
+fn tidal_arrows(primary: &DrawState, satellite: &DrawState, g: BigFloat, length: BigFloat)-> Vec<Arrow> {
+    let Ok(attributes) = PhysicalAttributes::new(primary.center, primary.velocity, primary.mass, primary.radius) else {
+        return Vec::new();
+    };
+
+    let direction = primary.center - satellite.center;
+    let base = direction.y.to_f64().atan2(direction.x.to_f64());
+
+    let points: Vec<Point> = (0..TIDAL_SAMPLES)
+        .map(|i| {
+            let angle = base + i as f64 / TIDAL_SAMPLES as f64 * std::f64::consts::TAU;
+            satellite.center + Vector {
+                x: satellite.radius * BigFloat::from(angle.cos()),
+                y: satellite.radius * BigFloat::from(angle.sin()),
+                z: BigFloat::from(0),
+            }
+        })
+        .collect();
+
+    let tides: Vec<Vector> = points
+        .iter()
+        .map(|p| tidal_field(&attributes, *p, satellite.center, g))
+        .collect();
+
+    match Arrow::normalized_scale(&tides, length) {
+        Some(scale) => points
+            .into_iter()
+            .zip(tides)
+            .map(|(p, t)| Arrow::new(p, t, scale, [0.3, 0.85, 0.9, 1.0]))
+            .collect(),
+        None => Vec::new(),
     }
 }
 
-impl PhysicalObject for Moon {
-    fn get_uid(&self)-> Uuid {
-        self.uid
+/// 限制步率时，耗时`elapsed`的一步之后还需等待的时长，使每一步总共占用`frame`
+fn limiter_delay(elapsed: Duration, frame: Duration)-> Duration {
+    frame.saturating_sub(elapsed)
+}
+
+/// 等待`duration`
+///
+/// `std::thread::sleep`通常会多睡眠约1ms，因此先睡眠到剩余`SPIN_THRESHOLD`，再忙等到结束
+fn sleep_accurately(duration: Duration) {
+    const SPIN_THRESHOLD: Duration = Duration::from_millis(1);
+
+    let deadline = Instant::now() + duration;
+    if duration > SPIN_THRESHOLD {
+        std::thread::sleep(duration - SPIN_THRESHOLD);
     }
 
-    fn get_physical_attributes(&self)-> &PhysicalAttributes {
-        &self.phyattr
+    while Instant::now() < deadline {
+        std::hint::spin_loop();
     }
+}
 
-    fn get_physical_attributes_mut(&mut self)-> &mut PhysicalAttributes {
-        &mut self.phyattr
+/// 将相机移回原点，并恢复默认的缩放比例与世界的默认显示比例的底
+fn reset_camera(camera: Camera, world: &dyn World)-> Camera {
+    Camera {
+        scale_base: world.get_default_scale_base(),
+        scale: BigFloat::from(1.0),
+        coord: [0.0, 0.0, 0.0],
+        ..camera
     }
 }
 
-impl Drawable for Moon {
-    fn draw(&self, ctx: RenderContext) {
-        Circle {
-            center: ctx.renderer.scale_from_point(self.phyattr.center.clone()),
-            radius: 0.12 * ctx.renderer.scale.to_f32(),
-            fill_color: [0.25, 0.25, 0.25, 1.0],
-        }.draw(ctx)
+/// 按R键重置时创建新的世界，切换过场景时重新创建当前场景，否则调用`world_factory`
+fn reset_world(scene: Option<usize>, world_factory: &WorldFactory, scenes: &[WorldFactory])-> Box<dyn World + Send> {
+    scene.map_or(world_factory, |i| &scenes[i])()
+}
+
+/// 数字键1-9对应的场景下标，从0开始
+fn scene_index(key: winit::event::VirtualKeyCode)-> usize {
+    use winit::event::VirtualKeyCode;
+
+    match key {
+        VirtualKeyCode::Key1 => 0,
+        VirtualKeyCode::Key2 => 1,
+        VirtualKeyCode::Key3 => 2,
+        VirtualKeyCode::Key4 => 3,
+        VirtualKeyCode::Key5 => 4,
+        VirtualKeyCode::Key6 => 5,
+        VirtualKeyCode::Key7 => 6,
+        VirtualKeyCode::Key8 => 7,
+        _ => 8,
     }
 }
 
-impl Default for EarthMoonWorld {
-    fn default()-> Self {
-        Self {
-            executor: SpaceExecutor::default(),
+/// 按一次WASD时相机移动的距离，使画面平移`PAN_FRACTION`个窗口半宽
+///
+/// 着色器中相机坐标会乘两次缩放比例，因此距离与缩放比例的平方成反比
+fn pan_step(scale: f64)-> f32 {
+    (PAN_FRACTION / scale.powi(2)) as f32
+}
 
-            earth: Earth::new(
-                Point { x: ZERO, y: ZERO, z: ZERO },
+/// 相机跟随的下一个天体
+///
+/// 未跟随任何天体或跟随的天体已不存在时返回第一个天体，跟随最后一个天体时回到第一个
+fn next_follow_target(current: Option<Uuid>, bodies: &[DrawState])-> Option<Uuid> {
+    let next = current
+        .and_then(|uid| bodies.iter().position(|b| b.uid == uid))
+        .map_or(0, |i| (i + 1) % bodies.len());
 
-                Vector::ZERO
-            ),
+    bodies.get(next).map(|b| b.uid)
+}
 
-            // 月球以近地点为起点
-            moon: Moon::new(
-                Point {
-                    x: ZERO,
-                    y: "3.57e8".parse().unwrap(),
-                    z: ZERO
-                },
+/// 天体不再被其余天体中质量最大者束缚时输出警告
+fn warn_if_escaping(world: &dyn World, uid: Uuid) {
+    let bodies = world.bodies();
+    let Some(body) = bodies.iter().find(|b| b.get_uid() == uid) else {
+        return;
+    };
+
+    let primary = bodies
+        .iter()
+        .filter(|b| b.get_uid() != uid)
+        .max_by(|a, b| {
+            a.get_physical_attributes().mass
+                .partial_cmp(&b.get_physical_attributes().mass)
+                .unwrap_or(std::cmp::Ordering::Equal)
+        });
 
-                Vector {
-                    x: BigFloat::from(1022),
-                    y: ZERO,
-                    z: ZERO,
-                }
-            ),
+    if let Some(primary) = primary {
+        if !is_bound(primary.get_physical_attributes(), body.get_physical_attributes(), world.executor().g) {
+            log::warn!("Body {} is on an escape trajectory", uid);
         }
     }
 }
 
-impl World for EarthMoonWorld {
-    fn get_drawable_items<'items, 'this: 'items>(&'this self)-> Vec<&'items dyn Drawable> {
-        vec![&self.earth, &self.moon]
-    }
-
-    fn execute(&mut self, time: Duration) {
-        let mut objects = Objects::new(vec![&mut self.earth, &mut self.moon]);
-        self.executor.execute_force(&mut objects, time);
-        self.executor.execute_displacement(&mut objects, time);
+/// 查找`point`处的天体，返回其UID
+///
+/// 判定半径取天体显示半径与`min_radius`中较大者，多个天体重叠时选择中心离`point`最近的一个
+fn body_at(bodies: &[DrawState], point: Point, min_radius: BigFloat)-> Option<Uuid> {
+    bodies
+        .iter()
+        .map(|b| (b, (b.center - point).model()))
+        .filter(|(b, d)| *d <= b.radius.max(&min_radius))
+        .min_by(|(_, a), (_, b)| a.partial_cmp(b).unwrap_or(std::cmp::Ordering::Equal))
+        .map(|(b, _)| b.uid)
+}
 
-        drop(objects);
+/// 将各天体当前的位置记录到其轨迹中，并移除已不存在的天体的轨迹
+///
+/// 相邻记录点之间的最小距离与显示比例的底成正比，使不同尺度的世界中轨迹的疏密相近
+fn update_trails(trails: &mut HashMap<Uuid, OrbitTrail>, bodies: &[DrawState], scale_base: BigFloat) {
+    trails.retain(|uid, _| bodies.iter().any(|b| b.uid == *uid));
+
+    for body in bodies {
+        let [r, g, b, _] = body.color;
+        trails
+            .entry(body.uid)
+            .or_insert_with(|| OrbitTrail::new(
+                TRAIL_LENGTH,
+                scale_base * BigFloat::from(TRAIL_MIN_DISTANCE_RATIO),
+                [r, g, b, 0.6],
+            ))
+            .push(body.center);
     }
+}
 
-    fn get_default_scale_base(&self)-> BigFloat {
-        "3.80e8".parse().unwrap()
+/// 恰好有两个触摸点时，返回两者间的距离
+fn pinch_distance(touches: &HashMap<u64, PhysicalPosition<f64>>)-> Option<f64> {
+    let mut points = touches.values();
+    match (points.next(), points.next(), points.next()) {
+        (Some(a), Some(b), None) => Some(((a.x - b.x).powi(2) + (a.y - b.y).powi(2)).sqrt()),
+        _ => None,
     }
 }
 
+/// 无窗口模式下执行模拟，并将每一步执行后的画面保存到`dir`
+fn record_headless(world: &mut dyn World, options: &HeadlessOptions, dir: &Path)-> Result<Vec<Vec<BodySnapshot>>> {
+    let mut renderer = Renderer::new_headless(RECORD_SIZE, SAMPLE_COUNT).block_on()?;
+    renderer.scale_base = world.get_default_scale_base();
+
+    let mut recorder = Recorder::new(dir)?;
+    let frames = run_headless_with(world, options.steps, options.dt, |world| {
+        let labels: Vec<_> = world.draw_frame().bodies
+            .iter()
+            .filter_map(|b| b.label.as_ref().map(|text| HudText::label(
+                text,
+                renderer.label_anchor(b.center, b.radius),
+                [0.85, 0.85, 0.85, 0.9],
+            )))
+            .collect();
+
+        let mut items = world.get_drawable_items();
+        items.extend(labels.iter().map(|l| l as &dyn Drawable));
+        recorder.record(&renderer.capture_frame(&items)?)?;
+        Ok(())
+    })?;
+
+    log::info!("Recorded {} frames to {}", recorder.frame_count(), dir.display());
+    Ok(frames)
+}
+
+/// 本程序使用的配置，初始为地月系统，数字键1-5切换预设场景
+fn default_builder()-> ApplicationBuilder {
+    ApplicationBuilder::new()
+        .title("Gravitation Simulator")
+        .visible(true)
+        .world(EarthMoonWorld::default)
+        .scene(EarthMoonWorld::default)
+        .scene(SolarSystemWorld::default)
+        .scene(FigureEightWorld::default)
+        .scene(|| RandomClusterWorld::new(CLUSTER_SIZE, CLUSTER_SEED))
+        .scene(AnalyticTwoBodyWorld::default)
+}
 
+/// 从命令行参数中移除`--size WxH`，返回指定的窗口大小
+fn take_window_size(args: &mut Vec<String>)-> Result<Option<(u32, u32)>> {
+    let Some(index) = args.iter().position(|arg| arg == "--size") else {
+        return Ok(None);
+    };
+
+    let value = args.get(index + 1).ok_or_else(|| anyhow!("Missing value for --size"))?;
+    let (width, height) = value.split_once('x').ok_or_else(|| anyhow!("Invalid value for --size: {}", value))?;
+    let size = (width.parse()?, height.parse()?);
+    args.drain(index..index + 2);
+    Ok(Some(size))
+}
 
 fn main() {
     env_logger::init();
 
-    let app = Application::new().block_on();
+    let usage = "Usage: gravitation-simulator [--size WxH] [--headless --steps N [--dt MS] [--csv PATH] [--json PATH] [--record DIR]]";
+    let mut args: Vec<String> = std::env::args().skip(1).collect();
+    let size = match take_window_size(&mut args) {
+        Ok(size) => size,
+        Err(e) => {
+            log::error!("{}", e);
+            eprintln!("{}", usage);
+            std::process::exit(1);
+        },
+    };
+
+    match HeadlessOptions::from_args(args) {
+        Ok(Some(options)) => {
+            let mut world = EarthMoonWorld::default();
+            let frames = match &options.record {
+                Some(dir) => match record_headless(&mut world, &options, dir) {
+                    Ok(frames) => frames,
+                    Err(e) => {
+                        log::error!("Failed to record: {}", e);
+                        std::process::exit(1);
+                    },
+                },
+                None => run_headless(&mut world, options.steps, options.dt),
+            };
+            log::info!("Headless simulation finished after {} steps", frames.len());
+
+            if let Some(path) = &options.csv {
+                match export_csv(path, &frames) {
+                    Ok(()) => log::info!("Trajectory exported to {}", path.display()),
+                    Err(e) => log::error!("Failed to export trajectory: {}", e),
+                }
+            }
+
+            #[cfg(feature = "serde")]
+            if let Some(path) = &options.json {
+                match export_json(path, &frames) {
+                    Ok(()) => log::info!("Trajectory exported to {}", path.display()),
+                    Err(e) => log::error!("Failed to export trajectory: {}", e),
+                }
+            }
+
+            // 未指定导出文件时以JSON格式输出到标准输出，便于脚本处理
+            #[cfg(feature = "serde")]
+            if options.csv.is_none() && options.json.is_none() {
+                match serde_json::to_string(&frames) {
+                    Ok(json) => println!("{}", json),
+                    Err(e) => log::error!("Failed to serialize frames: {}", e),
+                }
+            }
+
+            return;
+        },
+
+        Ok(None) => {},
+
+        Err(e) => {
+            log::error!("{}", e);
+            eprintln!("{}", usage);
+            std::process::exit(1);
+        },
+    }
+
+    let mut builder = default_builder();
+    if let Some((width, height)) = size {
+        builder = builder.size(width, height);
+    }
+    let app = builder.build().block_on();
 
     app.run().block_on();
 }
@@ -482,7 +1480,206 @@ async fn wasm_main() {
     std::panic::set_hook(Box::new(console_error_panic_hook::hook));
     console_log::init_with_level(log::Level::Info);
 
-    let app = Application::new().await;
+    let app = default_builder().build().await;
 
     app.run().await;
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::AtomicUsize;
+
+    #[test]
+    fn doubling_timewrap_is_clamped() {
+        assert_eq!(doubled_timewrap(1.0), 2.0);
+        assert_eq!(doubled_timewrap(MAX_TIMEWRAP), MAX_TIMEWRAP);
+        assert_eq!(doubled_timewrap(-MAX_TIMEWRAP), -MAX_TIMEWRAP);
+    }
+
+    #[test]
+    fn doubling_step_keeps_value_on_overflow() {
+        assert_eq!(doubled_step(Duration::from_millis(30)), Duration::from_millis(60));
+        assert_eq!(doubled_step(Duration::MAX), Duration::MAX);
+    }
+
+    #[test]
+    fn one_step_request_runs_exactly_one_step() {
+        let control = Arc::new(StepControl::new());
+        let executed = Arc::new(AtomicUsize::new(0));
+        control.toggle_pause();
+
+        let physics = {
+            let control = Arc::clone(&control);
+            let executed = Arc::clone(&executed);
+            std::thread::spawn(move || {
+                while control.wait_for_tick() {
+                    executed.fetch_add(1, Ordering::SeqCst);
+                }
+            })
+        };
+
+        control.request_step();
+        while executed.load(Ordering::SeqCst) == 0 {
+            std::thread::yield_now();
+        }
+        std::thread::sleep(Duration::from_millis(50));
+        assert_eq!(executed.load(Ordering::SeqCst), 1);
+
+        control.stop();
+        physics.join().unwrap();
+    }
+
+    #[test]
+    fn tab_cycles_follow_target() {
+        let bodies = EarthMoonWorld::default().draw_frame().bodies;
+        let (earth, moon) = (bodies[0].uid, bodies[1].uid);
+
+        assert_eq!(next_follow_target(None, &bodies), Some(earth));
+        assert_eq!(next_follow_target(Some(earth), &bodies), Some(moon));
+        assert_eq!(next_follow_target(Some(moon), &bodies), Some(earth));
+        assert_eq!(next_follow_target(Some(Uuid::new_v4()), &bodies), Some(earth));
+        assert_eq!(next_follow_target(Some(earth), &[]), None);
+    }
+
+    #[test]
+    fn pan_step_shrinks_with_scale_squared() {
+        assert_eq!(pan_step(1.0), PAN_FRACTION as f32);
+        assert_eq!(pan_step(2.0), (PAN_FRACTION / 4.0) as f32);
+        assert_eq!(pan_step(0.5), (PAN_FRACTION * 4.0) as f32);
+        assert!((pan_step(10.0) - (PAN_FRACTION / 100.0) as f32).abs() < 1e-9);
+    }
+
+    #[test]
+    fn camera_reset_leaves_world_untouched() {
+        let mut world = EarthMoonWorld::default();
+        world.execute(TimeStep::from_millis_f64(30.0));
+        let before = world.snapshot();
+        let elapsed = world.elapsed();
+
+        let moved = Camera {
+            scale_base: BigFloat::from(1.0),
+            scale: BigFloat::from(3.0),
+            size: (64, 48),
+            coord: [0.5, -0.25, 0.0],
+        };
+        let camera = reset_camera(moved, &world);
+
+        assert_eq!(camera.coord, [0.0, 0.0, 0.0]);
+        assert_eq!(camera.scale, BigFloat::from(1.0));
+        assert_eq!(camera.scale_base, world.get_default_scale_base());
+        assert_eq!(camera.size, (64, 48));
+        assert_eq!(world.snapshot(), before);
+        assert_eq!(world.elapsed(), elapsed);
+    }
+
+    #[test]
+    fn limiter_waits_for_the_rest_of_the_frame() {
+        let frame = Duration::from_millis(30);
+        assert_eq!(limiter_delay(Duration::from_millis(10), frame), Duration::from_millis(20));
+        assert_eq!(limiter_delay(Duration::ZERO, frame), frame);
+        assert_eq!(limiter_delay(frame, frame), Duration::ZERO);
+        assert_eq!(limiter_delay(Duration::from_millis(45), frame), Duration::ZERO);
+    }
+
+    #[test]
+    fn builder_stores_config() {
+        let builder = ApplicationBuilder::new()
+            .title("Embedded")
+            .size(640, 480)
+            .visible(false)
+            .world(SolarSystemWorld::default)
+            .scene(FigureEightWorld::default);
+
+        assert_eq!(builder.title, "Embedded");
+        assert_eq!(builder.size, Some((640, 480)));
+        assert!(!builder.visible);
+        assert_eq!(builder.scenes.len(), 1);
+        assert_eq!((builder.world_factory)().get_drawable_items().len(), SolarSystemWorld::default().get_drawable_items().len());
+        assert_eq!((builder.scenes[0])().get_drawable_items().len(), 3);
+    }
+
+    #[test]
+    fn builder_defaults() {
+        let builder = default_builder();
+        assert_eq!(builder.title, "Gravitation Simulator");
+        assert_eq!(builder.size, None);
+        assert!(builder.visible);
+        assert_eq!(builder.scenes.len(), 5);
+    }
+
+    #[test]
+    fn window_size_is_taken_from_args() {
+        let mut args: Vec<String> = ["--size", "800x600", "--headless"].iter().map(|s| s.to_string()).collect();
+        assert_eq!(take_window_size(&mut args).unwrap(), Some((800, 600)));
+        assert_eq!(args, vec!["--headless"]);
+
+        let mut args = vec![String::from("--size"), String::from("800")];
+        assert!(take_window_size(&mut args).is_err());
+
+        let mut args = Vec::new();
+        assert_eq!(take_window_size(&mut args).unwrap(), None);
+    }
+
+    #[test]
+    fn reset_invokes_world_factory() {
+        let calls = Arc::new(AtomicUsize::new(0));
+        let counter = calls.clone();
+        let world_factory: WorldFactory = Box::new(move || {
+            counter.fetch_add(1, Ordering::SeqCst);
+            Box::new(FigureEightWorld::default())
+        });
+        let scenes: Vec<WorldFactory> = vec![Box::new(|| Box::new(EarthMoonWorld::default()))];
+
+        let world = reset_world(None, &world_factory, &scenes);
+        assert_eq!(calls.load(Ordering::SeqCst), 1);
+        assert_eq!(world.get_drawable_items().len(), 3);
+
+        reset_world(None, &world_factory, &scenes);
+        assert_eq!(calls.load(Ordering::SeqCst), 2);
+
+        // 切换到场景后重置的是该场景
+        let world = reset_world(Some(0), &world_factory, &scenes);
+        assert_eq!(calls.load(Ordering::SeqCst), 2);
+        assert_eq!(world.get_drawable_items().len(), 2);
+    }
+
+    #[test]
+    fn digit_key_selects_scene() {
+        use winit::event::VirtualKeyCode;
+
+        let scenes = default_builder().scenes;
+        let bodies = |key| scenes.get(scene_index(key)).map(|factory| factory().bodies().len());
+
+        assert_eq!(bodies(VirtualKeyCode::Key1), Some(2));
+        assert_eq!(bodies(VirtualKeyCode::Key2), Some(7));
+        assert_eq!(bodies(VirtualKeyCode::Key3), Some(3));
+        assert_eq!(bodies(VirtualKeyCode::Key4), Some(CLUSTER_SIZE));
+        assert_eq!(bodies(VirtualKeyCode::Key6), None);
+        assert_eq!(scene_index(VirtualKeyCode::Key9), 8);
+    }
+
+    #[test]
+    fn wall_clock_step_is_capped_relative_to_base_step() {
+        let ms = Duration::from_millis;
+        assert_eq!(wall_clock_step(ms(16), DEFAULT_BASE_STEP), ms(16));
+        assert_eq!(wall_clock_step(ms(120), ms(30)), ms(120));
+
+        // 卡顿之后的一步不超过基础时长的`MAX_WALL_CLOCK_FACTOR`倍
+        assert_eq!(wall_clock_step(Duration::from_secs(5), ms(30)), ms(120));
+        assert_eq!(wall_clock_step(ms(121), ms(30)), ms(120));
+
+        // 减小基础时长同时降低上限，按[键在实时模式下也能提高精度
+        assert_eq!(wall_clock_step(ms(16), ms(2)), ms(8));
+        assert_eq!(wall_clock_step(Duration::from_secs(5), ms(60)), ms(240));
+        assert_eq!(wall_clock_step(Duration::ZERO, ms(30)), Duration::ZERO);
+    }
+
+    #[test]
+    fn surface_errors_map_to_recovery() {
+        assert_eq!(surface_recovery(&SurfaceError::Lost), SurfaceRecovery::Reconfigure);
+        assert_eq!(surface_recovery(&SurfaceError::Outdated), SurfaceRecovery::Reconfigure);
+        assert_eq!(surface_recovery(&SurfaceError::Timeout), SurfaceRecovery::SkipFrame);
+        assert_eq!(surface_recovery(&SurfaceError::OutOfMemory), SurfaceRecovery::Exit);
+    }
+}
@@ -1,28 +1,40 @@
 use std::ops::*;
-use num_bigfloat::{ BigFloat, ZERO, ONE, TWO };
+use num_bigfloat::{ BigFloat, ZERO, ONE, TWO, PI };
 use uuid::Uuid;
 use std::time::Duration;
+use std::collections::{ HashMap, HashSet };
 use std::fmt::{ Display, Formatter, Result as FmtResult };
+#[cfg(feature = "serde")]
+use serde::{ Serialize, Deserialize };
 
 
 
 /// 表示三维空间中的一个点的坐标
 #[derive(Clone, Copy, PartialEq, Debug)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct Point {
+    #[cfg_attr(feature = "serde", serde(with = "num_bigfloat::serde::str"))]
     pub x: BigFloat,
+    #[cfg_attr(feature = "serde", serde(with = "num_bigfloat::serde::str"))]
     pub y: BigFloat,
+    #[cfg_attr(feature = "serde", serde(with = "num_bigfloat::serde::str"))]
     pub z: BigFloat,
 }
 
 /// 一个三维向量
 #[derive(Clone, Copy, PartialEq, Debug)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct Vector {
+    #[cfg_attr(feature = "serde", serde(with = "num_bigfloat::serde::str"))]
     pub x: BigFloat,
+    #[cfg_attr(feature = "serde", serde(with = "num_bigfloat::serde::str"))]
     pub y: BigFloat,
+    #[cfg_attr(feature = "serde", serde(with = "num_bigfloat::serde::str"))]
     pub z: BigFloat,
 }
 
-#[derive(Clone, Debug)]
+#[derive(Clone, PartialEq, Debug)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct PhysicalAttributes {
     /// 物体的重心
     pub center: Point,
@@ -38,7 +50,65 @@ pub struct PhysicalAttributes {
     /// 物体的质量，以Kg为单位
     ///
     /// 在计算中会和`force`参与计算加速度
+    #[cfg_attr(feature = "serde", serde(with = "num_bigfloat::serde::str"))]
     pub mass: BigFloat,
+
+    /// 物体的半径，以m为单位
+    ///
+    /// 用于碰撞检测
+    #[cfg_attr(feature = "serde", serde(with = "num_bigfloat::serde::str"))]
+    pub radius: BigFloat,
+
+    /// 物体所带的电荷量，以C(库仑)为单位
+    ///
+    /// 仅`ChargeExecutor`会计算电荷间的作用力，默认为0
+    #[cfg_attr(feature = "serde", serde(with = "num_bigfloat::serde::str", default))]
+    pub charge: BigFloat,
+}
+
+/// 某一时刻单个物体的状态
+///
+/// 用于保存与恢复世界
+#[derive(Clone, PartialEq, Debug)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct BodySnapshot {
+    pub uid: Uuid,
+    pub attributes: PhysicalAttributes,
+}
+
+/// 解析或校验物理量时的错误
+#[derive(Clone, PartialEq, Debug)]
+pub enum PhysicsError {
+    /// 字符串不是合法的数字，或者是NaN、无穷大
+    InvalidNumber(String),
+
+    /// 质量不为正数
+    NonPositiveMass(BigFloat),
+
+    /// 半径为负数
+    NegativeRadius(BigFloat),
+}
+
+/// 二体问题中卫星相对于主星的轨道根数
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct OrbitalElements {
+    /// 半长轴，以m为单位
+    ///
+    /// 双曲线轨道时为负数
+    pub semi_major_axis: BigFloat,
+
+    /// 离心率
+    ///
+    /// 0为圆轨道，小于1为椭圆轨道，不小于1时卫星会脱离主星
+    pub eccentricity: BigFloat,
+
+    /// 轨道倾角，即轨道平面与xy平面的夹角，以弧度为单位
+    pub inclination: BigFloat,
+
+    /// 轨道周期，以s为单位
+    ///
+    /// 非椭圆轨道时为`None`
+    pub period: Option<BigFloat>,
 }
 
 pub struct Objects<'a: 'this, 'this> {
@@ -46,10 +116,401 @@ pub struct Objects<'a: 'this, 'this> {
     _marker: std::marker::PhantomData<&'this Self>,
 }
 
+#[derive(Debug)]
+pub struct SpaceExecutor {
+    /// 引力常数，以m^3 / (kg * s^2)为单位
+    ///
+    /// 默认取6.67259 x 10^-11，使用自然单位制时可设为1
+    pub g: BigFloat,
+
+    /// 软化长度，以m为单位
+    ///
+    /// 计算引力时分母取`r^2 + softening^2`，避免两物体极度接近时引力趋于无穷，默认为0
+    pub softening: BigFloat,
+
+    /// 被固定的物体的UID
+    ///
+    /// 这些物体仍参与引力计算，但在`execute_displacement`中速度被置为0且不发生位移
+    pub anchored: HashSet<Uuid>,
+
+    /// 为`true`时在每次`execute_force`中记录两物体间的最小距离到`closest_approach`
+    ///
+    /// 记录需要额外计算一遍两两之间的距离，默认为`false`
+    pub track_closest: bool,
+
+    /// 历次`execute_force`中观测到的两物体间的最小距离及这两个物体的UID
+    ///
+    /// 仅在`track_closest`为`true`时记录，可用于碰撞预警与调整步长，置为`None`以重新开始记录
+    pub closest_approach: Option<(Uuid, Uuid, BigFloat)>,
+
+    /// 速度上限，以m/s为单位
+    ///
+    /// 不为`None`时，每一步计算位移后将超过上限的速度按原方向缩小到上限并输出警告，
+    /// 避免步长过大导致速度发散，默认为`None`
+    pub max_velocity: Option<BigFloat>,
+
+    /// 为`true`时，调试构建中发现物体的位置或速度为NaN或无穷大时panic，否则只输出错误
+    ///
+    /// 默认为`false`
+    pub halt_on_non_finite: bool,
+
+    /// `execute_force`中复用的缓冲，避免每一步重新分配
+    scratch: ForceScratch,
+}
+
+/// 计算受力时使用的临时缓冲
+#[derive(Debug, Default)]
+struct ForceScratch {
+    centers: Vec<Point>,
+    masses: Vec<BigFloat>,
+    forces: Vec<Vector>,
+}
+
+/// 使用速度Verlet(Velocity-Verlet)积分的执行器
+///
+/// 受力计算与`SpaceExecutor`相同，但位移计算使用上一步与当前的加速度的平均值更新速度，
+/// 长时间运行时的能量漂移远小于欧拉法
+#[derive(Debug, Default)]
+pub struct VerletExecutor {
+    /// 用于计算受力的执行器
+    pub gravity: SpaceExecutor,
+
+    /// 上一步中各物体的加速度，以物体的UID为键
+    accelerations: HashMap<Uuid, Vector>,
+}
+
+/// 带有碰撞检测的执行器
+///
+/// 每一步位移计算后，若两物体重心间的距离不超过两者半径之和，则将两者合并为一个物体。
+/// 合并时质量相加、动量守恒，较轻的物体被并入较重的物体中
+#[derive(Debug, Default)]
+pub struct CollisionExecutor {
+    /// 用于计算引力的执行器
+    pub gravity: SpaceExecutor,
+
+    /// 已被合并、需要从世界中移除的物体的UID
+    removed: Vec<Uuid>,
+}
+
+/// 碰撞时相互弹开的执行器
+///
+/// 每一步位移计算后，若两物体重心间的距离不超过两者半径之和且正在相互接近，
+/// 则沿两者重心连线施加冲量，动量守恒
+#[derive(Debug)]
+pub struct BounceExecutor {
+    /// 用于计算引力的执行器
+    pub gravity: SpaceExecutor,
+
+    /// 恢复系数，取值在0到1之间
+    ///
+    /// 为1时为完全弹性碰撞，为0时碰撞后两者沿连线方向的相对速度为0，默认为1
+    pub restitution: BigFloat,
+}
+
+/// 使用Barnes-Hut八叉树近似计算引力的执行器
+///
+/// 每一步将所有物体放入八叉树，距离足够远的一组物体视为位于其质心的单个质点，
+/// 复杂度由O(N^2)降为O(N log N)。树的构建与遍历使用`f64`以提高速度，
+/// 物体的坐标仍以`BigFloat`保存
+#[derive(Debug)]
+pub struct BarnesHutExecutor {
+    /// 提供引力常数、软化长度与位移计算的执行器
+    pub gravity: SpaceExecutor,
+
+    /// 张角参数
+    ///
+    /// 节点边长与到该节点质心的距离之比小于`theta`时，将整个节点视为一个质点。
+    /// 为0时退化为精确计算，越大越快但越不精确，通常取0.5
+    pub theta: f64,
+}
+
+/// 以`f64`计算引力与位移的近似执行器
+///
+/// 受力与位移的公式与`SpaceExecutor`相同，但在每一步开始时将物体的状态转换为`f64`，
+/// 计算结束后再写回`BigFloat`，速度比`SpaceExecutor`快数十倍，适合天体较多的交互场景。
+/// 精度受`f64`限制，需要精确结果时仍应使用`SpaceExecutor`
+#[derive(Debug, Default)]
+pub struct F64Executor {
+    /// 提供引力常数、软化长度与被固定物体的执行器
+    pub gravity: SpaceExecutor,
+}
+
+/// `F64Executor`计算时使用的物体状态
+#[derive(Clone, Copy, Debug)]
+struct F64State {
+    center: [f64; 3],
+    velocity: [f64; 3],
+    force: [f64; 3],
+    mass: f64,
+    anchored: bool,
+}
+
+/// 八叉树的节点
+#[derive(Debug)]
+struct OctreeNode {
+    /// 节点所表示的立方体的中心
+    center: [f64; 3],
+
+    /// 立方体边长的一半
+    half_size: f64,
+
+    /// 节点内所有物体的总质量
+    mass: f64,
+
+    /// 节点内所有物体的质心
+    com: [f64; 3],
+
+    /// 子节点在`Octree::nodes`中的下标
+    children: Option<[usize; 8]>,
+
+    /// 叶节点中的物体的下标
+    ///
+    /// 通常只有一个，达到最大深度时(物体几乎重合)可能有多个
+    bodies: Vec<usize>,
+}
+
+/// 使用数组存储节点的八叉树
+#[derive(Debug)]
+struct Octree {
+    nodes: Vec<OctreeNode>,
+
+    /// 各物体的质量，下标与构建时的参数相同
+    masses: Vec<f64>,
+}
+
+/// 自适应步长的执行器
+///
+/// 每一步根据物体两两之间的最短特征时间(`距离 / 相对速度`与自由落体时间中的较小者)
+/// 将给定的时长均分为若干子步，物体接近时子步更多、远离时更少。
+/// 每个子步使用`SpaceExecutor`进行计算，子步的划分在`execute_displacement`内部完成，
+/// 因此分别调用`execute_force`与`execute_displacement`的结果与`step`相同
+#[derive(Debug)]
+pub struct AdaptiveExecutor {
+    /// 用于计算每个子步的执行器
+    pub gravity: SpaceExecutor,
+
+    /// 精度系数，子步长度不超过最短特征时间与该系数之积，默认为0.01
+    pub accuracy: BigFloat,
+
+    /// 每一步最多划分的子步数，默认为1000
+    pub max_substeps: usize,
+
+    /// 上一步实际使用的子步数
+    last_substeps: usize,
+}
+
+/// 在引力之外施加阻力的执行器
+///
+/// 阻力大小与速度的平方成正比、方向与速度相反，即`F = -k * |v| * v`，可用于模拟进入大气层等情形
+#[derive(Debug, Default)]
+pub struct DragExecutor {
+    /// 用于计算引力与位移的执行器
+    pub gravity: SpaceExecutor,
+
+    /// 阻力系数`k`，以kg/m为单位
+    ///
+    /// 为0时与`SpaceExecutor`的行为相同，默认为0
+    pub drag_coefficient: BigFloat,
+}
+
+/// 连接两个物体的弹簧
+#[derive(Clone, PartialEq, Debug)]
+pub struct Spring {
+    /// 弹簧一端的物体的UID
+    pub a: Uuid,
+
+    /// 弹簧另一端的物体的UID
+    pub b: Uuid,
+
+    /// 弹簧的原长，以m为单位
+    pub rest_length: BigFloat,
+
+    /// 劲度系数，以N/m为单位
+    pub k: BigFloat,
+}
+
+/// 在引力之外施加弹簧弹力的执行器
+///
+/// 对每个弹簧连接的两个物体施加胡克定律的弹力`F = -k * (|r| - rest_length) * r_hat`，
+/// 伸长时相互吸引、压缩时相互排斥
+#[derive(Debug, Default)]
+pub struct SpringExecutor {
+    /// 用于计算引力与位移的执行器
+    pub gravity: SpaceExecutor,
+
+    pub springs: Vec<Spring>,
+}
+
+/// 在引力之外计算电荷间库仑力的执行器
+///
+/// 两物体间的库仑力为`F = k_e * q1 * q2 / r^2`，同号相斥、异号相吸。
+/// 设置了软化长度时分母与引力相同，取`r^2 + softening^2`
+#[derive(Debug)]
+pub struct ChargeExecutor {
+    /// 用于计算引力与位移的执行器
+    pub gravity: SpaceExecutor,
+
+    /// 库仑常数，以N * m^2 / C^2为单位
+    ///
+    /// 默认取8.9875517923 x 10^9
+    pub k_e: BigFloat,
+}
+
+/// 在牛顿引力之外加入一阶后牛顿(1PN)修正的执行器
+///
+/// 对每一对物体，以相对位置`r`与相对速度`v`计算修正加速度
+/// `a = G * M / (c^2 * r^3) * ((4 * G * M / r - v^2) * r + 4 * (r · v) * v)`，
+/// 其中`M`为两物体的总质量。在其中一方质量远大于另一方时与史瓦西度规下的测试粒子一致，
+/// 可以使偏心轨道的近日点发生进动(如水星近日点进动)
+#[derive(Debug)]
+pub struct RelativisticExecutor {
+    /// 用于计算牛顿引力与位移的执行器
+    pub gravity: SpaceExecutor,
+
+    /// 光速，以m/s为单位
+    ///
+    /// 默认取299792458，演示时可调小以放大进动效果
+    pub c: BigFloat,
+}
+
+/// 使用四阶龙格-库塔法(RK4)积分的执行器
+///
+/// 每一步在四个中间状态下重新计算引力场，精度远高于欧拉法，但计算量约为其四倍
 #[derive(Debug, Default)]
-pub struct SpaceExecutor {}
+pub struct Rk4Executor {
+    /// 用于计算引力的执行器
+    pub gravity: SpaceExecutor,
+}
+
+/// 由多个执行器叠加受力的执行器
+///
+/// 每一步先清零受力，再由`integrator`与各层依次调用`execute_force`将受力累加，
+/// 最后由`integrator`根据累加后的受力计算一次位移。
+/// `DragExecutor`等包装了`SpaceExecutor`的执行器自身已计算引力，与其他同样计算引力的层叠加时引力会被重复计算
+pub struct CompositeExecutor {
+    /// 计算自身受力并负责积分的执行器
+    pub integrator: Box<dyn Integrator>,
+
+    /// 只用于累加受力的执行器，其`execute_displacement`与`step`不会被调用
+    pub layers: Vec<Box<dyn Executor>>,
+}
+
+/// 在每一步结束后调用回调函数检查物体状态的执行器
+///
+/// 受力与位移均由`inner`计算，回调函数在`execute_displacement`之后以计算后的状态调用，
+/// 可用于记录最小距离、总能量等调试信息。回调函数不应修改物体
+pub struct InspectExecutor<E: Executor> {
+    pub inner: E,
+
+    /// 每一步结束后调用的回调函数，为`None`时与`inner`的行为相同
+    pub step_hook: Option<StepHook>,
+}
+
+/// `InspectExecutor`每一步结束后调用的回调函数
+pub type StepHook = Box<dyn FnMut(&Objects) + Send>;
+
+/// 对所有物体施加均匀外力场的执行器
+///
+/// 每个物体受力为`mass * field`，不计算物体之间的引力，可用于模拟行星表面附近的抛体运动。
+/// 受力恒定时位移计算`x += v * t + a * t^2 / 2`是精确的
+#[derive(Debug)]
+pub struct UniformFieldExecutor {
+    /// 场强，即加速度，以m/s^2为单位
+    pub field: Vector,
+}
+
+/// 带符号的时间步长
+///
+/// `Duration`无法表示负值，时间倒流时由`backward`标记方向
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Default)]
+pub struct TimeStep {
+    /// 步长的绝对值
+    pub duration: Duration,
+
+    /// 为`true`时时间倒流
+    pub backward: bool,
+}
+
+/// 一套长度、质量与时间单位，各字段为该单位等于多少个国际单位
+///
+/// 例如以天文单位、太阳质量与年为单位时，`length`约为1.496e11(m)。
+/// 电荷不随单位制换算
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct UnitSystem {
+    /// 长度单位，以m为单位
+    pub length: BigFloat,
+
+    /// 质量单位，以kg为单位
+    pub mass: BigFloat,
+
+    /// 时间单位，以s为单位
+    pub time: BigFloat,
+}
+
+
+
+impl PhysicalAttributes {
+    /// 创建一个不受力、不带电的物体的物理属性
+    ///
+    /// 质量不为正数或半径为负数时返回错误
+    pub fn new(center: Point, velocity: Vector, mass: BigFloat, radius: BigFloat)-> Result<Self, PhysicsError> {
+        Ok(Self {
+            center,
+            velocity,
+            force: Vector::ZERO,
+            mass: check_mass(mass)?,
+            radius: check_radius(radius)?,
+            charge: ZERO,
+        })
+    }
+
+    /// 施加冲量`impulse`(以N·s为单位)，使速度立即改变`impulse / mass`
+    pub fn apply_impulse(&mut self, impulse: Vector) {
+        self.velocity += impulse / self.mass;
+    }
+
+    /// 位置与速度的各分量是否都不是NaN或无穷大
+    pub fn is_finite(&self)-> bool {
+        self.center.is_finite() && self.velocity.is_finite()
+    }
+
+    /// 速度的大小超过`max`时保持方向不变、将大小缩小到`max`，返回是否进行了缩小
+    pub fn clamp_speed(&mut self, max: BigFloat)-> bool {
+        let speed = self.velocity.model();
+        if speed > max {
+            self.velocity = self.velocity / speed * max;
+            true
+        } else {
+            false
+        }
+    }
+}
+
+impl PhysicalObject for BodySnapshot {
+    fn get_uid(&self)-> Uuid {
+        self.uid
+    }
+
+    fn get_physical_attributes(&self)-> &PhysicalAttributes {
+        &self.attributes
+    }
+
+    fn get_physical_attributes_mut(&mut self)-> &mut PhysicalAttributes {
+        &mut self.attributes
+    }
+}
 
+impl Display for PhysicsError {
+    fn fmt(&self, formatter: &mut Formatter<'_>)-> FmtResult {
+        match self {
+            Self::InvalidNumber(s) => write!(formatter, "Invalid number: {:?}", s),
+            Self::NonPositiveMass(m) => write!(formatter, "Mass must be positive: {}", m),
+            Self::NegativeRadius(r) => write!(formatter, "Radius must not be negative: {}", r),
+        }
+    }
+}
 
+impl std::error::Error for PhysicsError {}
 
 impl Display for PhysicalAttributes {
     fn fmt(&self, formatter: &mut Formatter<'_>)-> FmtResult {
@@ -63,7 +524,9 @@ impl Display for PhysicalAttributes {
 {}
     {}N
   Mass: {}
-"#, self.center, self.velocity, self.velocity.model(), self.force, self.force.model(), self.mass)
+  Radius: {}m
+  Charge: {}C
+"#, self.center, self.velocity, self.velocity.model(), self.force, self.force.model(), self.mass, self.radius, self.charge)
     }
 }
 
@@ -87,6 +550,26 @@ impl AddAssign<Vector> for Vector {
     }
 }
 
+impl Sub<Vector> for Vector {
+    type Output = Self;
+
+    fn sub(self, other: Vector)-> Self::Output {
+        Self {
+            x: self.x - other.x,
+            y: self.y - other.y,
+            z: self.z - other.z,
+        }
+    }
+}
+
+impl SubAssign<Vector> for Vector {
+    fn sub_assign(&mut self, other: Vector) {
+        self.x -= other.x;
+        self.y -= other.y;
+        self.z -= other.z;
+    }
+}
+
 impl Mul<BigFloat> for Vector {
     type Output = Self;
 
@@ -99,6 +582,30 @@ impl Mul<BigFloat> for Vector {
     }
 }
 
+impl Neg for Vector {
+    type Output = Self;
+
+    fn neg(self)-> Self::Output {
+        Self {
+            x: -self.x,
+            y: -self.y,
+            z: -self.z,
+        }
+    }
+}
+
+impl Div<BigFloat> for Vector {
+    type Output = Self;
+
+    fn div(self, other: BigFloat)-> Self::Output {
+        Self {
+            x: self.x / other,
+            y: self.y / other,
+            z: self.z / other,
+        }
+    }
+}
+
 impl Display for Vector {
     fn fmt(&self, formatter: &mut Formatter<'_>)-> FmtResult {
         write!(formatter, r#"
@@ -108,6 +615,18 @@ impl Display for Vector {
     }
 }
 
+impl Display for Objects<'_, '_> {
+    fn fmt(&self, formatter: &mut Formatter<'_>)-> FmtResult {
+        write!(formatter, "{} object(s)", self.len())?;
+
+        for object in self.iter() {
+            write!(formatter, "\n\nObject {}:{}", object.get_uid(), object.get_physical_attributes())?;
+        }
+
+        Ok(())
+    }
+}
+
 impl Display for Point {
     fn fmt(&self, formatter: &mut Formatter<'_>)-> FmtResult {
         write!(formatter, r#"
@@ -121,71 +640,1439 @@ impl<'a, 'it> Objects<'a, 'it> {
     pub fn new(objects: Vec<&'a mut dyn PhysicalObject>)-> Objects<'a, 'it> {
         Self {
             inner: objects,
-            _marker: std::marker::PhantomData::<&'it Self>::default(),
+            _marker: std::marker::PhantomData::<&'it Self>,
+        }
+    }
+
+    /// 获取UID为`uid`的物体，不存在时返回`None`
+    pub fn get_by_uid(&self, uid: Uuid)-> Option<&dyn PhysicalObject> {
+        self.inner.iter().find(|o| o.get_uid() == uid).map(|o| &**o as &dyn PhysicalObject)
+    }
+
+    /// 获取UID为`uid`的物体的可变引用，不存在时返回`None`
+    pub fn get_by_uid_mut(&mut self, uid: Uuid)-> Option<&mut dyn PhysicalObject> {
+        self.inner.iter_mut().find(|o| o.get_uid() == uid).map(|o| &mut **o as &mut dyn PhysicalObject)
+    }
+
+    /// 对UID为`uid`的物体施加冲量，物体不存在时返回`false`
+    pub fn apply_impulse(&mut self, uid: Uuid, impulse: Vector)-> bool {
+        match self.get_by_uid_mut(uid) {
+            Some(object) => {
+                object.get_physical_attributes_mut().apply_impulse(impulse);
+                true
+            },
+            None => false,
         }
     }
+
+    /// 计算所有物体中心的轴对齐包围盒，返回最小角与最大角
+    ///
+    /// 没有物体时返回`None`
+    pub fn bounding_box(&self)-> Option<(Point, Point)> {
+        bounding_box(self.iter().map(|o| o.get_physical_attributes().center))
+    }
 }
 
-impl Executor for SpaceExecutor {
-    fn execute_force(&mut self, objects: &mut Objects, _time: Duration) {
-        // 计算每个物体所受引力情况
-        // 根据万有引力公式进行计算
-        // F = (G * m1 * m2) / (r^2)
-        // 引力常数G取6.67259 x 10^-11 (m^3 / (kg * s^2))
-        #[allow(non_snake_case)]
-        let G = "6.67259e-11".parse::<BigFloat>().unwrap();
-        let mut forces = Vec::new();
+impl SpaceExecutor {
+    /// 创建一个使用指定软化长度的执行器
+    pub fn with_softening(softening: BigFloat)-> Self {
+        Self {
+            softening,
+            ..Default::default()
+        }
+    }
+
+    /// 创建一个使用指定引力常数的执行器
+    pub fn with_g(g: BigFloat)-> Self {
+        Self {
+            g,
+            ..Default::default()
+        }
+    }
 
-        for object1 in objects.iter() {
+    /// 固定UID为`uid`的物体，使其不受任何力的影响保持静止
+    ///
+    /// 用于卫星绕静止的主星运动等简单情形。只对使用本执行器计算位移的执行器有效，
+    /// `VerletExecutor`与`Rk4Executor`使用各自的积分方法，不受影响
+    pub fn anchor(&mut self, uid: Uuid) {
+        self.anchored.insert(uid);
+    }
 
-            let attr1 = (*object1).get_physical_attributes();
+    /// 取消对UID为`uid`的物体的固定
+    pub fn unanchor(&mut self, uid: Uuid) {
+        self.anchored.remove(&uid);
+    }
 
-            let mut final_force = Vector { x:ZERO, y:ZERO, z:ZERO };
+    /// 从`objects`的当前状态出发预测之后`steps`步中每个物体的位置，不修改`objects`
+    ///
+    /// 返回以物体UID为键、按时间顺序排列的位置，每个物体共`steps`个
+    pub fn predict(&self, objects: &Objects, steps: usize, dt: TimeStep)-> HashMap<Uuid, Vec<Point>> {
+        let mut state: Vec<BodySnapshot> = objects
+            .iter()
+            .map(|o| BodySnapshot { uid: o.get_uid(), attributes: o.get_physical_attributes().clone() })
+            .collect();
 
-            for object2 in objects.iter().filter(|i| (**i).get_uid() != (*object1).get_uid()) {
+        let mut executor = Self {
+            g: self.g,
+            softening: self.softening,
+            anchored: self.anchored.clone(),
+            track_closest: false,
+            closest_approach: None,
+            max_velocity: self.max_velocity,
+            halt_on_non_finite: self.halt_on_non_finite,
+            scratch: ForceScratch::default(),
+        };
 
-                let attr2 = (*object2).get_physical_attributes();
+        let mut paths: HashMap<Uuid, Vec<Point>> = state
+            .iter()
+            .map(|s| (s.uid, Vec::with_capacity(steps)))
+            .collect();
 
-                let r = attr1.center.distance(&attr2.center);
+        let mut predicted = Objects::new(
+            state.iter_mut().map(|s| s as &mut dyn PhysicalObject).collect()
+        );
+
+        for _ in 0..steps {
+            executor.step(&mut predicted, dt);
+
+            for object in predicted.iter() {
+                if let Some(path) = paths.get_mut(&object.get_uid()) {
+                    path.push(object.get_physical_attributes().center);
+                }
+            }
+        }
+
+        paths
+    }
+
+    /// 计算系统的总机械能，以J为单位
+    ///
+    /// 为所有物体的动能`0.5 * m * v^2`与两两之间的引力势能`-G * m1 * m2 / r`之和。
+    /// 设置了软化长度时，势能中的`r`取`sqrt(r^2 + softening^2)`，与受力计算保持一致
+    pub fn total_energy(&self, objects: &Objects)-> BigFloat {
+        let half = BigFloat::from(0.5);
+        let softening_sq = self.softening.pow(&TWO);
+        let mut energy = ZERO;
+
+        for (i, object1) in objects.iter().enumerate() {
+            let attr1 = (*object1).get_physical_attributes();
+            energy += half * attr1.mass * attr1.velocity.model().pow(&TWO);
+
+            for object2 in objects.iter().skip(i + 1) {
+                let attr2 = (*object2).get_physical_attributes();
+                let r = (attr1.center.distance(&attr2.center).pow(&TWO) + softening_sq).sqrt();
                 if r == ZERO {
                     continue;
                 }
 
-                let force_size = (G * attr1.mass * attr2.mass) / (r.pow(&TWO));
-                let f = attr1.center.unit_vector_to(&attr2.center) * force_size;
-                final_force += f;
-
+                energy -= self.g * attr1.mass * attr2.mass / r;
             }
-
-            forces.push(final_force);
         }
 
-        objects
-            .iter_mut()
-            .zip(forces.iter())
-            .for_each(|(obj, force)| (**obj).get_physical_attributes_mut().force = *force);
+        energy
     }
 
-    fn execute_displacement(&mut self, objects: &mut Objects, time: Duration) {
-        for current_object in objects.iter_mut() {
-            let attr = (*current_object).get_physical_attributes_mut();
-            let t = BigFloat::from(time.as_micros()) / BigFloat::from(1e6);
-            let acceleration = attr.force * (ONE / attr.mass);
-            let displacement = attr.velocity * t + acceleration * t.pow(&TWO) * BigFloat::from(0.5);
+    /// 计算距离最近的两个物体，返回它们的UID与中心间的距离
+    ///
+    /// 物体少于两个时返回`None`
+    pub fn min_pairwise_distance(&self, objects: &Objects)-> Option<(Uuid, Uuid, BigFloat)> {
+        let mut closest: Option<(Uuid, Uuid, BigFloat)> = None;
 
-            attr.center += displacement;
-            attr.velocity += acceleration * t;
+        for (i, object1) in objects.iter().enumerate() {
+            let center1 = (*object1).get_physical_attributes().center;
+
+            for object2 in objects.iter().skip(i + 1) {
+                let distance = center1.distance(&(*object2).get_physical_attributes().center);
+
+                if closest.as_ref().is_none_or(|(_, _, d)| distance < *d) {
+                    closest = Some((object1.get_uid(), object2.get_uid(), distance));
+                }
+            }
         }
-    }
-}
 
-impl Point {
-    /// 计算两点间的距离
-    pub fn distance(&self, other: &Point)-> BigFloat {
-        let x_sq = (self.x - other.x).pow(&TWO);
-        let y_sq = (self.y - other.y).pow(&TWO);
-        let z_sq = (self.z - other.z).pow(&TWO);
-        (x_sq + y_sq + z_sq).sqrt()
+        closest
+    }
+
+    /// 计算系统的质心
+    ///
+    /// 总质量为0时返回原点
+    pub fn center_of_mass(&self, objects: &Objects)-> Point {
+        let mut total_mass = ZERO;
+        let mut weighted = Vector::ZERO;
+
+        for object in objects.iter() {
+            let attr = (*object).get_physical_attributes();
+            total_mass += attr.mass;
+            weighted += attr.center.as_vector() * attr.mass;
+        }
+
+        if total_mass == ZERO {
+            return Point::ZERO;
+        }
+
+        Point::ZERO + weighted / total_mass
+    }
+
+    /// 计算系统的总动量，以kg * m/s为单位
+    pub fn total_momentum(&self, objects: &Objects)-> Vector {
+        let mut momentum = Vector::ZERO;
+
+        for object in objects.iter() {
+            let attr = (*object).get_physical_attributes();
+            momentum += attr.velocity * attr.mass;
+        }
+
+        momentum
+    }
+
+    /// 将系统变换到质心参考系
+    ///
+    /// 所有物体的位置减去质心坐标、速度减去质心速度，变换后质心位于原点且总动量为0。
+    /// 总质量为0时不做任何变换
+    pub fn recenter_to_com(&mut self, objects: &mut Objects) {
+        let total_mass = objects
+            .iter()
+            .fold(ZERO, |acc, i| acc + (*i).get_physical_attributes().mass);
+        if total_mass == ZERO {
+            return;
+        }
+
+        let shift = -self.center_of_mass(objects).as_vector();
+        let velocity_shift = -(self.total_momentum(objects) / total_mass);
+
+        for object in objects.iter_mut() {
+            let attr = (**object).get_physical_attributes_mut();
+            attr.center += shift;
+            attr.velocity += velocity_shift;
+        }
+    }
+
+    /// 计算系统相对于`about`点的总角动量，以kg * m^2/s为单位
+    ///
+    /// 为所有物体的`r × (m * v)`之和，其中`r`为由`about`指向物体重心的向量
+    pub fn total_angular_momentum(&self, objects: &Objects, about: &Point)-> Vector {
+        let mut angular_momentum = Vector::ZERO;
+
+        for object in objects.iter() {
+            let attr = (*object).get_physical_attributes();
+            let r = about.vector_to(&attr.center);
+            angular_momentum += r.cross(&(attr.velocity * attr.mass));
+        }
+
+        angular_momentum
+    }
+
+    /// 将超过`max_velocity`的速度缩小到上限，并输出被缩小的物体的UID
+    ///
+    /// `max_velocity`为`None`时不做任何事
+    pub fn clamp_velocities(&self, objects: &mut Objects) {
+        let Some(max) = self.max_velocity else {
+            return;
+        };
+
+        for object in objects.iter_mut() {
+            let uid = object.get_uid();
+            if object.get_physical_attributes_mut().clamp_speed(max) {
+                log::warn!("Speed of {} exceeded {} m/s and was clamped", uid, max);
+            }
+        }
+    }
+
+    /// 返回位置或速度含有NaN或无穷大的物体的UID，并为每个这样的物体输出错误
+    pub fn check_finite(&self, objects: &Objects)-> Vec<Uuid> {
+        objects
+            .iter()
+            .filter(|o| !o.get_physical_attributes().is_finite())
+            .map(|o| {
+                let attr = o.get_physical_attributes();
+                log::error!("{} has a non-finite state: center {}, velocity {}", o.get_uid(), attr.center, attr.velocity);
+                o.get_uid()
+            })
+            .collect()
+    }
+
+    /// 计算位移后的收尾工作，各执行器在`execute_displacement`的最后调用
+    ///
+    /// 按`max_velocity`限制速度，调试构建中还会检查物体的状态是否为有限值，
+    /// `halt_on_non_finite`为`true`且发现非有限值时panic
+    pub fn finish_displacement(&self, objects: &mut Objects) {
+        self.clamp_velocities(objects);
+        self.assert_finite(objects);
+    }
+
+    /// 调试构建中检查物体的状态是否为有限值，`halt_on_non_finite`为`true`且发现非有限值时panic
+    ///
+    /// 非调试构建中不进行检查
+    pub fn assert_finite(&self, objects: &Objects) {
+        #[cfg(debug_assertions)]
+        {
+            let bad = self.check_finite(objects);
+            if self.halt_on_non_finite && !bad.is_empty() {
+                panic!("{} objects have non-finite states", bad.len());
+            }
+        }
+
+        #[cfg(not(debug_assertions))]
+        let _ = objects;
+    }
+
+    /// 计算一组质点两两之间的引力，返回每个质点所受的合力
+    ///
+    /// `centers`与`masses`按下标一一对应，返回值的顺序与之相同
+    pub fn gravitational_forces(&self, centers: &[Point], masses: &[BigFloat])-> Vec<Vector> {
+        let mut forces = Vec::with_capacity(centers.len());
+        self.gravitational_forces_into(centers, masses, &mut forces);
+        forces
+    }
+
+    /// 与`gravitational_forces`相同，但将结果写入`forces`
+    ///
+    /// `forces`会先被清空，重复使用同一个`Vec`时不会重新分配
+    pub fn gravitational_forces_into(&self, centers: &[Point], masses: &[BigFloat], forces: &mut Vec<Vector>) {
+        // 根据万有引力公式进行计算
+        // F = (G * m1 * m2) / (r^2 + ε^2)
+        // G为引力常数，ε为软化长度
+        #[allow(non_snake_case)]
+        let G = self.g;
+        let softening_sq = self.softening.pow(&TWO);
+        forces.clear();
+
+        for (i, (center1, mass1)) in centers.iter().zip(masses.iter()).enumerate() {
+
+            let mut final_force = Vector { x:ZERO, y:ZERO, z:ZERO };
+
+            for (j, (center2, mass2)) in centers.iter().zip(masses.iter()).enumerate() {
+                if i == j {
+                    continue;
+                }
+
+                // 两点重合时方向不确定，不计算引力
+                let Some(direction) = center1.try_unit_vector_to(center2) else {
+                    continue;
+                };
+
+                let r = center1.distance(center2);
+                let force_size = (G * *mass1 * *mass2) / (r.pow(&TWO) + softening_sq);
+                let f = direction * force_size;
+                final_force += f;
+
+            }
+
+            forces.push(final_force);
+        }
+    }
+}
+
+impl Default for SpaceExecutor {
+    fn default()-> Self {
+        Self {
+            g: "6.67259e-11".parse().unwrap(),
+            softening: ZERO,
+            anchored: HashSet::new(),
+            track_closest: false,
+            closest_approach: None,
+            max_velocity: None,
+            halt_on_non_finite: false,
+            scratch: ForceScratch::default(),
+        }
+    }
+}
+
+impl Executor for SpaceExecutor {
+    fn execute_force(&mut self, objects: &mut Objects, _time: TimeStep) {
+        // 计算每个物体所受引力情况
+        // 暂时取出缓冲，使计算时可以同时借用`self`
+        let mut scratch = std::mem::take(&mut self.scratch);
+        scratch.centers.clear();
+        scratch.masses.clear();
+        for object in objects.iter() {
+            let attr = (*object).get_physical_attributes();
+            scratch.centers.push(attr.center);
+            scratch.masses.push(attr.mass);
+        }
+
+        self.gravitational_forces_into(&scratch.centers, &scratch.masses, &mut scratch.forces);
+
+        objects
+            .iter_mut()
+            .zip(scratch.forces.iter())
+            .for_each(|(obj, force)| (**obj).get_physical_attributes_mut().force += *force);
+        self.scratch = scratch;
+
+        if !self.track_closest {
+            return;
+        }
+
+        if let Some(current) = self.min_pairwise_distance(objects) {
+            if self.closest_approach.as_ref().is_none_or(|(_, _, d)| current.2 < *d) {
+                self.closest_approach = Some(current);
+            }
+        }
+    }
+
+    fn execute_displacement(&mut self, objects: &mut Objects, time: TimeStep) {
+        for current_object in objects.iter_mut() {
+            if self.anchored.contains(&current_object.get_uid()) {
+                current_object.get_physical_attributes_mut().velocity = Vector::ZERO;
+                continue;
+            }
+
+            let attr = (*current_object).get_physical_attributes_mut();
+            let t = time.seconds();
+            let acceleration = attr.force / attr.mass;
+            let displacement = attr.velocity * t + acceleration * t.pow(&TWO) * BigFloat::from(0.5);
+
+            attr.center += displacement;
+            attr.velocity += acceleration * t;
+        }
+
+        self.finish_displacement(objects);
+    }
+}
+
+impl VerletExecutor {
+    pub fn new(gravity: SpaceExecutor)-> Self {
+        Self {
+            gravity,
+            accelerations: HashMap::new(),
+        }
+    }
+}
+
+impl Executor for VerletExecutor {
+    fn execute_force(&mut self, objects: &mut Objects, time: TimeStep) {
+        self.gravity.execute_force(objects, time);
+    }
+
+    fn execute_displacement(&mut self, objects: &mut Objects, time: TimeStep) {
+        let t = time.seconds();
+        let half = BigFloat::from(0.5);
+
+        for current_object in objects.iter_mut() {
+            let uid = (*current_object).get_uid();
+            let attr = (*current_object).get_physical_attributes_mut();
+            let acceleration = attr.force / attr.mass;
+
+            // 用上一步与当前位置的加速度的平均值补完上一步的速度更新
+            // 首次参与计算的物体没有上一步的加速度，跳过
+            if let Some(last) = self.accelerations.get(&uid) {
+                attr.velocity += (*last + acceleration) * half * t;
+            }
+
+            attr.center += attr.velocity * t + acceleration * t.pow(&TWO) * half;
+
+            self.accelerations.insert(uid, acceleration);
+        }
+
+        self.gravity.finish_displacement(objects);
+
+        // 物体被移除后丢弃其加速度，避免记录无限增长
+        if self.accelerations.len() > objects.len() {
+            let live: HashSet<Uuid> = objects.iter().map(|o| o.get_uid()).collect();
+            self.accelerations.retain(|uid, _| live.contains(uid));
+        }
+    }
+}
+
+impl CollisionExecutor {
+    pub fn new(gravity: SpaceExecutor)-> Self {
+        Self {
+            gravity,
+            removed: Vec::new(),
+        }
+    }
+
+    /// 取出自上次调用以来被合并掉的物体的UID
+    ///
+    /// 这些物体的属性已并入其他物体，`World`应当将其移除
+    pub fn take_removed(&mut self)-> Vec<Uuid> {
+        std::mem::take(&mut self.removed)
+    }
+
+    /// 检测碰撞并合并相互接触的物体，返回被合并掉的物体的UID
+    ///
+    /// 被合并掉的物体仍留在`objects`中，其后不再参与本次检测
+    pub fn merge_collisions(objects: &mut Objects)-> Vec<Uuid> {
+        let mut removed = Vec::new();
+
+        for i in 0..objects.len() {
+            if removed.contains(&objects[i].get_uid()) {
+                continue;
+            }
+
+            for j in (i + 1)..objects.len() {
+                if removed.contains(&objects[j].get_uid()) {
+                    continue;
+                }
+
+                let attr1 = objects[i].get_physical_attributes();
+                let attr2 = objects[j].get_physical_attributes();
+                if attr1.center.distance(&attr2.center) > attr1.radius + attr2.radius {
+                    continue;
+                }
+
+                // 较重的物体保留，较轻的物体并入其中
+                let (survivor, absorbed) = if attr1.mass >= attr2.mass { (i, j) } else { (j, i) };
+                let absorbed_attr = objects[absorbed].get_physical_attributes().clone();
+                let attr = objects[survivor].get_physical_attributes_mut();
+
+                // m1 * v1 + m2 * v2 = (m1 + m2) * v
+                let mass = attr.mass + absorbed_attr.mass;
+                attr.velocity = (attr.velocity * attr.mass + absorbed_attr.velocity * absorbed_attr.mass) / mass;
+                attr.center = Point::ZERO
+                    + (attr.center.as_vector() * attr.mass + absorbed_attr.center.as_vector() * absorbed_attr.mass) / mass;
+                attr.force += absorbed_attr.force;
+                attr.charge += absorbed_attr.charge;
+                attr.radius = (attr.radius.pow(&BigFloat::from(3)) + absorbed_attr.radius.pow(&BigFloat::from(3))).cbrt();
+                attr.mass = mass;
+
+                removed.push(objects[absorbed].get_uid());
+
+                if absorbed == i {
+                    break;
+                }
+            }
+        }
+
+        removed
+    }
+}
+
+impl Executor for CollisionExecutor {
+    fn execute_force(&mut self, objects: &mut Objects, time: TimeStep) {
+        self.gravity.execute_force(objects, time);
+    }
+
+    fn execute_displacement(&mut self, objects: &mut Objects, time: TimeStep) {
+        self.gravity.execute_displacement(objects, time);
+
+        let removed = Self::merge_collisions(objects);
+        self.removed.extend(removed);
+    }
+}
+
+impl BounceExecutor {
+    pub fn new(gravity: SpaceExecutor, restitution: BigFloat)-> Self {
+        Self {
+            gravity,
+            restitution,
+        }
+    }
+
+    /// 检测碰撞并对相互接触且正在接近的物体施加冲量
+    ///
+    /// 冲量大小为`(1 + e) * v_n / (1 / m1 + 1 / m2)`，其中`v_n`为沿连线方向的相对接近速度
+    pub fn resolve_collisions(objects: &mut Objects, restitution: BigFloat) {
+        for i in 0..objects.len() {
+            for j in (i + 1)..objects.len() {
+                let attr1 = objects[i].get_physical_attributes();
+                let attr2 = objects[j].get_physical_attributes();
+                if attr1.center.distance(&attr2.center) > attr1.radius + attr2.radius {
+                    continue;
+                }
+
+                // 重心重合时没有确定的法线方向，无法计算冲量
+                let Some(normal) = attr1.center.try_unit_vector_to(&attr2.center) else {
+                    continue;
+                };
+
+                // 相对速度为正表示两者正在分离，无需处理
+                let approaching = (attr2.velocity - attr1.velocity).dot(&normal);
+                if !approaching.is_negative() {
+                    continue;
+                }
+
+                let (mass1, mass2) = (attr1.mass, attr2.mass);
+                let impulse = -(ONE + restitution) * approaching / (ONE / mass1 + ONE / mass2);
+                let delta = normal * impulse;
+
+                objects[i].get_physical_attributes_mut().velocity -= delta / mass1;
+                objects[j].get_physical_attributes_mut().velocity += delta / mass2;
+            }
+        }
+    }
+}
+
+impl Default for BounceExecutor {
+    fn default()-> Self {
+        Self::new(SpaceExecutor::default(), ONE)
+    }
+}
+
+impl Executor for BounceExecutor {
+    fn execute_force(&mut self, objects: &mut Objects, time: TimeStep) {
+        self.gravity.execute_force(objects, time);
+    }
+
+    fn execute_displacement(&mut self, objects: &mut Objects, time: TimeStep) {
+        self.gravity.execute_displacement(objects, time);
+        Self::resolve_collisions(objects, self.restitution);
+    }
+}
+
+impl DragExecutor {
+    pub fn new(gravity: SpaceExecutor, drag_coefficient: BigFloat)-> Self {
+        Self {
+            gravity,
+            drag_coefficient,
+        }
+    }
+
+    /// 计算以`velocity`运动的物体所受的阻力
+    pub fn drag_force(&self, velocity: &Vector)-> Vector {
+        if velocity.is_zero() {
+            return Vector::ZERO;
+        }
+
+        let speed = velocity.model();
+        -(*velocity / speed * (self.drag_coefficient * speed.pow(&TWO)))
+    }
+}
+
+impl Executor for DragExecutor {
+    fn execute_force(&mut self, objects: &mut Objects, time: TimeStep) {
+        self.gravity.execute_force(objects, time);
+
+        if self.drag_coefficient == ZERO {
+            return;
+        }
+
+        for object in objects.iter_mut() {
+            let attr = (*object).get_physical_attributes_mut();
+            attr.force += self.drag_force(&attr.velocity);
+        }
+    }
+
+    fn execute_displacement(&mut self, objects: &mut Objects, time: TimeStep) {
+        self.gravity.execute_displacement(objects, time);
+    }
+}
+
+impl SpringExecutor {
+    pub fn new(gravity: SpaceExecutor, springs: Vec<Spring>)-> Self {
+        Self {
+            gravity,
+            springs,
+        }
+    }
+}
+
+impl Executor for SpringExecutor {
+    fn execute_force(&mut self, objects: &mut Objects, time: TimeStep) {
+        self.gravity.execute_force(objects, time);
+
+        for spring in self.springs.iter() {
+            // 弹簧连接的物体不在本次计算中时忽略该弹簧
+            let (Some(a), Some(b)) = (objects.get_by_uid(spring.a), objects.get_by_uid(spring.b)) else {
+                continue;
+            };
+
+            let center_a = a.get_physical_attributes().center;
+            let center_b = b.get_physical_attributes().center;
+            let Some(direction) = center_a.try_unit_vector_to(&center_b) else {
+                continue;
+            };
+
+            // 作用在a上的力，伸长时指向b
+            let force = direction * (spring.k * (center_a.distance(&center_b) - spring.rest_length));
+
+            if let Some(a) = objects.get_by_uid_mut(spring.a) {
+                a.get_physical_attributes_mut().force += force;
+            }
+            if let Some(b) = objects.get_by_uid_mut(spring.b) {
+                b.get_physical_attributes_mut().force -= force;
+            }
+        }
+    }
+
+    fn execute_displacement(&mut self, objects: &mut Objects, time: TimeStep) {
+        self.gravity.execute_displacement(objects, time);
+    }
+}
+
+impl ChargeExecutor {
+    pub fn new(gravity: SpaceExecutor)-> Self {
+        Self {
+            gravity,
+            k_e: "8.9875517923e9".parse().unwrap(),
+        }
+    }
+
+    /// 计算一组带电质点两两之间的库仑力，返回每个质点所受的合力
+    ///
+    /// `centers`与`charges`按下标一一对应，返回值的顺序与之相同
+    pub fn coulomb_forces(&self, centers: &[Point], charges: &[BigFloat])-> Vec<Vector> {
+        let softening_sq = self.gravity.softening.pow(&TWO);
+        let mut forces = Vec::with_capacity(centers.len());
+
+        for (i, (center1, charge1)) in centers.iter().zip(charges.iter()).enumerate() {
+            let mut final_force = Vector::ZERO;
+
+            if *charge1 == ZERO {
+                forces.push(final_force);
+                continue;
+            }
+
+            for (j, (center2, charge2)) in centers.iter().zip(charges.iter()).enumerate() {
+                if i == j || *charge2 == ZERO {
+                    continue;
+                }
+
+                let Some(direction) = center1.try_unit_vector_to(center2) else {
+                    continue;
+                };
+
+                // 同号时乘积为正，方向背离另一电荷
+                let r_sq = center1.distance(center2).pow(&TWO);
+                let force_size = self.k_e * *charge1 * *charge2 / (r_sq + softening_sq);
+                final_force -= direction * force_size;
+            }
+
+            forces.push(final_force);
+        }
+
+        forces
+    }
+}
+
+impl Default for ChargeExecutor {
+    fn default()-> Self {
+        Self::new(SpaceExecutor::default())
+    }
+}
+
+impl Executor for ChargeExecutor {
+    fn execute_force(&mut self, objects: &mut Objects, time: TimeStep) {
+        self.gravity.execute_force(objects, time);
+
+        let (centers, charges): (Vec<Point>, Vec<BigFloat>) = objects
+            .iter()
+            .map(|i| {
+                let attr = (*i).get_physical_attributes();
+                (attr.center, attr.charge)
+            })
+            .unzip();
+
+        let forces = self.coulomb_forces(&centers, &charges);
+
+        objects
+            .iter_mut()
+            .zip(forces.iter())
+            .for_each(|(obj, force)| (**obj).get_physical_attributes_mut().force += *force);
+    }
+
+    fn execute_displacement(&mut self, objects: &mut Objects, time: TimeStep) {
+        self.gravity.execute_displacement(objects, time);
+    }
+}
+
+impl RelativisticExecutor {
+    pub fn new(gravity: SpaceExecutor)-> Self {
+        Self {
+            gravity,
+            c: BigFloat::from(299_792_458),
+        }
+    }
+
+    /// 计算一组质点两两之间的1PN修正力，返回每个质点所受的修正力之和
+    ///
+    /// `centers`、`velocities`与`masses`按下标一一对应，返回值的顺序与之相同
+    pub fn post_newtonian_forces(&self, centers: &[Point], velocities: &[Vector], masses: &[BigFloat])-> Vec<Vector> {
+        let c_sq = self.c.pow(&TWO);
+        let four = BigFloat::from(4);
+        let mut forces = vec![Vector::ZERO; centers.len()];
+
+        for i in 0..centers.len() {
+            for j in (i + 1)..centers.len() {
+                // 由j指向i的相对位置与i相对于j的速度
+                let r = centers[i] - centers[j];
+                let distance = r.model();
+                if distance == ZERO {
+                    continue;
+                }
+
+                let v = velocities[i] - velocities[j];
+                let gm = self.gravity.g * (masses[i] + masses[j]);
+
+                let correction = r * (four * gm / distance - v.dot(&v)) + v * (four * r.dot(&v));
+                let factor = self.gravity.g * masses[i] * masses[j] / (c_sq * distance.pow(&BigFloat::from(3)));
+
+                // 修正项对两物体大小相等、方向相反
+                let force = correction * factor;
+                forces[i] += force;
+                forces[j] -= force;
+            }
+        }
+
+        forces
+    }
+}
+
+impl Default for RelativisticExecutor {
+    fn default()-> Self {
+        Self::new(SpaceExecutor::default())
+    }
+}
+
+impl Executor for RelativisticExecutor {
+    fn execute_force(&mut self, objects: &mut Objects, time: TimeStep) {
+        self.gravity.execute_force(objects, time);
+
+        let mut centers = Vec::with_capacity(objects.len());
+        let mut velocities = Vec::with_capacity(objects.len());
+        let mut masses = Vec::with_capacity(objects.len());
+        for object in objects.iter() {
+            let attr = (*object).get_physical_attributes();
+            centers.push(attr.center);
+            velocities.push(attr.velocity);
+            masses.push(attr.mass);
+        }
+
+        let forces = self.post_newtonian_forces(&centers, &velocities, &masses);
+
+        objects
+            .iter_mut()
+            .zip(forces.iter())
+            .for_each(|(obj, force)| (**obj).get_physical_attributes_mut().force += *force);
+    }
+
+    fn execute_displacement(&mut self, objects: &mut Objects, time: TimeStep) {
+        self.gravity.execute_displacement(objects, time);
+    }
+}
+
+impl BarnesHutExecutor {
+    pub fn new(gravity: SpaceExecutor, theta: f64)-> Self {
+        Self {
+            gravity,
+            theta,
+        }
+    }
+
+    /// 计算一组质点所受的引力，返回值的顺序与参数相同
+    pub fn gravitational_forces(&self, centers: &[Point], masses: &[BigFloat])-> Vec<Vector> {
+        let positions: Vec<[f64; 3]> = centers
+            .iter()
+            .map(|c| c.to_f64_array())
+            .collect();
+        let masses: Vec<f64> = masses.iter().map(|m| m.to_f64()).collect();
+
+        let tree = Octree::build(&positions, &masses);
+        let g = self.gravity.g.to_f64();
+        let softening_sq = self.gravity.softening.to_f64().powi(2);
+
+        positions
+            .iter()
+            .zip(masses.iter())
+            .enumerate()
+            .map(|(i, (pos, mass))| {
+                let acc = tree.field_at(i, pos, self.theta, softening_sq);
+                Vector::from_f64_array(acc.map(|a| a * g * mass))
+            })
+            .collect()
+    }
+}
+
+impl F64Executor {
+    pub fn new(gravity: SpaceExecutor)-> Self {
+        Self { gravity }
+    }
+
+    /// 将物体的状态转换为`f64`
+    fn load(&self, objects: &Objects)-> Vec<F64State> {
+        objects
+            .iter()
+            .map(|o| {
+                let attr = (*o).get_physical_attributes();
+                F64State {
+                    center: attr.center.to_f64_array(),
+                    velocity: attr.velocity.to_f64_array(),
+                    force: attr.force.to_f64_array(),
+                    mass: attr.mass.to_f64(),
+                    anchored: self.gravity.anchored.contains(&o.get_uid()),
+                }
+            })
+            .collect()
+    }
+
+    /// 将计算结果写回物体
+    fn store(objects: &mut Objects, states: &[F64State]) {
+        for (object, state) in objects.iter_mut().zip(states.iter()) {
+            let attr = (**object).get_physical_attributes_mut();
+            attr.center = Point::from_f64_array(state.center);
+            attr.velocity = Vector::from_f64_array(state.velocity);
+            attr.force = Vector::from_f64_array(state.force);
+        }
+    }
+
+    /// 将两两之间的引力累加到`force`上，返回距离最近的两个物体的下标与距离
+    fn accumulate_forces(&self, states: &mut [F64State])-> Option<(usize, usize, f64)> {
+        let g = self.gravity.g.to_f64();
+        let softening_sq = self.gravity.softening.to_f64().powi(2);
+        let mut closest: Option<(usize, usize, f64)> = None;
+
+        // 每对物体只计算一次，利用牛顿第三定律同时累加到两者上
+        for i in 0..states.len() {
+            for j in (i + 1)..states.len() {
+                let d = [
+                    states[j].center[0] - states[i].center[0],
+                    states[j].center[1] - states[i].center[1],
+                    states[j].center[2] - states[i].center[2],
+                ];
+                let r_sq = d[0] * d[0] + d[1] * d[1] + d[2] * d[2];
+                let r = r_sq.sqrt();
+
+                if closest.is_none_or(|(_, _, c)| r < c) {
+                    closest = Some((i, j, r));
+                }
+
+                // 两点重合时方向不确定，不计算引力
+                if r == 0.0 {
+                    continue;
+                }
+
+                let force_size = g * states[i].mass * states[j].mass / (r_sq + softening_sq);
+                for (k, component) in d.iter().enumerate() {
+                    let f = component / r * force_size;
+                    states[i].force[k] += f;
+                    states[j].force[k] -= f;
+                }
+            }
+        }
+
+        closest
+    }
+
+    /// 根据受力计算速度与位移
+    fn integrate(states: &mut [F64State], t: f64) {
+        for state in states.iter_mut() {
+            if state.anchored {
+                state.velocity = [0.0; 3];
+                continue;
+            }
+
+            for k in 0..3 {
+                let acceleration = state.force[k] / state.mass;
+                state.center[k] += state.velocity[k] * t + 0.5 * acceleration * t * t;
+                state.velocity[k] += acceleration * t;
+            }
+        }
+    }
+
+    /// 按`gravity.max_velocity`限制速度，与`SpaceExecutor::clamp_velocities`一致
+    fn clamp_velocities(&self, objects: &Objects, states: &mut [F64State]) {
+        let Some(max) = self.gravity.max_velocity else {
+            return;
+        };
+        let max = max.to_f64();
+
+        for (object, state) in objects.iter().zip(states.iter_mut()) {
+            let v = state.velocity;
+            let speed = (v[0] * v[0] + v[1] * v[1] + v[2] * v[2]).sqrt();
+            if speed > max {
+                state.velocity = v.map(|c| c / speed * max);
+                log::warn!("Speed of {} exceeded {} m/s and was clamped", object.get_uid(), max);
+            }
+        }
+    }
+
+    /// 记录最近距离，与`SpaceExecutor::execute_force`一致，`gravity.track_closest`为`false`时不记录
+    fn record_closest(&mut self, objects: &Objects, closest: Option<(usize, usize, f64)>) {
+        let Some((i, j, distance)) = closest.filter(|_| self.gravity.track_closest) else {
+            return;
+        };
+        let distance = BigFloat::from(distance);
+
+        if self.gravity.closest_approach.as_ref().is_none_or(|(_, _, d)| distance < *d) {
+            self.gravity.closest_approach = Some((objects[i].get_uid(), objects[j].get_uid(), distance));
+        }
+    }
+}
+
+impl Executor for F64Executor {
+    fn execute_force(&mut self, objects: &mut Objects, _time: TimeStep) {
+        let mut states = self.load(objects);
+        let closest = self.accumulate_forces(&mut states);
+        Self::store(objects, &states);
+        self.record_closest(objects, closest);
+    }
+
+    fn execute_displacement(&mut self, objects: &mut Objects, time: TimeStep) {
+        let mut states = self.load(objects);
+        Self::integrate(&mut states, time.seconds().to_f64());
+        self.clamp_velocities(objects, &mut states);
+        Self::store(objects, &states);
+        self.gravity.assert_finite(objects);
+    }
+
+    fn step(&mut self, objects: &mut Objects, time: TimeStep) {
+        self.step_many(objects, time, 1);
+    }
+
+    /// 所有步都以`f64`计算，只在开始与结束时各转换一次
+    fn step_many(&mut self, objects: &mut Objects, time: TimeStep, count: usize) {
+        let t = time.seconds().to_f64();
+        let mut states = self.load(objects);
+        let mut closest = None;
+
+        for _ in 0..count {
+            for state in states.iter_mut() {
+                state.force = [0.0; 3];
+            }
+            let current = self.accumulate_forces(&mut states);
+            if closest.is_none_or(|(_, _, c)| current.is_some_and(|(_, _, d)| d < c)) {
+                closest = current;
+            }
+            Self::integrate(&mut states, t);
+            self.clamp_velocities(objects, &mut states);
+        }
+
+        Self::store(objects, &states);
+        self.gravity.assert_finite(objects);
+        self.record_closest(objects, closest);
+    }
+}
+
+impl Default for BarnesHutExecutor {
+    fn default()-> Self {
+        Self::new(SpaceExecutor::default(), 0.5)
+    }
+}
+
+impl Executor for BarnesHutExecutor {
+    fn execute_force(&mut self, objects: &mut Objects, _time: TimeStep) {
+        let (centers, masses): (Vec<Point>, Vec<BigFloat>) = objects
+            .iter()
+            .map(|i| {
+                let attr = (*i).get_physical_attributes();
+                (attr.center, attr.mass)
+            })
+            .unzip();
+
+        let forces = self.gravitational_forces(&centers, &masses);
+
+        objects
+            .iter_mut()
+            .zip(forces.iter())
+            .for_each(|(obj, force)| (**obj).get_physical_attributes_mut().force += *force);
+    }
+
+    fn execute_displacement(&mut self, objects: &mut Objects, time: TimeStep) {
+        self.gravity.execute_displacement(objects, time);
+    }
+}
+
+impl Octree {
+    /// 最大深度，超过后重合的物体被放入同一个叶节点
+    const MAX_DEPTH: usize = 48;
+
+    fn build(positions: &[[f64; 3]], masses: &[f64])-> Self {
+        let mut min = [f64::MAX; 3];
+        let mut max = [f64::MIN; 3];
+        for p in positions {
+            for k in 0..3 {
+                min[k] = min[k].min(p[k]);
+                max[k] = max[k].max(p[k]);
+            }
+        }
+
+        let center = [(min[0] + max[0]) / 2.0, (min[1] + max[1]) / 2.0, (min[2] + max[2]) / 2.0];
+        let half_size = (0..3)
+            .map(|k| (max[k] - min[k]) / 2.0)
+            .fold(0.0, f64::max)
+            .max(f64::MIN_POSITIVE);
+
+        let mut tree = Self {
+            nodes: vec![OctreeNode::new(center, half_size)],
+            masses: masses.to_vec(),
+        };
+
+        for i in 0..positions.len() {
+            tree.insert(0, i, positions, 0);
+        }
+
+        for node in tree.nodes.iter_mut() {
+            if node.mass > 0.0 {
+                for k in 0..3 {
+                    node.com[k] /= node.mass;
+                }
+            }
+        }
+
+        tree
+    }
+
+    /// 将下标为`body`的物体插入节点`node`
+    ///
+    /// 构建过程中`com`暂存质量与坐标之积的和，构建完成后再除以总质量
+    fn insert(&mut self, node: usize, body: usize, positions: &[[f64; 3]], depth: usize) {
+        let pos = positions[body];
+        let mass = self.masses[body];
+
+        let n = &mut self.nodes[node];
+        n.mass += mass;
+        for (c, p) in n.com.iter_mut().zip(pos.iter()) {
+            *c += p * mass;
+        }
+
+        if let Some(children) = n.children {
+            let octant = n.octant_of(&pos);
+            self.insert(children[octant], body, positions, depth + 1);
+            return;
+        }
+
+        if n.bodies.is_empty() || depth >= Self::MAX_DEPTH {
+            n.bodies.push(body);
+            return;
+        }
+
+        // 叶节点已有物体，将其分裂为八个子节点
+        let (center, half_size) = (n.center, n.half_size);
+        let existing = std::mem::take(&mut n.bodies);
+        let mut children = [0; 8];
+        for (octant, child) in children.iter_mut().enumerate() {
+            let quarter = half_size / 2.0;
+            let child_center = [
+                center[0] + if octant & 1 != 0 { quarter } else { -quarter },
+                center[1] + if octant & 2 != 0 { quarter } else { -quarter },
+                center[2] + if octant & 4 != 0 { quarter } else { -quarter },
+            ];
+            *child = self.nodes.len();
+            self.nodes.push(OctreeNode::new(child_center, quarter));
+        }
+        self.nodes[node].children = Some(children);
+
+        for b in existing.into_iter().chain(std::iter::once(body)) {
+            let octant = self.nodes[node].octant_of(&positions[b]);
+            self.insert(children[octant], b, positions, depth + 1);
+        }
+    }
+
+    /// 计算除`body`外所有物体在`pos`处产生的引力场(未乘引力常数)
+    fn field_at(&self, body: usize, pos: &[f64; 3], theta: f64, softening_sq: f64)-> [f64; 3] {
+        let mut field = [0.0; 3];
+        let mut stack = vec![0];
+
+        // 质量为`mass`的质点位于`at`处时对`pos`处产生的引力场
+        let mut add_point = |at: &[f64; 3], mass: f64| {
+            let d = [at[0] - pos[0], at[1] - pos[1], at[2] - pos[2]];
+            let r_sq = d[0] * d[0] + d[1] * d[1] + d[2] * d[2];
+            if r_sq == 0.0 {
+                return;
+            }
+
+            let r = r_sq.sqrt();
+            let f = mass / ((r_sq + softening_sq) * r);
+            for k in 0..3 {
+                field[k] += d[k] * f;
+            }
+        };
+
+        while let Some(i) = stack.pop() {
+            let node = &self.nodes[i];
+            if node.mass == 0.0 {
+                continue;
+            }
+
+            match node.children {
+                None => {
+                    // 叶节点中的物体几乎重合，均视为位于质心处，并排除物体自身的质量
+                    let own_mass = if node.bodies.contains(&body) { self.masses[body] } else { 0.0 };
+                    add_point(&node.com, node.mass - own_mass);
+                },
+
+                Some(children) => {
+                    let d = [node.com[0] - pos[0], node.com[1] - pos[1], node.com[2] - pos[2]];
+                    let r = (d[0] * d[0] + d[1] * d[1] + d[2] * d[2]).sqrt();
+
+                    if r > 0.0 && node.half_size * 2.0 / r < theta {
+                        add_point(&node.com, node.mass);
+                    } else {
+                        stack.extend_from_slice(&children);
+                    }
+                },
+            }
+        }
+
+        field
+    }
+}
+
+impl OctreeNode {
+    fn new(center: [f64; 3], half_size: f64)-> Self {
+        Self {
+            center,
+            half_size,
+            mass: 0.0,
+            com: [0.0; 3],
+            children: None,
+            bodies: Vec::new(),
+        }
+    }
+
+    /// 获取坐标所在的子节点序号
+    fn octant_of(&self, pos: &[f64; 3])-> usize {
+        let mut octant = 0;
+        if pos[0] >= self.center[0] { octant |= 1; }
+        if pos[1] >= self.center[1] { octant |= 2; }
+        if pos[2] >= self.center[2] { octant |= 4; }
+        octant
+    }
+}
+
+impl AdaptiveExecutor {
+    pub fn new(gravity: SpaceExecutor)-> Self {
+        Self {
+            gravity,
+            accuracy: BigFloat::from(0.01),
+            max_substeps: 1000,
+            last_substeps: 0,
+        }
+    }
+
+    /// 上一步实际使用的子步数，尚未执行过计算时为0
+    pub fn last_substeps(&self)-> usize {
+        self.last_substeps
+    }
+
+    /// 计算将时长`time`划分的子步数，结果在`1..=max_substeps`之间
+    pub fn substeps(&self, objects: &Objects, time: TimeStep)-> usize {
+        let mut min_timescale: Option<BigFloat> = None;
+
+        for (i, object1) in objects.iter().enumerate() {
+            let attr1 = (*object1).get_physical_attributes();
+
+            for object2 in objects.iter().skip(i + 1) {
+                let attr2 = (*object2).get_physical_attributes();
+                let r = attr1.center.distance(&attr2.center);
+                if r == ZERO {
+                    continue;
+                }
+
+                // 两物体相互接近或掠过所需的时间
+                let relative_speed = (attr2.velocity - attr1.velocity).model();
+                if relative_speed != ZERO {
+                    let t = r / relative_speed;
+                    min_timescale = Some(min_timescale.map_or(t, |m| m.min(&t)));
+                }
+
+                // 两物体由静止开始相互下落所需时间的量级
+                let mu = self.gravity.g * (attr1.mass + attr2.mass);
+                if mu != ZERO {
+                    let t = (r.pow(&BigFloat::from(3)) / mu).sqrt();
+                    min_timescale = Some(min_timescale.map_or(t, |m| m.min(&t)));
+                }
+            }
+        }
+
+        let Some(min_timescale) = min_timescale else {
+            return 1;
+        };
+
+        let n = (time.seconds().abs() / (min_timescale * self.accuracy)).to_f64().ceil();
+        if n.is_nan() || n < 1.0 {
+            1
+        } else {
+            (n as usize).min(self.max_substeps.max(1))
+        }
+    }
+}
+
+impl Default for AdaptiveExecutor {
+    fn default()-> Self {
+        Self::new(SpaceExecutor::default())
+    }
+}
+
+impl Executor for AdaptiveExecutor {
+    fn execute_force(&mut self, objects: &mut Objects, time: TimeStep) {
+        self.gravity.execute_force(objects, time);
+    }
+
+    fn execute_displacement(&mut self, objects: &mut Objects, time: TimeStep) {
+        let n = self.substeps(objects, time);
+        let substep = time / n as u32;
+
+        // 首个子步使用已累加的受力，之后的子步重新计算引力
+        self.gravity.execute_displacement(objects, substep);
+        for _ in 1..n {
+            self.gravity.step(objects, substep);
+        }
+
+        self.last_substeps = n;
+    }
+}
+
+impl Rk4Executor {
+    pub fn new(gravity: SpaceExecutor)-> Self {
+        Self { gravity }
+    }
+
+    /// 计算各质点在给定位置下的加速度
+    fn accelerations(&self, centers: &[Point], masses: &[BigFloat])-> Vec<Vector> {
+        self.gravity
+            .gravitational_forces(centers, masses)
+            .into_iter()
+            .zip(masses.iter())
+            .map(|(f, m)| f / *m)
+            .collect()
+    }
+}
+
+impl Executor for Rk4Executor {
+    fn execute_force(&mut self, objects: &mut Objects, time: TimeStep) {
+        self.gravity.execute_force(objects, time);
+    }
+
+    fn execute_displacement(&mut self, objects: &mut Objects, time: TimeStep) {
+        let t = time.seconds();
+        let half_t = t * BigFloat::from(0.5);
+
+        let mut centers = Vec::with_capacity(objects.len());
+        let mut velocities = Vec::with_capacity(objects.len());
+        let mut masses = Vec::with_capacity(objects.len());
+        for i in objects.iter() {
+            let attr = (*i).get_physical_attributes();
+            centers.push(attr.center);
+            velocities.push(attr.velocity);
+            masses.push(attr.mass);
+        }
+
+        // 以步长`dt`沿导数(`dx`, `dv`)从初始状态前进，得到中间状态
+        let advance = |dx: &[Vector], dv: &[Vector], dt: BigFloat| -> (Vec<Point>, Vec<Vector>) {
+            let c = centers.iter().zip(dx.iter()).map(|(c, d)| *c + *d * dt).collect();
+            let v = velocities.iter().zip(dv.iter()).map(|(v, d)| *v + *d * dt).collect();
+            (c, v)
+        };
+
+        let k1x = velocities.clone();
+        let k1v = self.accelerations(&centers, &masses);
+
+        let (c2, k2x) = advance(&k1x, &k1v, half_t);
+        let k2v = self.accelerations(&c2, &masses);
+
+        let (c3, k3x) = advance(&k2x, &k2v, half_t);
+        let k3v = self.accelerations(&c3, &masses);
+
+        let (c4, k4x) = advance(&k3x, &k3v, t);
+        let k4v = self.accelerations(&c4, &masses);
+
+        let sixth_t = t / BigFloat::from(6);
+        for (i, current_object) in objects.iter_mut().enumerate() {
+            let attr = (*current_object).get_physical_attributes_mut();
+            attr.center += (k1x[i] + (k2x[i] + k3x[i]) * TWO + k4x[i]) * sixth_t;
+            attr.velocity += (k1v[i] + (k2v[i] + k3v[i]) * TWO + k4v[i]) * sixth_t;
+        }
+
+        self.gravity.finish_displacement(objects);
+    }
+}
+
+impl CompositeExecutor {
+    pub fn new(integrator: Box<dyn Integrator>, layers: Vec<Box<dyn Executor>>)-> Self {
+        Self { integrator, layers }
+    }
+}
+
+impl UniformFieldExecutor {
+    pub fn new(field: Vector)-> Self {
+        Self { field }
+    }
+
+    /// 地表附近竖直向下(y轴负方向)的重力场，`g = 9.81 m/s^2`
+    pub fn earth_surface()-> Self {
+        Self::new(Vector {
+            x: ZERO,
+            y: -BigFloat::from(9.81),
+            z: ZERO,
+        })
+    }
+}
+
+impl Default for UniformFieldExecutor {
+    fn default()-> Self {
+        Self::new(Vector::ZERO)
+    }
+}
+
+impl Executor for UniformFieldExecutor {
+    fn execute_force(&mut self, objects: &mut Objects, _time: TimeStep) {
+        for object in objects.iter_mut() {
+            let attr = object.get_physical_attributes_mut();
+            attr.force += self.field * attr.mass;
+        }
+    }
+
+    fn execute_displacement(&mut self, objects: &mut Objects, time: TimeStep) {
+        let t = time.seconds();
+
+        for object in objects.iter_mut() {
+            let attr = object.get_physical_attributes_mut();
+            let acceleration = attr.force / attr.mass;
+            attr.center += attr.velocity * t + acceleration * t.pow(&TWO) * BigFloat::from(0.5);
+            attr.velocity += acceleration * t;
+        }
+    }
+}
+
+impl<E: Executor> InspectExecutor<E> {
+    pub fn new<F>(inner: E, step_hook: F)-> Self
+        where F: FnMut(&Objects) + Send + 'static
+    {
+        Self {
+            inner,
+            step_hook: Some(Box::new(step_hook)),
+        }
+    }
+}
+
+impl<E: Executor> Executor for InspectExecutor<E> {
+    fn execute_force(&mut self, objects: &mut Objects, time: TimeStep) {
+        self.inner.execute_force(objects, time);
+    }
+
+    fn execute_displacement(&mut self, objects: &mut Objects, time: TimeStep) {
+        self.inner.execute_displacement(objects, time);
+
+        if let Some(hook) = self.step_hook.as_mut() {
+            hook(objects);
+        }
+    }
+
+    fn step(&mut self, objects: &mut Objects, time: TimeStep) {
+        self.inner.step(objects, time);
+
+        if let Some(hook) = self.step_hook.as_mut() {
+            hook(objects);
+        }
+    }
+}
+
+impl Executor for CompositeExecutor {
+    fn execute_force(&mut self, objects: &mut Objects, time: TimeStep) {
+        self.integrator.execute_force(objects, time);
+        for layer in self.layers.iter_mut() {
+            layer.execute_force(objects, time);
+        }
+    }
+
+    fn execute_displacement(&mut self, objects: &mut Objects, time: TimeStep) {
+        self.integrator.execute_displacement(objects, time);
+    }
+}
+
+impl Integrator for SpaceExecutor {}
+impl Integrator for VerletExecutor {}
+impl Integrator for F64Executor {}
+impl Integrator for UniformFieldExecutor {}
+
+impl Point {
+    /// 计算两点间的距离
+    pub fn distance(&self, other: &Point)-> BigFloat {
+        let x_sq = (self.x - other.x).pow(&TWO);
+        let y_sq = (self.y - other.y).pow(&TWO);
+        let z_sq = (self.z - other.z).pow(&TWO);
+        (x_sq + y_sq + z_sq).sqrt()
     }
 
     /// 获取以自身为起点，`other`点为终点的向量
@@ -197,13 +2084,69 @@ impl Point {
         }
     }
 
+    /// 以原点为起点、自身为终点的向量
+    pub fn as_vector(&self)-> Vector {
+        Vector {
+            x: self.x,
+            y: self.y,
+            z: self.z,
+        }
+    }
+
+    pub const ZERO: Self = Self { x:ZERO, y:ZERO, z:ZERO };
+
     /// 获取到`other`点的方向上的单位向量
     ///
     /// Panics:
     /// 如果该点与原来的点在同一位置上，则会触发panic，因为向量模为0
     pub fn unit_vector_to(&self, other: &Point)-> Vector {
         let v = self.vector_to(other);
-        v * (ONE / v.model())
+        v / v.model()
+    }
+
+    /// 获取到`other`点的方向上的单位向量
+    ///
+    /// 与`unit_vector_to`不同，两点重合时返回`None`而不是panic
+    pub fn try_unit_vector_to(&self, other: &Point)-> Option<Vector> {
+        let v = self.vector_to(other);
+        if v.is_zero() {
+            None
+        } else {
+            Some(v / v.model())
+        }
+    }
+
+    /// 各坐标是否都不是NaN或无穷大
+    pub fn is_finite(&self)-> bool {
+        [self.x, self.y, self.z].iter().all(|c| !c.is_nan() && !c.is_inf())
+    }
+
+    /// 转换为`[x, y, z]`形式的`f64`数组
+    ///
+    /// 有精度损失，绝对值很大的坐标会丢失低位，超出`f64`范围时变为无穷大
+    pub fn to_f64_array(self)-> [f64; 3] {
+        [self.x.to_f64(), self.y.to_f64(), self.z.to_f64()]
+    }
+
+    /// 从`[x, y, z]`形式的`f64`数组创建
+    pub fn from_f64_array(a: [f64; 3])-> Self {
+        Self {
+            x: BigFloat::from(a[0]),
+            y: BigFloat::from(a[1]),
+            z: BigFloat::from(a[2]),
+        }
+    }
+}
+
+impl From<[BigFloat; 3]> for Point {
+    fn from(a: [BigFloat; 3])-> Self {
+        Self { x: a[0], y: a[1], z: a[2] }
+    }
+}
+
+impl From<Point> for [BigFloat; 3] {
+    fn from(p: Point)-> Self {
+        [p.x, p.y, p.z]
     }
 }
 
@@ -217,35 +2160,390 @@ impl Add<Vector> for Point {
             z: self.z + v.z,
         }
     }
-}
+}
+
+impl AddAssign<Vector> for Point {
+    fn add_assign(&mut self, v: Vector) {
+        self.x += v.x;
+        self.y += v.y;
+        self.z += v.z;
+    }
+}
+
+/// 两点相减得到以`other`为起点、自身为终点的向量
+impl Sub<Point> for Point {
+    type Output = Vector;
+
+    fn sub(self, other: Point)-> Vector {
+        other.vector_to(&self)
+    }
+}
+
+impl Vector {
+    pub fn model(&self)-> BigFloat {
+        (self.x.pow(&TWO) + self.y.pow(&TWO) + self.z.pow(&TWO)).sqrt()
+    }
+
+    /// 判断是否为零向量，即三个分量均为0
+    pub fn is_zero(&self)-> bool {
+        self.x == ZERO && self.y == ZERO && self.z == ZERO
+    }
+
+    /// 计算与`other`的点积
+    pub fn dot(&self, other: &Vector)-> BigFloat {
+        self.x * other.x + self.y * other.y + self.z * other.z
+    }
+
+    /// 计算与`other`的叉积`self × other`
+    pub fn cross(&self, other: &Vector)-> Vector {
+        Vector {
+            x: self.y * other.z - self.z * other.y,
+            y: self.z * other.x - self.x * other.z,
+            z: self.x * other.y - self.y * other.x,
+        }
+    }
+
+    pub const ZERO: Self = Self { x:ZERO, y:ZERO, z:ZERO };
+
+    /// 各分量是否都不是NaN或无穷大
+    pub fn is_finite(&self)-> bool {
+        [self.x, self.y, self.z].iter().all(|c| !c.is_nan() && !c.is_inf())
+    }
+
+    /// 转换为`[x, y, z]`形式的`f64`数组
+    ///
+    /// 有精度损失，绝对值很大的分量会丢失低位，超出`f64`范围时变为无穷大
+    pub fn to_f64_array(self)-> [f64; 3] {
+        [self.x.to_f64(), self.y.to_f64(), self.z.to_f64()]
+    }
+
+    /// 从`[x, y, z]`形式的`f64`数组创建
+    pub fn from_f64_array(a: [f64; 3])-> Self {
+        Self {
+            x: BigFloat::from(a[0]),
+            y: BigFloat::from(a[1]),
+            z: BigFloat::from(a[2]),
+        }
+    }
+}
+
+impl From<[BigFloat; 3]> for Vector {
+    fn from(a: [BigFloat; 3])-> Self {
+        Self { x: a[0], y: a[1], z: a[2] }
+    }
+}
+
+impl From<Vector> for [BigFloat; 3] {
+    fn from(v: Vector)-> Self {
+        [v.x, v.y, v.z]
+    }
+}
+
+impl<'a> Deref for Objects<'a, '_> {
+    type Target = Vec<&'a mut dyn PhysicalObject>;
+
+    fn deref(&self)-> &Self::Target {
+        &self.inner
+    }
+}
+
+impl<'a> DerefMut for Objects<'a, '_> {
+    fn deref_mut(&mut self)-> &mut Self::Target {
+        &mut self.inner
+    }
+}
+
+
+
+/// 计算从质量为`mass`、半径为`radius`的天体表面逃逸所需的速度，以m/s为单位
+///
+/// 即第二宇宙速度`sqrt(2 * G * M / r)`
+pub fn escape_velocity(mass: BigFloat, radius: BigFloat, g: BigFloat)-> BigFloat {
+    (TWO * g * mass / radius).sqrt()
+}
+
+/// 计算绕质量为`mass`的天体在距离`radius`处做圆周运动所需的速度，以m/s为单位
+///
+/// 即`sqrt(G * M / r)`，`radius`取天体半径时为第一宇宙速度
+pub fn orbital_velocity(mass: BigFloat, radius: BigFloat, g: BigFloat)-> BigFloat {
+    (g * mass / radius).sqrt()
+}
+
+/// 由卫星相对于主星的位置与速度计算轨道根数
+///
+/// 使用二体问题的标准引力参数`μ = G * (m1 + m2)`，由活力公式得到半长轴，
+/// 由比角动量`h = r × v`得到离心率与倾角
+pub fn orbital_elements(primary: &PhysicalAttributes, satellite: &PhysicalAttributes, g: BigFloat)-> OrbitalElements {
+    let mu = g * (primary.mass + satellite.mass);
+    let r = primary.center.vector_to(&satellite.center);
+    let v = satellite.velocity - primary.velocity;
+
+    // 比轨道能量 ε = v^2 / 2 - μ / r
+    let energy = v.dot(&v) / TWO - mu / r.model();
+    let semi_major_axis = -mu / (TWO * energy);
+
+    // e = sqrt(1 + 2 * ε * h^2 / μ^2)，舍入误差可能使根号内略小于0
+    let h = r.cross(&v);
+    let h_sq = h.dot(&h);
+    let e_sq = ONE + TWO * energy * h_sq / mu.pow(&TWO);
+    let eccentricity = if e_sq.is_negative() { ZERO } else { e_sq.sqrt() };
+
+    let inclination = if h_sq == ZERO {
+        ZERO
+    } else {
+        (h.z / h_sq.sqrt()).acos()
+    };
+
+    let period = if energy.is_negative() {
+        Some(TWO * PI * (semi_major_axis.pow(&BigFloat::from(3)) / mu).sqrt())
+    } else {
+        None
+    };
+
+    OrbitalElements {
+        semi_major_axis,
+        eccentricity,
+        inclination,
+        period,
+    }
+}
+
+/// 求解开普勒方程，计算二体问题中卫星经过时间`t`(以s为单位)后相对于主星的位置与速度
+///
+/// `r0`与`v0`为初始时刻的相对位置与相对速度，`mu`为标准引力参数`G * (m1 + m2)`。
+/// 使用以偏近点角之差`ΔE`表示的f、g函数，`ΔE`由牛顿法求解。
+/// 只支持椭圆轨道，轨道不是椭圆或`r0`为零向量时返回`None`
+pub fn kepler_propagate(r0: Vector, v0: Vector, mu: BigFloat, t: BigFloat)-> Option<(Vector, Vector)> {
+    const MAX_ITERATIONS: usize = 64;
+
+    let r0_len = r0.model();
+    if r0_len == ZERO {
+        return None;
+    }
+
+    // 由活力公式得到半长轴，比轨道能量不为负时不是椭圆轨道
+    let energy = v0.dot(&v0) / TWO - mu / r0_len;
+    if !energy.is_negative() {
+        return None;
+    }
+    let a = -mu / (TWO * energy);
+    let sqrt_a = a.sqrt();
+    let n = (mu / a.pow(&BigFloat::from(3))).sqrt();
+
+    // 运动是周期性的，先将时间约化到一个周期之内以减少牛顿法的迭代次数
+    let period = TWO * PI / n;
+    let t = t - (t / period).floor() * period;
+
+    let sigma0 = r0.dot(&v0) / mu.sqrt();
+    let c1 = ONE - r0_len / a;
+    let c2 = sigma0 / sqrt_a;
+    let mean = n * t;
+    let tolerance: BigFloat = "1e-30".parse().unwrap();
+
+    // 求解 n * t = ΔE - c1 * sin(ΔE) + c2 * (1 - cos(ΔE))
+    let mut e = mean;
+    for _ in 0..MAX_ITERATIONS {
+        let (sin, cos) = (e.sin(), e.cos());
+        let f = e - c1 * sin + c2 * (ONE - cos) - mean;
+        let df = ONE - c1 * cos + c2 * sin;
+        let delta = f / df;
+        e -= delta;
+
+        if delta.abs() < tolerance {
+            break;
+        }
+    }
+
+    let (sin, cos) = (e.sin(), e.cos());
+    let r_len = a + (r0_len - a) * cos + sigma0 * sqrt_a * sin;
+
+    let f = ONE - a / r0_len * (ONE - cos);
+    let g = t - (e - sin) / n;
+    let f_dot = -(mu * a).sqrt() / (r_len * r0_len) * sin;
+    let g_dot = ONE - a / r_len * (ONE - cos);
+
+    Some((r0 * f + v0 * g, r0 * f_dot + v0 * g_dot))
+}
+
+/// 判断卫星是否被主星引力束缚，即比轨道能量`v^2 / 2 - μ / r`是否小于0
+///
+/// 与`orbital_elements`相同，`μ = G * (m1 + m2)`，位置与速度均取卫星相对于主星的值。
+/// 两者重合时视为被束缚
+pub fn is_bound(primary: &PhysicalAttributes, satellite: &PhysicalAttributes, g: BigFloat)-> bool {
+    let mu = g * (primary.mass + satellite.mass);
+    let r = primary.center.distance(&satellite.center);
+    if r == ZERO {
+        return true;
+    }
+
+    let v = satellite.velocity - primary.velocity;
+    (v.dot(&v) / TWO - mu / r).is_negative()
+}
+
+/// 计算主星在卫星上的点`at`处产生的潮汐加速度，以m/s^2为单位
+///
+/// 即主星在`at`处与在卫星中心`satellite_center`处产生的引力加速度之差。卫星整体以中心处的加速度运动，
+/// 因此该差值为`at`处相对于卫星的加速度：在两天体连线上朝向与背向主星的两点处均指向卫星外侧，
+/// 在与连线垂直的方向上指向卫星中心。`at`或`satellite_center`与主星中心重合时结果没有意义
+pub fn tidal_field(primary: &PhysicalAttributes, at: Point, satellite_center: Point, g: BigFloat)-> Vector {
+    let acceleration = |p: Point| {
+        let r = primary.center - p;
+        let d = r.model();
+        r * (g * primary.mass / (d * d * d))
+    };
+
+    acceleration(at) - acceleration(satellite_center)
+}
+
+/// 将字符串解析为`BigFloat`，NaN与无穷大视为非法
+pub fn parse_bigfloat(s: &str)-> Result<BigFloat, PhysicsError> {
+    match BigFloat::parse(s.trim()) {
+        Some(n) if !n.is_nan() && !n.is_inf() => Ok(n),
+        _ => Err(PhysicsError::InvalidNumber(s.to_owned())),
+    }
+}
+
+/// 检查质量为正数
+///
+/// 质量为0时计算加速度会除以0，因此同样视为非法
+pub fn check_mass(mass: BigFloat)-> Result<BigFloat, PhysicsError> {
+    if mass.is_negative() || mass == ZERO {
+        Err(PhysicsError::NonPositiveMass(mass))
+    } else {
+        Ok(mass)
+    }
+}
+
+/// 检查半径不为负数
+pub fn check_radius(radius: BigFloat)-> Result<BigFloat, PhysicsError> {
+    if radius.is_negative() {
+        Err(PhysicsError::NegativeRadius(radius))
+    } else {
+        Ok(radius)
+    }
+}
+
+/// 计算一组点的轴对齐包围盒，返回最小角与最大角
+///
+/// 没有点时返回`None`
+pub fn bounding_box<I>(points: I)-> Option<(Point, Point)>
+    where I: IntoIterator<Item = Point>
+{
+    let mut points = points.into_iter();
+    let first = points.next()?;
+
+    Some(points.fold((first, first), |(min, max), p| (
+        Point { x: min.x.min(&p.x), y: min.y.min(&p.y), z: min.z.min(&p.z) },
+        Point { x: max.x.max(&p.x), y: max.y.max(&p.y), z: max.z.max(&p.z) },
+    )))
+}
+
+impl UnitSystem {
+    /// 国际单位制
+    pub const SI: Self = Self { length: ONE, mass: ONE, time: ONE };
+
+    /// 以`length`(m)与`mass`(kg)为单位，并选取时间单位使引力常数`g_si`在该单位制下为1
+    ///
+    /// 即时间单位为`sqrt(length^3 / (g_si * mass))`，用于`G = 1`的自然单位制
+    pub fn with_unit_g(length: BigFloat, mass: BigFloat, g_si: BigFloat)-> Self {
+        Self {
+            length,
+            mass,
+            time: (length.pow(&BigFloat::from(3)) / (g_si * mass)).sqrt(),
+        }
+    }
+
+    /// 国际单位制下的引力常数`g_si`在该单位制下的值
+    pub fn gravitational_constant(&self, g_si: BigFloat)-> BigFloat {
+        g_si * self.mass * self.time.pow(&TWO) / self.length.pow(&BigFloat::from(3))
+    }
+
+    /// 将以该单位制表示的物理属性换算为国际单位制
+    pub fn convert_to_si(&self, attributes: &PhysicalAttributes)-> PhysicalAttributes {
+        Self::convert(attributes, self.length, self.mass, self.time)
+    }
+
+    /// 将以国际单位制表示的物理属性换算为该单位制
+    pub fn convert_from_si(&self, attributes: &PhysicalAttributes)-> PhysicalAttributes {
+        Self::convert(attributes, ONE / self.length, ONE / self.mass, ONE / self.time)
+    }
+
+    fn convert(attributes: &PhysicalAttributes, length: BigFloat, mass: BigFloat, time: BigFloat)-> PhysicalAttributes {
+        PhysicalAttributes {
+            center: Point::ZERO + attributes.center.as_vector() * length,
+            velocity: attributes.velocity * (length / time),
+            force: attributes.force * (mass * length / time.pow(&TWO)),
+            mass: attributes.mass * mass,
+            radius: attributes.radius * length,
+            charge: attributes.charge,
+        }
+    }
+}
+
+impl TimeStep {
+    pub fn forward(duration: Duration)-> Self {
+        Self { duration, backward: false }
+    }
+
+    pub fn backward(duration: Duration)-> Self {
+        Self { duration, backward: true }
+    }
+
+    /// 由带符号的毫秒数构造，负值表示时间倒流
+    pub fn from_millis_f64(millis: f64)-> Self {
+        Self {
+            duration: Duration::from_secs_f64(millis.abs() / 1000.0),
+            backward: millis < 0.0,
+        }
+    }
+
+    /// 以`base`乘以带符号的倍数`factor`构造，`factor`为负时时间倒流
+    ///
+    /// 以纳秒为精度计算，`factor`很小时不会被截断为0。
+    /// 结果超出`Duration`的范围时取`Duration::MAX`，`factor`为NaN时取0
+    pub fn scaled(base: Duration, factor: f64)-> Self {
+        let duration = match Duration::try_from_secs_f64(base.as_secs_f64() * factor.abs()) {
+            Ok(duration) => duration,
+            Err(_) if factor.is_nan() => Duration::ZERO,
+            Err(_) => Duration::MAX,
+        };
+
+        Self {
+            duration,
+            backward: factor < 0.0,
+        }
+    }
+
+    /// 将已经过的时间`elapsed`推进一步，时间倒流时减少，不会小于0
+    pub fn advance(&self, elapsed: Duration)-> Duration {
+        if self.backward {
+            elapsed.saturating_sub(self.duration)
+        } else {
+            elapsed.saturating_add(self.duration)
+        }
+    }
 
-impl AddAssign<Vector> for Point {
-    fn add_assign(&mut self, v: Vector) {
-        self.x += v.x;
-        self.y += v.y;
-        self.z += v.z;
+    /// 以秒为单位的带符号步长
+    pub fn seconds(&self)-> BigFloat {
+        let t = BigFloat::from(self.duration.as_nanos()) / BigFloat::from(1e9);
+        if self.backward { -t } else { t }
     }
 }
 
-impl Vector {
-    pub fn model(&self)-> BigFloat {
-        (self.x.pow(&TWO) + self.y.pow(&TWO) + self.z.pow(&TWO)).sqrt()
+impl From<Duration> for TimeStep {
+    fn from(duration: Duration)-> Self {
+        Self::forward(duration)
     }
-
-    pub const ZERO: Self = Self { x:ZERO, y:ZERO, z:ZERO };
 }
 
-impl<'a> Deref for Objects<'a, '_> {
-    type Target = Vec<&'a mut dyn PhysicalObject>;
-
-    fn deref(&self)-> &Self::Target {
-        &self.inner
-    }
-}
+impl Div<u32> for TimeStep {
+    type Output = Self;
 
-impl<'a> DerefMut for Objects<'a, '_> {
-    fn deref_mut(&mut self)-> &mut Self::Target {
-        &mut self.inner
+    fn div(self, rhs: u32)-> Self {
+        Self {
+            duration: self.duration / rhs,
+            backward: self.backward,
+        }
     }
 }
 
@@ -269,9 +2567,889 @@ pub trait PhysicalObject {
 
 /// 实现该trait可以用于执行物理计算
 pub trait Executor {
+    /// 将所有物体的受力清零
+    fn reset_forces(&mut self, objects: &mut Objects) {
+        for object in objects.iter_mut() {
+            object.get_physical_attributes_mut().force = Vector::ZERO;
+        }
+    }
+
     /// 计算受力
-    fn execute_force(&mut self, objects: &mut Objects, time: Duration);
+    ///
+    /// 计算结果累加到物体已有的`force`上，调用前应先调用`reset_forces`
+    fn execute_force(&mut self, objects: &mut Objects, time: TimeStep);
 
     /// 计算速度与位移
-    fn execute_displacement(&mut self, objects: &mut Objects, time: Duration);
+    fn execute_displacement(&mut self, objects: &mut Objects, time: TimeStep);
+
+    /// 执行完整的一步计算
+    ///
+    /// 默认先清零受力，再计算受力与位移，需要在中间状态重新计算受力的执行器可以覆盖此方法
+    fn step(&mut self, objects: &mut Objects, time: TimeStep) {
+        self.reset_forces(objects);
+        self.execute_force(objects, time);
+        self.execute_displacement(objects, time);
+    }
+
+    /// 连续执行`count`步计算
+    ///
+    /// 在同一个`Objects`上循环调用`step`，避免每一步重新收集物体，适合性能测试与离线计算
+    fn step_many(&mut self, objects: &mut Objects, time: TimeStep, count: usize) {
+        for _ in 0..count {
+            self.step(objects, time);
+        }
+    }
+}
+
+/// 只根据物体上累加的`force`计算位移的执行器，可以作为`CompositeExecutor`的`integrator`
+///
+/// `Rk4Executor`与`AdaptiveExecutor`在积分过程中自行重新计算引力，会忽略其他层施加的受力，因此不实现该trait
+pub trait Integrator: Executor {}
+
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::{ Arc, Mutex };
+
+    fn point(x: f64, y: f64, z: f64)-> Point {
+        Point::from_f64_array([x, y, z])
+    }
+
+    fn vector(x: f64, y: f64, z: f64)-> Vector {
+        Vector::from_f64_array([x, y, z])
+    }
+
+    fn body(mass: f64, center: Point, velocity: Vector)-> BodySnapshot {
+        BodySnapshot {
+            uid: Uuid::new_v4(),
+            attributes: PhysicalAttributes::new(center, velocity, BigFloat::from(mass), ZERO).unwrap(),
+        }
+    }
+
+    fn objects(state: &mut [BodySnapshot])-> Objects<'_, '_> {
+        Objects::new(state.iter_mut().map(|s| s as &mut dyn PhysicalObject).collect())
+    }
+
+    /// 两个质量为1、相距1的物体绕质心做圆周运动(G = 1)
+    ///
+    /// 引力`G * m^2 / d^2`提供向心力`m * v^2 / (d / 2)`，因此`v = sqrt(G * m / (2 * d))`
+    fn circular_pair()-> Vec<BodySnapshot> {
+        let v = 0.5f64.sqrt();
+        vec![
+            body(1.0, point(0.5, 0.0, 0.0), vector(0.0, v, 0.0)),
+            body(1.0, point(-0.5, 0.0, 0.0), vector(0.0, -v, 0.0)),
+        ]
+    }
+
+    fn separation(state: &[BodySnapshot])-> f64 {
+        state[0].attributes.center.distance(&state[1].attributes.center).to_f64()
+    }
+
+    #[test]
+    fn f64_executor_matches_bigfloat_over_short_run() {
+        let mut exact = circular_pair();
+        let mut approx = exact.clone();
+        let dt = TimeStep::from_millis_f64(10.0);
+
+        SpaceExecutor::with_g(ONE).step_many(&mut objects(&mut exact), dt, 200);
+        F64Executor::new(SpaceExecutor::with_g(ONE)).step_many(&mut objects(&mut approx), dt, 200);
+
+        for (e, a) in exact.iter().zip(approx.iter()) {
+            let d = (e.attributes.center - a.attributes.center).model().to_f64();
+            assert!(d < 1e-9, "positions differ by {}", d);
+        }
+    }
+
+    #[test]
+    fn f64_step_many_clamps_every_step() {
+        let start = vec![
+            body(1.0, point(-1.0, 0.0, 0.0), Vector::ZERO),
+            body(1.0, point(1.0, 0.0, 0.0), Vector::ZERO),
+        ];
+        let dt = TimeStep::from_millis_f64(100.0);
+        let executor = || {
+            let mut gravity = SpaceExecutor::with_g(ONE);
+            gravity.max_velocity = Some(BigFloat::from(0.01));
+            F64Executor::new(gravity)
+        };
+
+        let mut batched = start.clone();
+        executor().step_many(&mut objects(&mut batched), dt, 20);
+
+        let mut single = start;
+        let mut stepper = executor();
+        for _ in 0..20 {
+            stepper.step(&mut objects(&mut single), dt);
+        }
+
+        for (b, s) in batched.iter().zip(single.iter()) {
+            assert!(b.attributes.velocity.model().to_f64() <= 0.01 + 1e-15);
+            let d = (b.attributes.center - s.attributes.center).model().to_f64();
+            assert!(d < 1e-12, "batched and single steps differ by {}", d);
+        }
+    }
+
+    #[test]
+    fn adaptive_flyby_uses_more_substeps_than_distant_pair() {
+        let dt = TimeStep::from_millis_f64(100.0);
+        let mut flyby = vec![
+            body(1.0, point(-0.01, 0.0, 0.0), vector(0.0, 1.0, 0.0)),
+            body(1.0, point(0.01, 0.0, 0.0), vector(0.0, -1.0, 0.0)),
+        ];
+        let mut distant = vec![
+            body(1.0, point(-100.0, 0.0, 0.0), Vector::ZERO),
+            body(1.0, point(100.0, 0.0, 0.0), Vector::ZERO),
+        ];
+
+        let mut executor = AdaptiveExecutor::new(SpaceExecutor::with_g(ONE));
+        executor.step(&mut objects(&mut flyby), dt);
+        let flyby_substeps = executor.last_substeps();
+        executor.step(&mut objects(&mut distant), dt);
+        let distant_substeps = executor.last_substeps();
+
+        assert_eq!(distant_substeps, 1);
+        assert!(flyby_substeps > 10, "flyby used {} substeps", flyby_substeps);
+    }
+
+    #[test]
+    fn adaptive_execute_passthroughs_subdivide_like_step() {
+        let dt = TimeStep::from_millis_f64(100.0);
+        let mut stepped = circular_pair();
+        let mut manual = stepped.clone();
+
+        let mut executor = AdaptiveExecutor::new(SpaceExecutor::with_g(ONE));
+        executor.step(&mut objects(&mut stepped), dt);
+        let expected = executor.last_substeps();
+
+        let mut executor = AdaptiveExecutor::new(SpaceExecutor::with_g(ONE));
+        let mut manual_objects = objects(&mut manual);
+        executor.reset_forces(&mut manual_objects);
+        executor.execute_force(&mut manual_objects, dt);
+        executor.execute_displacement(&mut manual_objects, dt);
+        drop(manual_objects);
+
+        assert!(expected > 1);
+        assert_eq!(executor.last_substeps(), expected);
+        for (s, m) in stepped.iter().zip(manual.iter()) {
+            assert_eq!(s.attributes.center, m.attributes.center);
+        }
+    }
+
+    #[test]
+    fn small_timewrap_keeps_non_zero_step() {
+        let step = TimeStep::scaled(Duration::from_millis(30), 1.0 / 1000.0);
+        assert!(step.seconds() > ZERO);
+        assert_eq!(step.advance(Duration::ZERO), Duration::from_micros(30));
+    }
+
+    #[test]
+    fn tiny_timewrap_scales_step_proportionally() {
+        let base = Duration::from_millis(30);
+        for (factor, nanos) in [(1e-3, 30_000), (1e-5, 300), (1e-7, 3)] {
+            assert_eq!(TimeStep::scaled(base, factor).advance(Duration::ZERO), Duration::from_nanos(nanos));
+        }
+
+        assert_eq!(TimeStep::scaled(Duration::MAX, 2.0).advance(Duration::ZERO), Duration::MAX);
+        assert_eq!(TimeStep::scaled(base, f64::MAX).advance(Duration::ZERO), Duration::MAX);
+    }
+
+    fn with_infinite_force()-> Vec<BodySnapshot> {
+        let mut state = circular_pair();
+        state[0].attributes.force = Vector { x: num_bigfloat::INF_POS, y: ZERO, z: ZERO };
+        state
+    }
+
+    #[test]
+    fn infinite_force_is_detected() {
+        let mut state = with_infinite_force();
+        let bad = state[0].uid;
+        let mut objects = objects(&mut state);
+
+        let mut executor = SpaceExecutor::with_g(ONE);
+        executor.execute_displacement(&mut objects, TimeStep::from_millis_f64(10.0));
+
+        assert_eq!(executor.check_finite(&objects), vec![bad]);
+    }
+
+    #[cfg(debug_assertions)]
+    #[test]
+    #[should_panic(expected = "non-finite")]
+    fn infinite_force_halts_when_requested() {
+        let mut state = with_infinite_force();
+        let mut executor = SpaceExecutor::with_g(ONE);
+        executor.halt_on_non_finite = true;
+        executor.execute_displacement(&mut objects(&mut state), TimeStep::from_millis_f64(10.0));
+    }
+
+    #[test]
+    fn head_on_equal_masses_merge_at_rest() {
+        let mut state = vec![
+            body(2.0, point(-0.5, 0.0, 0.0), vector(3.0, 0.0, 0.0)),
+            body(2.0, point(0.5, 0.0, 0.0), vector(-3.0, 0.0, 0.0)),
+        ];
+        for s in state.iter_mut() {
+            s.attributes.radius = ONE;
+        }
+        let absorbed = state[1].uid;
+        let mut objects = objects(&mut state);
+
+        let mut executor = CollisionExecutor::new(SpaceExecutor::with_g(ZERO));
+        executor.step(&mut objects, TimeStep::from_millis_f64(1.0));
+        drop(objects);
+
+        assert_eq!(executor.take_removed(), vec![absorbed]);
+        assert!(executor.take_removed().is_empty());
+        assert_eq!(state[0].attributes.mass.to_f64(), 4.0);
+        assert!(state[0].attributes.velocity.is_zero());
+    }
+
+    #[test]
+    fn composite_sums_gravity_and_constant_force() {
+        let mut state = vec![
+            body(1.0, point(-1.0, 0.0, 0.0), Vector::ZERO),
+            body(1.0, point(1.0, 0.0, 0.0), Vector::ZERO),
+        ];
+        let mut objects = objects(&mut state);
+
+        let mut executor = CompositeExecutor::new(
+            Box::new(SpaceExecutor::with_g(ONE)),
+            vec![Box::new(UniformFieldExecutor::new(vector(0.0, -1.0, 0.0)))],
+        );
+        executor.step(&mut objects, TimeStep::from_millis_f64(100.0));
+        drop(objects);
+
+        // 引力为0.25 N，外力场为1 N，位移为a * t^2 / 2
+        assert_eq!(state[0].attributes.force.to_f64_array(), [0.25, -1.0, 0.0]);
+        assert_eq!(state[1].attributes.force.to_f64_array(), [-0.25, -1.0, 0.0]);
+        let center = state[0].attributes.center.to_f64_array();
+        assert!((center[0] - (-1.0 + 0.00125)).abs() < 1e-12);
+        assert!((center[1] - (-0.005)).abs() < 1e-12);
+    }
+
+    #[test]
+    fn closest_pair_is_tracked_only_when_enabled() {
+        let mut state = vec![
+            body(1.0, point(0.0, 0.0, 0.0), Vector::ZERO),
+            body(1.0, point(10.0, 0.0, 0.0), Vector::ZERO),
+            body(1.0, point(10.0, 3.0, 0.0), Vector::ZERO),
+        ];
+        let (near1, near2) = (state[1].uid, state[2].uid);
+        let mut objects = objects(&mut state);
+
+        let mut executor = SpaceExecutor::with_g(ONE);
+        executor.execute_force(&mut objects, TimeStep::default());
+        assert_eq!(executor.closest_approach, None);
+
+        executor.track_closest = true;
+        executor.execute_force(&mut objects, TimeStep::default());
+        let (a, b, distance) = executor.closest_approach.unwrap();
+        assert_eq!((a, b), (near1, near2));
+        assert_eq!(distance.to_f64(), 3.0);
+    }
+
+    #[test]
+    fn verlet_keeps_circular_orbit_radius() {
+        let mut state = circular_pair();
+        let start = separation(&state);
+        let mut executor = VerletExecutor::new(SpaceExecutor::with_g(ONE));
+
+        let dt = TimeStep::from_millis_f64(10.0);
+        let mut objects = objects(&mut state);
+        executor.step_many(&mut objects, dt, 10000);
+        drop(objects);
+
+        let end = separation(&state);
+        assert!((end - start).abs() / start < 0.01, "radius drifted from {} to {}", start, end);
+    }
+
+    #[test]
+    fn softened_force_stays_bounded_for_near_coincident_pair() {
+        let mut gravity = SpaceExecutor::with_g(ONE);
+        gravity.softening = BigFloat::from(0.1);
+        let centers = [point(0.0, 0.0, 0.0), point(1e-12, 0.0, 0.0)];
+        let masses = [ONE, ONE];
+
+        // 软化后引力不超过G * m1 * m2 / ε^2
+        let forces = gravity.gravitational_forces(&centers, &masses);
+        for force in forces {
+            let size = force.model().to_f64();
+            assert!(size.is_finite());
+            assert!(size <= 100.0, "force {} exceeds the softened bound", size);
+        }
+    }
+
+    #[test]
+    fn try_unit_vector_to_coincident_point_is_none() {
+        let p = point(1.0, 2.0, 3.0);
+        assert_eq!(p.try_unit_vector_to(&p), None);
+        assert_eq!(p.try_unit_vector_to(&point(1.0, 2.0, 5.0)), Some(vector(0.0, 0.0, 1.0)));
+    }
+
+    #[test]
+    fn unit_g_unit_masses_at_unit_distance_give_one_newton() {
+        let forces = SpaceExecutor::with_g(ONE).gravitational_forces(&[point(0.0, 0.0, 0.0), point(1.0, 0.0, 0.0)], &[ONE, ONE]);
+        assert_eq!(forces, vec![vector(1.0, 0.0, 0.0), vector(-1.0, 0.0, 0.0)]);
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn earth_attributes_round_trip_through_json() {
+        let earth = PhysicalAttributes::new(
+            Point::ZERO,
+            Vector { x: ZERO, y: "29780.5".parse().unwrap(), z: ZERO },
+            "5.9722e24".parse().unwrap(),
+            "6.371e6".parse().unwrap(),
+        ).unwrap();
+
+        let json = serde_json::to_string(&earth).unwrap();
+        let parsed: PhysicalAttributes = serde_json::from_str(&json).unwrap();
+        assert_eq!(parsed, earth);
+    }
+
+    #[test]
+    fn bound_pair_energy_is_negative_and_steady() {
+        let mut state = circular_pair();
+        let mut objects = objects(&mut state);
+        let mut executor = VerletExecutor::new(SpaceExecutor::with_g(ONE));
+
+        let start = executor.gravity.total_energy(&objects).to_f64();
+        executor.step_many(&mut objects, TimeStep::from_millis_f64(10.0), 500);
+        let end = executor.gravity.total_energy(&objects).to_f64();
+
+        // 两个单位质量相距1、速度各为sqrt(0.5)时E = 0.5 - 1 = -0.5
+        assert!((start + 0.5).abs() < 1e-12);
+        assert!(end < 0.0);
+        assert!((end - start).abs() / start.abs() < 1e-3, "energy drifted from {} to {}", start, end);
+    }
+
+    #[test]
+    fn barnes_hut_with_zero_theta_matches_exact_forces() {
+        let centers = [
+            point(0.0, 0.0, 0.0),
+            point(1.0, 0.5, 0.0),
+            point(-2.0, 1.0, 0.5),
+            point(3.0, -1.5, -1.0),
+            point(0.5, 4.0, 2.0),
+        ];
+        let masses: Vec<BigFloat> = [1.0, 2.0, 0.5, 3.0, 1.5].into_iter().map(BigFloat::from).collect();
+
+        let exact = SpaceExecutor::with_g(ONE).gravitational_forces(&centers, &masses);
+        let approx = BarnesHutExecutor::new(SpaceExecutor::with_g(ONE), 0.0).gravitational_forces(&centers, &masses);
+
+        for (e, a) in exact.iter().zip(approx.iter()) {
+            let error = (*e - *a).model().to_f64() / e.model().to_f64();
+            assert!(error < 1e-12, "relative error {}", error);
+        }
+    }
+
+    #[test]
+    fn barnes_hut_approximates_exact_forces() {
+        // 一个致密的星团与一个较远的稀疏星团，质量与分布均不对称，八叉树有多层
+        let mut rng = crate::rng::Xorshift64::new(3);
+        let mut centers = Vec::new();
+        let mut masses = Vec::new();
+        for i in 0..160 {
+            let (offset, spread) = if i < 120 { (0.0, 1.0) } else { (20.0, 4.0) };
+            centers.push(point(
+                offset + rng.range_f64(-spread, spread),
+                rng.range_f64(-spread, spread) * 0.5,
+                rng.range_f64(-spread, spread) * 0.25,
+            ));
+            masses.push(BigFloat::from(rng.range_f64(0.1, 2.0)));
+        }
+
+        let exact = SpaceExecutor::with_g(ONE).gravitational_forces(&centers, &masses);
+        let approx = BarnesHutExecutor::new(SpaceExecutor::with_g(ONE), 0.5).gravitational_forces(&centers, &masses);
+
+        let errors: Vec<f64> = exact
+            .iter()
+            .zip(approx.iter())
+            .map(|(e, a)| (*e - *a).model().to_f64() / e.model().to_f64())
+            .collect();
+        let max = errors.iter().copied().fold(0.0, f64::max);
+        let mean = errors.iter().sum::<f64>() / errors.len() as f64;
+
+        assert!(max < 0.1, "max relative error {}", max);
+        assert!(mean < 0.01, "mean relative error {}", mean);
+
+        // 与精确结果不同，说明确实使用了近似
+        assert!(max > 1e-9, "max relative error {}", max);
+    }
+
+    #[test]
+    fn center_of_mass_is_weighted_midpoint() {
+        let mut state = vec![
+            body(1.0, point(-3.0, 0.0, 0.0), Vector::ZERO),
+            body(3.0, point(1.0, 4.0, 0.0), Vector::ZERO),
+        ];
+        let com = SpaceExecutor::default().center_of_mass(&objects(&mut state));
+        assert_eq!(com, point(0.0, 3.0, 0.0));
+    }
+
+    #[test]
+    fn orbiting_body_has_angular_momentum_about_origin() {
+        let mut state = vec![body(2.0, point(3.0, 0.0, 0.0), vector(0.0, 5.0, 0.0))];
+        let l = SpaceExecutor::default().total_angular_momentum(&objects(&mut state), &Point::ZERO);
+        assert_eq!(l, vector(0.0, 0.0, 30.0));
+    }
+
+    #[test]
+    fn recentering_zeroes_total_momentum() {
+        let mut state = vec![
+            body(5.0, point(1.0, 2.0, 0.0), vector(0.0, 0.0, 0.0)),
+            body(1.0, point(4.0, 2.0, 1.0), vector(0.5, 3.0, -1.0)),
+        ];
+        let mut objects = objects(&mut state);
+        let mut executor = SpaceExecutor::default();
+
+        executor.recenter_to_com(&mut objects);
+
+        assert!(executor.total_momentum(&objects).model().to_f64() < 1e-30);
+        assert!(executor.center_of_mass(&objects).as_vector().model().to_f64() < 1e-30);
+    }
+
+    #[test]
+    fn circular_orbit_elements() {
+        // μ = G * (m1 + m2) = 2，r = 2时圆轨道速度为sqrt(μ / r) = 1
+        let primary = body(1.0, Point::ZERO, Vector::ZERO);
+        let satellite = body(1.0, point(2.0, 0.0, 0.0), vector(0.0, 1.0, 0.0));
+        let elements = orbital_elements(&primary.attributes, &satellite.attributes, ONE);
+
+        let expected_period = 2.0 * std::f64::consts::PI * (2.0f64.powi(3) / 2.0).sqrt();
+        assert!(elements.eccentricity.to_f64() < 1e-12);
+        assert!((elements.semi_major_axis.to_f64() - 2.0).abs() < 1e-12);
+        assert!((elements.period.unwrap().to_f64() - expected_period).abs() < 1e-9);
+    }
+
+    #[test]
+    fn vector_sub() {
+        assert_eq!(vector(3.0, 5.0, 7.0) - vector(1.0, 2.0, 3.0), vector(2.0, 3.0, 4.0));
+    }
+
+    #[test]
+    fn vector_sub_assign() {
+        let mut v = vector(3.0, 5.0, 7.0);
+        v -= vector(1.0, 2.0, 3.0);
+        assert_eq!(v, vector(2.0, 3.0, 4.0));
+    }
+
+    #[test]
+    fn vector_div() {
+        assert_eq!(vector(2.0, -4.0, 6.0) / TWO, vector(1.0, -2.0, 3.0));
+    }
+
+    #[test]
+    fn point_sub() {
+        assert_eq!(point(4.0, 6.0, 8.0) - point(1.0, 2.0, 3.0), vector(3.0, 4.0, 5.0));
+    }
+
+    #[test]
+    fn vector_neg() {
+        assert_eq!(-vector(1.0, -2.0, 3.0), vector(-1.0, 2.0, -3.0));
+    }
+
+    #[test]
+    fn vector_is_zero() {
+        assert!(Vector::ZERO.is_zero());
+        assert!(!vector(0.0, 0.0, 1e-30).is_zero());
+    }
+
+    #[test]
+    fn equal_mass_elastic_bounce_swaps_velocities() {
+        let mut state = vec![
+            body(1.0, point(-0.5, 0.0, 0.0), vector(1.0, 0.0, 0.0)),
+            body(1.0, point(0.5, 0.0, 0.0), vector(-0.5, 0.0, 0.0)),
+        ];
+        for b in state.iter_mut() {
+            b.attributes.radius = ONE;
+        }
+
+        let mut executor = BounceExecutor::new(SpaceExecutor::with_g(ZERO), ONE);
+        executor.step(&mut objects(&mut state), TimeStep::from_millis_f64(1.0));
+
+        let speeds: Vec<f64> = state.iter().map(|b| b.attributes.velocity.x.to_f64()).collect();
+        assert!((speeds[0] + 0.5).abs() < 1e-12 && (speeds[1] - 1.0).abs() < 1e-12, "velocities {:?}", speeds);
+    }
+
+    #[test]
+    fn drag_slows_body_monotonically_without_gravity() {
+        let mut state = vec![body(2.0, Point::ZERO, vector(10.0, 5.0, 0.0))];
+        let mut objects = objects(&mut state);
+        let mut executor = DragExecutor::new(SpaceExecutor::with_g(ZERO), BigFloat::from(0.1));
+
+        let mut last = 10f64.hypot(5.0);
+        for _ in 0..50 {
+            executor.step(&mut objects, TimeStep::from_millis_f64(100.0));
+            let speed = objects[0].get_physical_attributes().velocity.model().to_f64();
+            assert!(speed < last && speed > 0.0, "speed went from {} to {}", last, speed);
+            last = speed;
+        }
+    }
+
+    #[test]
+    fn spring_oscillates_about_rest_length() {
+        let mut state = vec![
+            body(1.0, point(-0.75, 0.0, 0.0), Vector::ZERO),
+            body(1.0, point(0.75, 0.0, 0.0), Vector::ZERO),
+        ];
+        let spring = Spring { a: state[0].uid, b: state[1].uid, rest_length: ONE, k: ONE };
+        let mut executor = SpringExecutor::new(SpaceExecutor::with_g(ZERO), vec![spring]);
+
+        // 约化质量为0.5，周期为2π * sqrt(0.5)约4.44s
+        let (mut min, mut max) = (f64::MAX, f64::MIN);
+        for _ in 0..500 {
+            executor.step(&mut objects(&mut state), TimeStep::from_millis_f64(10.0));
+            let d = separation(&state);
+            min = min.min(d);
+            max = max.max(d);
+        }
+
+        assert!((min - 0.5).abs() < 0.05, "minimum separation {}", min);
+        assert!((max - 1.5).abs() < 0.05, "maximum separation {}", max);
+    }
+
+    #[test]
+    fn like_charges_repel_and_opposite_charges_attract() {
+        let force_on_first = |q1: f64, q2: f64| {
+            let mut state = vec![
+                body(1.0, point(-1.0, 0.0, 0.0), Vector::ZERO),
+                body(1.0, point(1.0, 0.0, 0.0), Vector::ZERO),
+            ];
+            state[0].attributes.charge = BigFloat::from(q1);
+            state[1].attributes.charge = BigFloat::from(q2);
+
+            let mut objects = objects(&mut state);
+            let mut executor = ChargeExecutor::new(SpaceExecutor::with_g(ZERO));
+            executor.reset_forces(&mut objects);
+            executor.execute_force(&mut objects, TimeStep::from_millis_f64(1.0));
+            objects[0].get_physical_attributes().force.x.to_f64()
+        };
+
+        // 第一个物体位于左侧，受力向左为斥力
+        assert!(force_on_first(1e-3, 2e-3) < 0.0);
+        assert!(force_on_first(-1e-3, -2e-3) < 0.0);
+        assert!(force_on_first(1e-3, -2e-3) > 0.0);
+    }
+
+    #[test]
+    fn earth_orbital_and_escape_velocity() {
+        let g = SpaceExecutor::default().g;
+        let mass: BigFloat = "5.972e24".parse().unwrap();
+        let radius: BigFloat = "6.371e6".parse().unwrap();
+
+        // sqrt(6.67259e-11 * 5.972e24 / 6.371e6)约为7908.7 m/s，逃逸速度为其sqrt(2)倍
+        let orbital = orbital_velocity(mass, radius, g).to_f64();
+        let escape = escape_velocity(mass, radius, g).to_f64();
+        assert!((orbital - 7908.67).abs() < 0.01, "orbital velocity {}", orbital);
+        assert!((escape - 11184.54).abs() < 0.01, "escape velocity {}", escape);
+    }
+
+    #[test]
+    fn anchored_body_never_moves() {
+        let mut state = circular_pair();
+        let anchored = state[0].uid;
+        let start = state[0].attributes.center;
+
+        let mut executor = SpaceExecutor::with_g(ONE);
+        executor.anchored.insert(anchored);
+        for _ in 0..50 {
+            executor.step(&mut objects(&mut state), TimeStep::from_millis_f64(10.0));
+            assert_eq!(state[0].attributes.center, start);
+            assert_eq!(state[0].attributes.velocity, Vector::ZERO);
+        }
+        assert_ne!(state[1].attributes.center, point(0.5, 0.0, 0.0));
+    }
+
+    #[test]
+    fn forward_then_backward_returns_near_start() {
+        let mut state = circular_pair();
+        let start = state[0].attributes.center;
+        let mut executor = Rk4Executor::new(SpaceExecutor::with_g(ONE));
+        let dt = Duration::from_millis(10);
+
+        executor.step_many(&mut objects(&mut state), TimeStep::forward(dt), 200);
+        assert!((state[0].attributes.center - start).model().to_f64() > 0.5);
+
+        executor.step_many(&mut objects(&mut state), TimeStep::backward(dt), 200);
+        let error = (state[0].attributes.center - start).model().to_f64();
+        assert!(error < 1e-6, "returned {} away from the start", error);
+    }
+
+    #[test]
+    fn bounding_box_of_single_point() {
+        let p = point(1.0, -2.0, 3.0);
+        assert_eq!(bounding_box([p]), Some((p, p)));
+        assert_eq!(bounding_box(Vec::<Point>::new()), None);
+    }
+
+    #[test]
+    fn bounding_box_of_spread_points() {
+        let points = [point(1.0, -2.0, 3.0), point(-4.0, 5.0, 0.0), point(2.0, 0.0, -6.0)];
+        let expected = Some((point(-4.0, -2.0, -6.0), point(2.0, 5.0, 3.0)));
+        assert_eq!(bounding_box(points), expected);
+
+        let mut state: Vec<BodySnapshot> = points.into_iter().map(|p| body(1.0, p, Vector::ZERO)).collect();
+        assert_eq!(objects(&mut state).bounding_box(), expected);
+        assert_eq!(objects(&mut []).bounding_box(), None);
+    }
+
+    #[test]
+    fn objects_summary_lists_both_uids() {
+        let mut state = circular_pair();
+        let uids: Vec<Uuid> = state.iter().map(|b| b.uid).collect();
+        let summary = objects(&mut state).to_string();
+
+        for uid in uids {
+            assert!(summary.contains(&uid.to_string()), "{} missing from {}", uid, summary);
+        }
+    }
+
+    /// 偏心率为0.5、近心点距离为0.5的轨道运行约1.75圈，返回第二次经过近心点时的方位角
+    fn periapsis_angle(executor: &mut dyn Executor, steps: usize, dt: TimeStep)-> f64 {
+        let mut state = vec![
+            body(1.0, Point::ZERO, Vector::ZERO),
+            body(1e-6, point(0.5, 0.0, 0.0), vector(0.0, 3f64.sqrt(), 0.0)),
+        ];
+
+        let mut closest = (f64::MAX, 0.0);
+        for i in 0..steps {
+            executor.step(&mut objects(&mut state), dt);
+            let [x, y, _] = state[1].attributes.center.to_f64_array();
+            if i > steps / 3 && x.hypot(y) < closest.0 {
+                closest = (x.hypot(y), y.atan2(x));
+            }
+        }
+
+        closest.1
+    }
+
+    #[test]
+    fn relativistic_periapsis_precesses_beyond_newtonian() {
+        // 周期为2π，取光速为20时每圈进动6πGM / (c^2 * a * (1 - e^2))约0.063弧度
+        let dt = TimeStep::from_millis_f64(2.0);
+        let steps = 5500;
+
+        let newtonian = periapsis_angle(&mut SpaceExecutor::with_g(ONE), steps, dt);
+        let mut relativistic = RelativisticExecutor::new(SpaceExecutor::with_g(ONE));
+        relativistic.c = BigFloat::from(20);
+        let relativistic = periapsis_angle(&mut relativistic, steps, dt);
+
+        // 牛顿引力下轨道闭合，近心点仍在初始方位角0附近
+        assert!(newtonian.abs() < 0.01, "newtonian periapsis drifted to {}", newtonian);
+
+        let precession = relativistic - newtonian;
+        let expected = 6.0 * std::f64::consts::PI / (400.0 * 0.75);
+        assert!((precession - expected).abs() < 0.015, "precession {} (expected {})", precession, expected);
+    }
+
+    #[test]
+    fn get_by_uid_finds_present_and_rejects_absent() {
+        let mut state = circular_pair();
+        let uid = state[1].uid;
+        let mut objects = objects(&mut state);
+
+        assert_eq!(objects.get_by_uid(uid).map(|o| o.get_uid()), Some(uid));
+        assert!(objects.get_by_uid(Uuid::new_v4()).is_none());
+        assert!(objects.get_by_uid_mut(uid).is_some());
+        assert!(objects.get_by_uid_mut(Uuid::new_v4()).is_none());
+    }
+
+    #[test]
+    fn circular_prediction_keeps_constant_radius() {
+        let mut state = circular_pair();
+        let (a, b) = (state[0].uid, state[1].uid);
+        let start = state.clone();
+        let paths = SpaceExecutor::with_g(ONE).predict(&objects(&mut state), 300, TimeStep::from_millis_f64(1.0));
+
+        assert_eq!(state, start);
+        assert_eq!(paths[&a].len(), 300);
+        for (pa, pb) in paths[&a].iter().zip(paths[&b].iter()) {
+            let d = pa.distance(pb).to_f64();
+            assert!((d - 1.0).abs() < 1e-3, "separation {}", d);
+        }
+    }
+
+    #[test]
+    fn parse_bigfloat_accepts_numbers_and_rejects_garbage() {
+        assert_eq!(parse_bigfloat(" 5.972e24 "), Ok("5.972e24".parse().unwrap()));
+        assert_eq!(parse_bigfloat("-3"), Ok(BigFloat::from(-3)));
+        assert!(matches!(parse_bigfloat("earth"), Err(PhysicsError::InvalidNumber(_))));
+        assert!(matches!(parse_bigfloat("inf"), Err(PhysicsError::InvalidNumber(_))));
+
+        // 负数本身可以解析，质量为负由check_mass拒绝
+        let mass = parse_bigfloat("-1e3").unwrap();
+        assert!(matches!(check_mass(mass), Err(PhysicsError::NonPositiveMass(_))));
+    }
+
+    #[test]
+    fn check_mass_rejects_zero_and_accepts_positive() {
+        assert_eq!(check_mass(ZERO), Err(PhysicsError::NonPositiveMass(ZERO)));
+        assert_eq!(check_mass(BigFloat::from(7.35e22)), Ok(BigFloat::from(7.35e22)));
+        assert!(PhysicalAttributes::new(Point::ZERO, Vector::ZERO, ZERO, ONE).is_err());
+    }
+
+    #[test]
+    fn impulse_changes_velocity_by_impulse_over_mass() {
+        let mut attributes = body(4.0, Point::ZERO, vector(1.0, 0.0, 0.0)).attributes;
+        attributes.apply_impulse(vector(2.0, -8.0, 4.0));
+        assert_eq!(attributes.velocity, vector(1.5, -2.0, 1.0));
+
+        let mut state = circular_pair();
+        let uid = state[0].uid;
+        let before = state[0].attributes.velocity;
+        assert!(objects(&mut state).apply_impulse(uid, vector(0.0, 0.0, 3.0)));
+        assert_eq!(state[0].attributes.velocity, before + vector(0.0, 0.0, 3.0));
+        assert!(!objects(&mut state).apply_impulse(Uuid::new_v4(), vector(1.0, 0.0, 0.0)));
+    }
+
+    #[test]
+    fn uniform_field_falls_at_surface_gravity() {
+        let mut state = vec![body(3.0, point(0.0, 100.0, 0.0), Vector::ZERO)];
+        let mut executor = UniformFieldExecutor::earth_surface();
+        executor.step_many(&mut objects(&mut state), TimeStep::from_millis_f64(100.0), 10);
+
+        // 恒定受力下位移计算是精确的：1s后v = -9.81 m/s，下落9.81 / 2 m
+        let [_, y, _] = state[0].attributes.center.to_f64_array();
+        let [_, vy, _] = state[0].attributes.velocity.to_f64_array();
+        assert!((vy + 9.81).abs() < 1e-12, "vy {}", vy);
+        assert!((y - (100.0 - 4.905)).abs() < 1e-12, "y {}", y);
+    }
+
+    #[test]
+    fn circular_orbit_is_bound_and_hyperbolic_is_not() {
+        let primary = body(1.0, point(0.0, 0.0, 0.0), Vector::ZERO).attributes;
+        let satellite = |speed: f64| body(1e-9, point(1.0, 0.0, 0.0), vector(0.0, speed, 0.0)).attributes;
+
+        // 圆轨道速度为1，逃逸速度为sqrt(2)
+        assert!(is_bound(&primary, &satellite(1.0), ONE));
+        assert!(is_bound(&primary, &satellite(1.4), ONE));
+        assert!(!is_bound(&primary, &satellite(1.5), ONE));
+        assert!(!is_bound(&primary, &satellite(2.0), ONE));
+
+        // 只取决于相对速度
+        let mut moving = primary;
+        moving.velocity = vector(5.0, 0.0, 0.0);
+        let mut comoving = satellite(1.0);
+        comoving.velocity += vector(5.0, 0.0, 0.0);
+        assert!(is_bound(&moving, &comoving, ONE));
+    }
+
+    #[test]
+    fn unit_system_round_trips_through_si() {
+        let g_si: BigFloat = "6.674e-11".parse().unwrap();
+        let units = UnitSystem::with_unit_g("3.844e8".parse().unwrap(), "5.965e24".parse().unwrap(), g_si);
+        assert!((units.gravitational_constant(g_si) - ONE).abs() < BigFloat::from(1e-30));
+
+        let mut moon = PhysicalAttributes::new(
+            point(3.844e8, 0.0, 0.0),
+            vector(0.0, 1023.0, 0.0),
+            "7.349e22".parse().unwrap(),
+            "1.737e6".parse().unwrap(),
+        ).unwrap();
+        moon.force = vector(-1.98e20, 0.0, 0.0);
+
+        let normalized = units.convert_from_si(&moon);
+        assert!((normalized.center.x - ONE).abs() < BigFloat::from(1e-30));
+        assert!((normalized.mass * "5.965e24".parse::<BigFloat>().unwrap() / moon.mass - ONE).abs() < BigFloat::from(1e-30));
+
+        let restored = units.convert_to_si(&normalized);
+        let close = |a: BigFloat, b: BigFloat| (a - b).abs() <= b.abs() * BigFloat::from(1e-30);
+        assert!(close(restored.center.x, moon.center.x));
+        assert!(close(restored.velocity.y, moon.velocity.y));
+        assert!(close(restored.force.x, moon.force.x));
+        assert!(close(restored.mass, moon.mass));
+        assert!(close(restored.radius, moon.radius));
+    }
+
+    #[test]
+    fn inspect_hook_sees_each_step_once() {
+        let positions = |objects: &Objects| objects.iter().map(|o| o.get_physical_attributes().center).collect::<Vec<_>>();
+        let recorded = Arc::new(Mutex::new(Vec::new()));
+        let sink = recorded.clone();
+        let mut inspect = InspectExecutor::new(SpaceExecutor::with_g(ONE), move |objects: &Objects| {
+            sink.lock().unwrap().push(positions(objects));
+        });
+        let mut plain = SpaceExecutor::with_g(ONE);
+
+        let dt = TimeStep::from_millis_f64(10.0);
+        let mut inspected = circular_pair();
+        let mut reference = circular_pair();
+        for (a, b) in inspected.iter_mut().zip(&reference) {
+            a.uid = b.uid;
+        }
+
+        inspect.step_many(&mut objects(&mut inspected), dt, 5);
+        let recorded = recorded.lock().unwrap();
+        assert_eq!(recorded.len(), 5);
+
+        // 回调函数得到的是执行该步之后的位置
+        for frame in recorded.iter() {
+            plain.step(&mut objects(&mut reference), dt);
+            assert_eq!(*frame, positions(&objects(&mut reference)));
+        }
+    }
+
+    #[test]
+    fn array_conversions_round_trip() {
+        let exact: [BigFloat; 3] = ["1.5e30".parse().unwrap(), "-2.25".parse().unwrap(), ZERO];
+        assert_eq!(<[BigFloat; 3]>::from(Point::from(exact)), exact);
+        assert_eq!(<[BigFloat; 3]>::from(Vector::from(exact)), exact);
+
+        let a = [0.5, -3.0e8, 1.0e-20];
+        assert_eq!(Point::from_f64_array(a).to_f64_array(), a);
+        assert_eq!(Vector::from_f64_array(a).to_f64_array(), a);
+
+        // 经过f64时绝对值很大的坐标丢失低位
+        let large: BigFloat = "1.0000000000000000000001e30".parse().unwrap();
+        let p = Point::from([large, ZERO, ZERO]);
+        let restored = Point::from_f64_array(p.to_f64_array());
+        assert_ne!(restored, p);
+        assert!(((restored.x - large) / large).abs() < BigFloat::from(f64::EPSILON));
+
+        // 超出f64范围时变为无穷大
+        let huge: BigFloat = "1e400".parse().unwrap();
+        assert_eq!(Vector::from([huge, ZERO, ZERO]).to_f64_array()[0], f64::INFINITY);
+    }
+
+    #[test]
+    fn clamp_velocities_keeps_direction() {
+        let mut state = vec![
+            body(1.0, point(0.0, 0.0, 0.0), vector(30.0, -40.0, 0.0)),
+            body(1.0, point(1.0, 0.0, 0.0), vector(1.0, 0.0, 0.0)),
+        ];
+        let mut executor = SpaceExecutor::with_g(ONE);
+
+        // 没有上限时不做任何事
+        executor.clamp_velocities(&mut objects(&mut state));
+        assert_eq!(state[0].attributes.velocity, vector(30.0, -40.0, 0.0));
+
+        executor.max_velocity = Some(BigFloat::from(5));
+        executor.clamp_velocities(&mut objects(&mut state));
+
+        let [x, y, z] = state[0].attributes.velocity.to_f64_array();
+        assert!((x - 3.0).abs() < 1e-12 && (y + 4.0).abs() < 1e-12 && z == 0.0, "clamped to {:?}", [x, y, z]);
+        assert_eq!(state[1].attributes.velocity, vector(1.0, 0.0, 0.0));
+    }
+
+    #[test]
+    fn tidal_field_stretches_along_the_line() {
+        let primary = body(1.0, point(0.0, 0.0, 0.0), Vector::ZERO).attributes;
+        let center = point(10.0, 0.0, 0.0);
+        let field = |at: Point| tidal_field(&primary, at, center, ONE).to_f64_array();
+
+        // 朝向与背向主星的两点均指向卫星外侧
+        let near = field(point(9.0, 0.0, 0.0));
+        let far = field(point(11.0, 0.0, 0.0));
+        assert!(near[0] < 0.0 && near[1] == 0.0, "near side {:?}", near);
+        assert!(far[0] > 0.0 && far[1] == 0.0, "far side {:?}", far);
+
+        // 与连线垂直的方向上指向卫星中心
+        let side = field(point(10.0, 1.0, 0.0));
+        assert!(side[1] < 0.0, "side {:?}", side);
+
+        // 距离远大于卫星半径时大小约为`2 * G * M * d / r^3`
+        assert!((far[0] - 2.0 / 1000.0).abs() < 4e-4, "far side {:?}", far);
+    }
 }
@@ -0,0 +1,51 @@
+struct BasicUniform {
+    aspect_ratio: f32,
+    scale: f32,
+    camera_coord: vec3<f32>,
+}
+
+// 数组长度与`render.rs`中的`MAX_POTENTIAL_BODIES`一致
+struct PotentialData {
+    // xy为天体在标准化设备坐标中的位置，z为按总质量归一化的质量
+    bodies: array<vec4<f32>, 64>,
+    count: u32,
+    softening: f32,
+}
+
+@group(0) @binding(0) var<uniform> d: BasicUniform;
+
+@group(1) @binding(0) var<uniform> field: PotentialData;
+
+struct VertexOutput {
+    @builtin(position) position: vec4<f32>,
+    @location(0) ndc: vec2<f32>,
+}
+
+// 顶点为覆盖整个窗口的正方形的四个角
+@vertex
+fn vs_main(
+    @location(0) corner: vec2<f32>
+)-> VertexOutput {
+    var out: VertexOutput;
+    out.position = vec4<f32>(corner, 0.0, 1.0);
+    out.ndc = corner;
+
+    return out;
+}
+
+@fragment
+fn fs_main(in: VertexOutput)-> @location(0) vec4<f32> {
+    // 累加各天体的m / r，与势能-G * m / r只差一个负的常数因子
+    var depth = 0.0;
+    for (var i = 0u; i < field.count; i = i + 1u) {
+        let body = field.bodies[i];
+        let offset = vec2<f32>(in.ndc.x - body.x, (in.ndc.y - body.y) / d.aspect_ratio);
+        depth = depth + body.z / sqrt(dot(offset, offset) + field.softening * field.softening);
+    }
+
+    // 势阱深度的数量级变化很大，取对数后映射到[0, 1]，越深越暗
+    let t = clamp(log2(1.0 + depth) / 5.0, 0.0, 1.0);
+    let color = mix(vec3<f32>(0.05, 0.07, 0.12), vec3<f32>(0.0, 0.0, 0.005), t);
+
+    return vec4<f32>(color, 0.85);
+}
@@ -4,18 +4,55 @@ use bytemuck::{ Pod, Zeroable, cast_slice };
 use raw_window_handle::{ HasRawWindowHandle, HasRawDisplayHandle };
 use num_bigfloat::BigFloat;
 use std::mem::size_of;
+use std::collections::VecDeque;
+use winit::dpi::PhysicalPosition;
+use crate::physics::{ Point, Vector };
+use crate::hud::{ HudText, Starfield, DebugInfo };
+use crate::capture::{ Screenshot, BYTES_PER_PIXEL, padded_bytes_per_row, unpad_rows };
+use anyhow::{ Result, anyhow };
+use pollster::FutureExt;
+use std::path::Path;
+#[cfg(all(debug_assertions, feature = "hot-reload"))]
+use std::sync::{ Arc, atomic::{ AtomicBool, Ordering } };
+
+
+
+/// 深度缓冲的格式
+pub const DEPTH_FORMAT: TextureFormat = TextureFormat::Depth32Float;
+
+/// 生成星空背景使用的种子
+const STARFIELD_SEED: u64 = 0x5EED;
+
+/// 星空背景中星星的数量
+const STARFIELD_COUNT: usize = 400;
+
+/// `PotentialField`最多计入的天体数量，与`potential.wgsl`中数组的长度一致
+///
+/// 超出时只计入质量最大的天体
+pub const MAX_POTENTIAL_BODIES: usize = 64;
+
+/// 着色器源码所在的目录，重新加载着色器时从该目录读取
+const SHADER_DIR: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/src");
 
 
 
 macro_rules! default_render_pipeline_descriptor {
-    ($format:expr, $shader:expr, $layout:expr) => {
+    ($format:expr, $samples:expr, $shader:expr, $layout:expr) => {
+        default_render_pipeline_descriptor!($format, $samples, $shader, $layout, PrimitiveTopology::TriangleList)
+    };
+
+    ($format:expr, $samples:expr, $shader:expr, $layout:expr, $topology:expr) => {
+        default_render_pipeline_descriptor!($format, $samples, $shader, $layout, $topology, &[Vertex::LAYOUT])
+    };
+
+    ($format:expr, $samples:expr, $shader:expr, $layout:expr, $topology:expr, $buffers:expr) => {
         RenderPipelineDescriptor {
             label: Some("Render Pipeline"),
             layout: $layout,
             vertex: VertexState {
                 module: $shader,
                 entry_point: "vs_main",
-                buffers: &[Vertex::LAYOUT],
+                buffers: $buffers,
             },
             fragment: Some(FragmentState {
                 module: $shader,
@@ -38,12 +75,18 @@ macro_rules! default_render_pipeline_descriptor {
                 })],
             }),
             primitive: PrimitiveState {
-                topology: PrimitiveTopology::TriangleList,
+                topology: $topology,
                 ..Default::default()
             },
-            depth_stencil: None,
+            depth_stencil: Some(DepthStencilState {
+                format: DEPTH_FORMAT,
+                depth_write_enabled: true,
+                depth_compare: CompareFunction::LessEqual,
+                stencil: StencilState::default(),
+                bias: DepthBiasState::default(),
+            }),
             multisample: MultisampleState {
-                count: 1,
+                count: $samples,
                 mask: !0,
                 alpha_to_coverage_enabled: false,
             },
@@ -59,15 +102,15 @@ macro_rules! load_render_pass_from_render_context {
         let mut render_pass = $ctx.encoder.as_mut().unwrap().begin_render_pass(
             &RenderPassDescriptor {
                 label: Some("Render pass"),
-                color_attachments: &[Some(RenderPassColorAttachment {
-                    view: $ctx.view,
-                    resolve_target: None,
-                    ops: Operations {
+                color_attachments: &[Some($ctx.renderer.color_attachment($ctx.view, LoadOp::Load))],
+                depth_stencil_attachment: Some(RenderPassDepthStencilAttachment {
+                    view: &$ctx.renderer.depth_view,
+                    depth_ops: Some(Operations {
                         load: LoadOp::Load,
                         store: true,
-                    },
-                })],
-                depth_stencil_attachment: None,
+                    }),
+                    stencil_ops: None,
+                }),
             }
         );
 
@@ -81,6 +124,65 @@ macro_rules! load_render_pass_from_render_context {
 
 pub trait Drawable {
     fn draw(&self, ctx: RenderContext<'_>);
+
+    /// 若该物体可以表示为一个圆形，返回该圆形
+    ///
+    /// 通过`Renderer::draw_items`绘制时，返回`Some`的物体会被合并到同一个`Instances`中批量绘制，
+    /// 而不再单独调用`draw`
+    fn as_circle(&self, _renderer: &Renderer)-> Option<Circle> {
+        None
+    }
+}
+
+/// `Renderable`的物体绘制时的形状
+#[derive(Clone, Copy, PartialEq, Debug, Default)]
+pub enum Shape {
+    /// 半径为`radius`的圆
+    #[default]
+    Circle,
+
+    /// 边长为`2 * radius`的正方形，不绘制轮廓
+    Square,
+
+    /// 内外半径分别为`inner`与`outer`的圆环，以m为单位，不使用`radius`与轮廓
+    Ring {
+        inner: BigFloat,
+        outer: BigFloat,
+    },
+}
+
+/// `Renderable`按`shape`转换得到的图元，坐标已按相机缩放
+#[derive(Debug, Clone, Copy)]
+pub enum Primitive {
+    /// 圆与圆环，圆环的填充色完全透明
+    Circle(Circle),
+
+    /// 正方形
+    Rectangle(Rectangle),
+}
+
+/// 可以绘制为实心圆等简单形状的物体
+///
+/// 实现该trait的物体自动实现`Drawable`，以`center`为中心按`shape`绘制
+pub trait Renderable {
+    /// 圆心在世界中的坐标
+    fn center(&self)-> Point;
+
+    /// 显示半径，以m为单位，与坐标使用相同的缩放
+    fn radius(&self)-> BigFloat;
+
+    /// 填充颜色(RGBA)
+    fn color(&self)-> [f32; 4];
+
+    /// 轮廓的颜色与宽度，宽度以m为单位，默认没有轮廓
+    fn outline(&self)-> Option<([f32; 4], BigFloat)> {
+        None
+    }
+
+    /// 绘制时的形状，默认为圆
+    fn shape(&self)-> Shape {
+        Shape::Circle
+    }
 }
 
 
@@ -101,27 +203,237 @@ pub struct BasicUniform {
     pub _padding2: [f32; 1],
 }
 
+/// 相机状态，即世界坐标到窗口坐标的变换所需的数据
+///
+/// 由`Renderer::camera`复制得到，不依赖GPU设备，坐标变换均在此计算
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Camera {
+    pub scale_base: BigFloat,
+    pub scale: BigFloat,
+
+    /// 窗口大小，以像素为单位
+    pub size: (u32, u32),
+
+    /// 与`BasicUniform::camera_coord`相同
+    pub coord: [f32; 3],
+}
+
 pub struct Renderer {
-    pub debug: bool,
-    pub surface: Surface,
+    /// 左上角的调试信息
+    pub debug: DebugInfo,
+
+    /// 绘制到的窗口，以`new_headless`创建时为`None`
+    pub surface: Option<Surface>,
     pub device: Device,
     pub queue: Queue,
     pub config: SurfaceConfiguration,
     pub pipeline: RenderPipeline,
+    pub line_pipeline: RenderPipeline,
+    pub line_strip_pipeline: RenderPipeline,
     pub shader: ShaderModule,
     pub circle_shader: ShaderModule,
+    pub circle_pipeline: RenderPipeline,
+    pub circle_bind_group_layout: BindGroupLayout,
+    pub circle_instanced_shader: ShaderModule,
+    pub circle_instanced_pipeline: RenderPipeline,
+    pub hud_shader: ShaderModule,
+    pub hud_pipeline: RenderPipeline,
+    pub potential_shader: ShaderModule,
+    pub potential_pipeline: RenderPipeline,
+    pub potential_bind_group_layout: BindGroupLayout,
+    pub quad_vertex_buffer: Buffer,
+    pub quad_index_buffer: Buffer,
     pub basic_bind_group: BindGroup,
     pub basic_bind_group_layout: BindGroupLayout,
     pub basic_bind_group_buffer: Buffer,
     pub basic_bind_group_data: BasicUniform,
+    pub depth_texture: Texture,
+    pub depth_view: TextureView,
+
+    /// 多重采样抗锯齿(MSAA)的采样数，为1时不使用MSAA
+    pub sample_count: u32,
+
+    /// 多重采样的颜色缓冲，绘制完成后解析到surface上，`sample_count`为1时为`None`
+    pub msaa_texture: Option<(Texture, TextureView)>,
     pub size: (u32, u32),
-    pub timewrap: f64,
     pub scale: BigFloat,
     pub scale_base: BigFloat,
+
+    /// 每一帧开始时清空画面使用的背景色
+    pub clear_color: Color,
+
+    /// 为`Some`时将圆形绘制为由该数量的三角形组成的网格，而不是批量绘制的正方形
+    pub circle_segments: Option<u16>,
+
+    /// 为`true`时在所有物体之前绘制星空背景
+    pub starfield: bool,
+
+    /// 星空背景，创建渲染器时以固定的种子生成一次
+    pub stars: Starfield,
+}
+
+/// 监视着色器源码目录，文件被修改时标记需要重新加载着色器
+///
+/// 仅在调试构建且启用`hot-reload`特性时可用
+#[cfg(all(debug_assertions, feature = "hot-reload"))]
+pub struct ShaderWatcher {
+    _watcher: notify::RecommendedWatcher,
+    changed: Arc<AtomicBool>,
+}
+
+/// `Renderer::create_pipelines`创建的渲染管线
+struct Pipelines {
+    pipeline: RenderPipeline,
+    line_pipeline: RenderPipeline,
+    line_strip_pipeline: RenderPipeline,
+    circle_pipeline: RenderPipeline,
+    circle_instanced_pipeline: RenderPipeline,
+    hud_pipeline: RenderPipeline,
+    potential_pipeline: RenderPipeline,
+}
+
+#[cfg(all(debug_assertions, feature = "hot-reload"))]
+impl ShaderWatcher {
+    pub fn new()-> notify::Result<Self> {
+        use notify::{ Watcher, RecursiveMode, EventKind };
+
+        let changed = Arc::new(AtomicBool::new(false));
+        let flag = Arc::clone(&changed);
+        let mut watcher = notify::recommended_watcher(move |event: notify::Result<notify::Event>| {
+            let Ok(event) = event else {
+                return;
+            };
+
+            let is_shader = event.paths.iter().any(|p| p.extension().is_some_and(|e| e == "wgsl"));
+            if is_shader && matches!(event.kind, EventKind::Create(_) | EventKind::Modify(_)) {
+                flag.store(true, Ordering::Relaxed);
+            }
+        })?;
+        watcher.watch(Path::new(SHADER_DIR), RecursiveMode::NonRecursive)?;
+
+        Ok(Self {
+            _watcher: watcher,
+            changed,
+        })
+    }
+
+    /// 返回自上次调用以来着色器是否被修改过
+    pub fn take_changed(&self)-> bool {
+        self.changed.swap(false, Ordering::Relaxed)
+    }
+}
+
+impl Camera {
+    /// 窗口的宽高比，与`BasicUniform::aspect_ratio`相同
+    pub fn aspect_ratio(&self)-> f32 {
+        self.size.0 as f32 / self.size.1 as f32
+    }
+
+    pub fn scale_from_array3(&self, s: [BigFloat; 3])-> [f32; 3] {
+        let scale = self.scale_base / self.scale;
+        [(s[0] / scale).to_f32(), (s[1] / scale).to_f32(), (s[2] / scale).to_f32()]
+    }
+
+    pub fn scale_from_point(&self, p: Point)-> [f32; 3] {
+        self.scale_from_array3(p.into())
+    }
+
+    /// 按与坐标相同的比例缩放一段长度
+    pub fn scale_length(&self, length: BigFloat)-> f32 {
+        (length / (self.scale_base / self.scale)).to_f32()
+    }
+
+    /// 计算世界坐标中的点`p`在标准化设备坐标中的位置，与着色器中的相机变换相同
+    pub fn ndc_from_point(&self, p: Point)-> [f32; 2] {
+        let scale = self.scale.to_f32();
+        let [x, y, _] = self.scale_from_point(p);
+        let c = self.coord.map(|c| c * scale);
+
+        [(x + c[0]) * scale, (y - c[1]) * self.aspect_ratio() * scale]
+    }
+
+    /// 计算世界坐标中的点`p`在窗口中的像素坐标，原点为窗口左上角，y轴向下
+    pub fn screen_from_point(&self, p: Point)-> (f32, f32) {
+        let [x, y] = self.ndc_from_point(p);
+        ((x + 1.0) / 2.0 * self.size.0 as f32, (1.0 - y) / 2.0 * self.size.1 as f32)
+    }
+
+    /// 计算中心位于`center`、半径为`radius`的天体的标签锚点在窗口中的像素坐标
+    ///
+    /// 锚点位于天体右侧，与天体边缘相隔`LABEL_MARGIN`个屏幕像素，与天体中心等高。
+    /// 天体随缩放变大时标签随之外移，不与天体重叠
+    pub fn label_anchor(&self, center: Point, radius: BigFloat)-> (f32, f32) {
+        const LABEL_MARGIN: f32 = 4.0;
+
+        let (x, y) = self.screen_from_point(center);
+        // 着色器中坐标会再乘一次缩放比例，标准化设备坐标中的[-1, 1]对应窗口的宽度
+        let r = self.scale_length(radius) * self.scale.to_f32() * self.size.0 as f32 / 2.0;
+
+        (x + r + LABEL_MARGIN, y)
+    }
+
+    /// 将窗口中的像素坐标转换为世界坐标，是`scale_from_point`与着色器中相机变换的逆变换
+    ///
+    /// 返回的点位于z = 0平面上
+    pub fn point_from_screen(&self, screen: PhysicalPosition<f64>)-> Point {
+        let s = self.scale.to_f32() as f64;
+        let aspect_ratio = self.aspect_ratio() as f64;
+        let cam = self.coord;
+
+        // 像素坐标 -> 标准化设备坐标，y轴方向相反
+        let ndc_x = screen.x / self.size.0 as f64 * 2.0 - 1.0;
+        let ndc_y = 1.0 - screen.y / self.size.1 as f64 * 2.0;
+
+        // 撤销着色器中的 (pos + camera * scale) * scale 与宽高比修正
+        let x = ndc_x / s - cam[0] as f64 * s;
+        let y = ndc_y / (aspect_ratio * s) + cam[1] as f64 * s;
+
+        let scale = self.scale_base / self.scale;
+        Point {
+            x: BigFloat::from(x) * scale,
+            y: BigFloat::from(y) * scale,
+            z: BigFloat::from(0),
+        }
+    }
+
+    /// 移动相机并调整缩放比例，使`min`与`max`所围成的区域位于视野中央并完整显示
+    ///
+    /// 着色器中世界坐标`w`最终映射为`scale^2 * (w / scale_base ± camera)`，
+    /// 因此相机坐标与缩放比例无关，缩放比例取使区域的半宽与半高(乘以宽高比后)均不超过1的值，
+    /// 并留出一定边距。区域退化为一点时只移动相机
+    pub fn fit_bounds(&mut self, min: Point, max: Point) {
+        let two = BigFloat::from(2);
+        let half_width = (max.x - min.x) / two;
+        let half_height = (max.y - min.y) / two * BigFloat::from(self.aspect_ratio());
+
+        self.center_on(Point {
+            x: (min.x + max.x) / two,
+            y: (min.y + max.y) / two,
+            z: BigFloat::from(0),
+        });
+
+        let extent = half_width.max(&half_height);
+        if extent > BigFloat::from(0) {
+            // 留出10%的边距
+            self.scale = (self.scale_base / extent).sqrt() * BigFloat::from(0.9);
+        }
+    }
+
+    /// 移动相机使世界坐标中的点`p`位于窗口中央，不改变缩放比例
+    pub fn center_on(&mut self, p: Point) {
+        self.coord = [
+            (-p.x / self.scale_base).to_f32(),
+            (p.y / self.scale_base).to_f32(),
+            0.0,
+        ];
+    }
 }
 
 impl Renderer {
-    pub async fn new<W>(win: &W, size: (u32, u32))-> Renderer
+    /// 创建渲染器
+    ///
+    /// `sample_count`为MSAA的采样数，可取1、2、4或8，适配器不支持时使用不超过该值的最大可用采样数
+    pub async fn new<W>(win: &W, size: (u32, u32), sample_count: u32)-> Renderer
         where W: HasRawWindowHandle + HasRawDisplayHandle
     {
         // 此处的Instance是一个GPU实例
@@ -140,15 +452,6 @@ impl Renderer {
             ..Default::default()
         }).await.unwrap();
 
-        let (device, queue) = adapter.request_device(&DeviceDescriptor {
-            label: None,
-            limits: Limits::default(),
-            features: Features::empty(),
-        }, None).await.unwrap();
-
-        let shader = device.create_shader_module(include_wgsl!("generic.wgsl"));
-        let circle_shader = device.create_shader_module(include_wgsl!("circle.wgsl"));
-
         let caps = surface.get_capabilities(&adapter);
         let config = SurfaceConfiguration {
             usage: TextureUsages::RENDER_ATTACHMENT,
@@ -160,6 +463,62 @@ impl Renderer {
             view_formats: vec![],
         };
 
+        Self::from_adapter(adapter, Some(surface), config, sample_count).await
+    }
+
+    /// 创建不绑定窗口的渲染器，只能通过`capture_frame`绘制到离屏纹理上
+    ///
+    /// 用于无窗口模式下录制画面，没有可用的适配器时返回错误
+    pub async fn new_headless(size: (u32, u32), sample_count: u32)-> Result<Renderer> {
+        let instance = Instance::new( InstanceDescriptor {
+            backends: Backends::all(),
+            ..Default::default()
+        });
+
+        Self::new_headless_on(&instance, size, sample_count).await
+    }
+
+    /// 在给定的`instance`上创建不绑定窗口的渲染器，参见`new_headless`
+    ///
+    /// 调用者保留`instance`时可以通过`Instance::generate_report`统计创建的GPU资源
+    pub async fn new_headless_on(instance: &Instance, size: (u32, u32), sample_count: u32)-> Result<Renderer> {
+        let adapter = instance.request_adapter(&RequestAdapterOptions::default())
+            .await
+            .ok_or_else(|| anyhow!("No GPU adapter available"))?;
+
+        let config = SurfaceConfiguration {
+            usage: TextureUsages::RENDER_ATTACHMENT,
+            format: TextureFormat::Rgba8UnormSrgb,
+            width: size.0,
+            height: size.1,
+            present_mode: PresentMode::Fifo,
+            alpha_mode: CompositeAlphaMode::Auto,
+            view_formats: vec![],
+        };
+
+        Ok(Self::from_adapter(adapter, None, config, sample_count).await)
+    }
+
+    /// 在给定的适配器上创建设备与所有渲染资源
+    ///
+    /// `surface`不为`None`时按`config`配置该surface
+    async fn from_adapter(adapter: Adapter, surface: Option<Surface>, config: SurfaceConfiguration, sample_count: u32)-> Renderer {
+        let size = (config.width, config.height);
+
+        let (device, queue) = adapter.request_device(&DeviceDescriptor {
+            label: None,
+            limits: Limits::default(),
+            features: Features::empty(),
+        }, None).await.unwrap();
+
+        let shader = device.create_shader_module(include_wgsl!("generic.wgsl"));
+        let circle_shader = device.create_shader_module(include_wgsl!("circle.wgsl"));
+        let circle_instanced_shader = device.create_shader_module(include_wgsl!("circle_instanced.wgsl"));
+        let hud_shader = device.create_shader_module(include_wgsl!("hud.wgsl"));
+        let potential_shader = device.create_shader_module(include_wgsl!("potential.wgsl"));
+
+        let sample_count = Self::supported_sample_count(&adapter, config.format, sample_count);
+
         let basic_bind_group_layout = device.create_bind_group_layout(
             &BindGroupLayoutDescriptor {
                 label: Some("Basic bind group layout"),
@@ -209,19 +568,77 @@ impl Renderer {
             }
         );
 
-        let pipeline_layout = device.create_pipeline_layout(
-            &PipelineLayoutDescriptor {
-                label: Some("Pipeline layout"),
-                bind_group_layouts: &[
-                    &basic_bind_group_layout,
+        let circle_bind_group_layout = device.create_bind_group_layout(
+            &BindGroupLayoutDescriptor {
+                label: Some("Circle bind group layout"),
+                entries: &[
+                    BindGroupLayoutEntry {
+                        binding: 0,
+                        visibility: ShaderStages::FRAGMENT,
+                        count: None,
+                        ty: BindingType::Buffer {
+                            min_binding_size: None,
+                            has_dynamic_offset: false,
+                            ty: BufferBindingType::Uniform,
+                        },
+                    },
+                ],
+            }
+        );
+
+        let potential_bind_group_layout = device.create_bind_group_layout(
+            &BindGroupLayoutDescriptor {
+                label: Some("Potential bind group layout"),
+                entries: &[
+                    BindGroupLayoutEntry {
+                        binding: 0,
+                        visibility: ShaderStages::FRAGMENT,
+                        count: None,
+                        ty: BindingType::Buffer {
+                            min_binding_size: None,
+                            has_dynamic_offset: false,
+                            ty: BufferBindingType::Uniform,
+                        },
+                    },
                 ],
-                push_constant_ranges: &[],
             }
-        ); 
+        );
+
+        let Pipelines {
+            pipeline,
+            line_pipeline,
+            line_strip_pipeline,
+            circle_pipeline,
+            circle_instanced_pipeline,
+            hud_pipeline,
+            potential_pipeline,
+        } = Self::create_pipelines(
+            &device,
+            config.format,
+            sample_count,
+            &basic_bind_group_layout,
+            [&circle_bind_group_layout, &potential_bind_group_layout],
+            [&shader, &circle_shader, &circle_instanced_shader, &hud_shader, &potential_shader],
+        );
+
+        let quad_vertex_buffer = device.create_buffer_init(&BufferInitDescriptor {
+            label: Some("Quad vertex buffer"),
+            contents: cast_slice(&Circle::QUAD_CORNERS),
+            usage: BufferUsages::VERTEX,
+        });
 
-        let pipeline = device.create_render_pipeline(&default_render_pipeline_descriptor!(config.format, &shader, Some(&pipeline_layout)));
+        let quad_index_buffer = device.create_buffer_init(&BufferInitDescriptor {
+            label: Some("Quad index buffer"),
+            contents: cast_slice(&Rectangle::INDICES),
+            usage: BufferUsages::INDEX,
+        });
+
+        if let Some(surface) = &surface {
+            surface.configure(&device, &config);
+        }
 
-        surface.configure(&device, &config);
+        let (depth_texture, depth_view) = Self::create_depth_texture(&device, size, sample_count);
+        let msaa_texture = Self::create_msaa_texture(&device, &config, sample_count);
 
         Renderer {
             surface,
@@ -229,12 +646,29 @@ impl Renderer {
             queue,
             config,
             pipeline,
+            line_pipeline,
+            line_strip_pipeline,
             size,
             shader,
             circle_shader,
+            circle_pipeline,
+            circle_bind_group_layout,
+            circle_instanced_shader,
+            circle_instanced_pipeline,
+            hud_shader,
+            hud_pipeline,
+            potential_shader,
+            potential_pipeline,
+            potential_bind_group_layout,
+            quad_vertex_buffer,
+            quad_index_buffer,
             basic_bind_group,
             basic_bind_group_layout,
             basic_bind_group_buffer,
+            depth_texture,
+            depth_view,
+            sample_count,
+            msaa_texture,
             basic_bind_group_data: BasicUniform {
                 aspect_ratio: size.0 as f32 / size.1 as f32,
                 scale: 1.0,
@@ -244,91 +678,621 @@ impl Renderer {
             },
             scale: "1.0".parse().unwrap(),
             scale_base: "4.0e8".parse().unwrap(),
-            timewrap: 1.0,
-            debug: false,
+            debug: DebugInfo::default(),
+            clear_color: Color {
+                r: 0.05,
+                g: 0.05,
+                b: 0.05,
+                a: 1.00,
+            },
+            circle_segments: None,
+            starfield: false,
+            stars: Starfield::generate(STARFIELD_SEED, STARFIELD_COUNT),
         }
     }
 
-    /// 用`basic_bind_group_data`更新整个`BasicUniform`
-    fn update_buffer(&self) {
-        if self.debug {
-            self.print_msg();
-        }
+    /// 使用给定的着色器创建所有渲染管线
+    ///
+    /// `layouts`依次为圆形与势场的绑定组布局，
+    /// `shaders`依次为`generic.wgsl`、`circle.wgsl`、`circle_instanced.wgsl`、`hud.wgsl`与`potential.wgsl`的着色器模块
+    fn create_pipelines(
+        device: &Device,
+        format: TextureFormat,
+        sample_count: u32,
+        basic_bind_group_layout: &BindGroupLayout,
+        layouts: [&BindGroupLayout; 2],
+        shaders: [&ShaderModule; 5],
+    )-> Pipelines {
+        let [circle_bind_group_layout, potential_bind_group_layout] = layouts;
+        let [shader, circle_shader, circle_instanced_shader, hud_shader, potential_shader] = shaders;
 
-        self.queue.write_buffer(
-            &self.basic_bind_group_buffer,
-            0,
-            cast_slice(&[self.basic_bind_group_data]),
+        let pipeline_layout = device.create_pipeline_layout(
+            &PipelineLayoutDescriptor {
+                label: Some("Pipeline layout"),
+                bind_group_layouts: &[
+                    basic_bind_group_layout,
+                ],
+                push_constant_ranges: &[],
+            }
         );
-    }
 
-    /// 修改surface的大小，并同步`BasicUniform`
-    pub fn resize(&mut self, new_size: (u32, u32)) {
-        if new_size.0 > 0 && new_size.1 > 0 {
-            self.size = new_size;
-            self.config.width = new_size.0;
-            self.config.height = new_size.1;
-            self.surface.configure(&self.device, &self.config);
-
-            self.basic_bind_group_data.aspect_ratio = new_size.0 as f32 / new_size.1 as f32;
-            self.update_buffer();
-        }
-    }
-
-    pub fn scale_from_array3(&self, s: [BigFloat; 3])-> [f32; 3] {
-        let scale = self.scale_base / self.scale;
-        [(s[0] / scale).to_f32(), (s[1] / scale).to_f32(), (s[2] / scale).to_f32()]
-    }
-
-    pub fn scale_from_point(&self, p: crate::physics::Point)-> [f32; 3] {
-        self.scale_from_array3([p.x, p.y, p.z])
-    }
-
-    /// 缩放视图
-    pub fn scale(&mut self, scale: BigFloat) {
-        self.scale = scale;
-        self.basic_bind_group_data.scale = scale.to_f32();
-        self.update_buffer();
-    }
-
-    /// 移动相机到指定坐标
-    pub fn move_camera(&mut self, new_coord: [f32; 3]) {
-        self.basic_bind_group_data.camera_coord = new_coord;
-        self.update_buffer();
-    }
-
-    pub fn print_msg(&self) {
-        let data = &self.basic_bind_group_data;
-        let cam = &data.camera_coord;
-        print!("\x1bc");
-        println!("Camera: ({},{},{})", cam[0], cam[1], cam[2]);
-        println!("Scale:  {}", data.scale);
-        println!("Timewrap ratio: {}", self.timewrap);
-    }
-}
-
-
-
-#[repr(C)]
-#[derive(Debug, Pod, Zeroable, Clone, Copy)]
-pub struct Vertex {
-    pub position: [f32; 3],
-    pub color: [f32; 4],
-}
+        let pipeline = device.create_render_pipeline(&default_render_pipeline_descriptor!(format, sample_count, shader, Some(&pipeline_layout)));
+        let line_pipeline = device.create_render_pipeline(&default_render_pipeline_descriptor!(
+            format,
+            sample_count,
+            shader,
+            Some(&pipeline_layout),
+            PrimitiveTopology::LineList
+        ));
+        let line_strip_pipeline = device.create_render_pipeline(&default_render_pipeline_descriptor!(
+            format,
+            sample_count,
+            shader,
+            Some(&pipeline_layout),
+            PrimitiveTopology::LineStrip
+        ));
 
-impl Vertex {
-    pub const LAYOUT: VertexBufferLayout<'_> = VertexBufferLayout {
-        array_stride: size_of::<Self>() as u64,
-        step_mode: VertexStepMode::Vertex,
-        attributes: &[
-            VertexAttribute {
-                offset: 0,
-                shader_location: 0,
-                format: VertexFormat::Float32x3,
-            },
+        let circle_pipeline_layout = device.create_pipeline_layout(
+            &PipelineLayoutDescriptor {
+                label: Some("Circle pipeline layout"),
+                bind_group_layouts: &[
+                    basic_bind_group_layout,
+                    circle_bind_group_layout,
+                ],
+                push_constant_ranges: &[],
+            }
+        );
 
-            VertexAttribute {
-                offset: size_of::<[f32; 3]>() as u64,
+        let circle_pipeline = device.create_render_pipeline(
+            &RenderPipelineDescriptor {
+                label: Some("Circle render pipeline"),
+                layout: Some(&circle_pipeline_layout),
+                vertex: VertexState {
+                    module: shader,
+                    entry_point: "vs_main",
+                    buffers: &[Vertex::LAYOUT],
+                },
+                fragment: Some(FragmentState {
+                    module: circle_shader,
+                    entry_point: "circle_fs",
+                    targets: &[Some(ColorTargetState {
+                        format,
+                        blend: Some(BlendState {
+                            color: BlendComponent {
+                                src_factor: BlendFactor::SrcAlpha,
+                                dst_factor: BlendFactor::OneMinusSrcAlpha,
+                                operation: BlendOperation::Add,
+                            },
+                            alpha: BlendComponent {
+                                src_factor: BlendFactor::One,
+                                dst_factor: BlendFactor::OneMinusSrcAlpha,
+                                operation: BlendOperation::Add,
+                            },
+                        }),
+                        write_mask: ColorWrites::ALL,
+                    })],
+                }),
+                primitive: PrimitiveState {
+                    topology: PrimitiveTopology::TriangleList,
+                    strip_index_format: None,
+                    front_face: FrontFace::Ccw,
+                    cull_mode: None,
+                    unclipped_depth: false,
+                    polygon_mode: PolygonMode::Fill,
+                    conservative: false,
+                },
+                multisample: MultisampleState {
+                    count: sample_count,
+                    mask: !0,
+                    alpha_to_coverage_enabled: false,
+                },
+                depth_stencil: Some(DepthStencilState {
+                    format: DEPTH_FORMAT,
+                    depth_write_enabled: true,
+                    depth_compare: CompareFunction::LessEqual,
+                    stencil: StencilState::default(),
+                    bias: DepthBiasState::default(),
+                }),
+                multiview: None,
+            }
+        );
+
+        let circle_instanced_pipeline = device.create_render_pipeline(&default_render_pipeline_descriptor!(
+            format,
+            sample_count,
+            circle_instanced_shader,
+            Some(&pipeline_layout),
+            PrimitiveTopology::TriangleList,
+            &[Circle::QUAD_LAYOUT, Circle::INSTANCE_LAYOUT]
+        ));
+
+        // HUD总是绘制在最上层，不参与深度测试
+        let hud_pipeline = device.create_render_pipeline(&RenderPipelineDescriptor {
+            label: Some("HUD render pipeline"),
+            depth_stencil: Some(DepthStencilState {
+                format: DEPTH_FORMAT,
+                depth_write_enabled: false,
+                depth_compare: CompareFunction::Always,
+                stencil: StencilState::default(),
+                bias: DepthBiasState::default(),
+            }),
+            ..default_render_pipeline_descriptor!(format, sample_count, hud_shader, Some(&pipeline_layout))
+        });
+
+        let potential_pipeline_layout = device.create_pipeline_layout(
+            &PipelineLayoutDescriptor {
+                label: Some("Potential pipeline layout"),
+                bind_group_layouts: &[
+                    basic_bind_group_layout,
+                    potential_bind_group_layout,
+                ],
+                push_constant_ranges: &[],
+            }
+        );
+
+        // 势场作为背景覆盖整个窗口，与HUD一样不参与深度测试
+        let potential_pipeline = device.create_render_pipeline(&RenderPipelineDescriptor {
+            label: Some("Potential render pipeline"),
+            depth_stencil: Some(DepthStencilState {
+                format: DEPTH_FORMAT,
+                depth_write_enabled: false,
+                depth_compare: CompareFunction::Always,
+                stencil: StencilState::default(),
+                bias: DepthBiasState::default(),
+            }),
+            ..default_render_pipeline_descriptor!(
+                format,
+                sample_count,
+                potential_shader,
+                Some(&potential_pipeline_layout),
+                PrimitiveTopology::TriangleList,
+                &[Circle::QUAD_LAYOUT]
+            )
+        });
+
+        Pipelines {
+            pipeline,
+            line_pipeline,
+            line_strip_pipeline,
+            circle_pipeline,
+            circle_instanced_pipeline,
+            hud_pipeline,
+            potential_pipeline,
+        }
+    }
+
+    /// 从源码目录重新读取所有着色器并重建渲染管线
+    ///
+    /// 任一着色器读取失败或无法通过验证时返回错误，此时继续使用原有的着色器与渲染管线
+    pub fn reload_shaders(&mut self)-> Result<()> {
+        self.reload_shaders_from(Path::new(SHADER_DIR))
+    }
+
+    /// 从`dir`重新读取所有着色器并重建渲染管线，出错时的行为与`reload_shaders`相同
+    pub fn reload_shaders_from(&mut self, dir: &Path)-> Result<()> {
+        let read = |name: &'static str| {
+            std::fs::read_to_string(dir.join(name)).map(|source| (name, source))
+        };
+        let sources = [
+            read("generic.wgsl")?,
+            read("circle.wgsl")?,
+            read("circle_instanced.wgsl")?,
+            read("hud.wgsl")?,
+            read("potential.wgsl")?,
+        ];
+
+        // 捕获验证错误，避免着色器有误时触发wgpu默认的panic
+        self.device.push_error_scope(ErrorFilter::Validation);
+
+        let [shader, circle_shader, circle_instanced_shader, hud_shader, potential_shader] = sources.map(|(name, source)| {
+            self.device.create_shader_module(ShaderModuleDescriptor {
+                label: Some(name),
+                source: ShaderSource::Wgsl(source.into()),
+            })
+        });
+
+        let pipelines = Self::create_pipelines(
+            &self.device,
+            self.config.format,
+            self.sample_count,
+            &self.basic_bind_group_layout,
+            [&self.circle_bind_group_layout, &self.potential_bind_group_layout],
+            [&shader, &circle_shader, &circle_instanced_shader, &hud_shader, &potential_shader],
+        );
+
+        if let Some(e) = self.device.pop_error_scope().block_on() {
+            return Err(anyhow!("Failed to reload shaders: {}", e));
+        }
+
+        self.shader = shader;
+        self.circle_shader = circle_shader;
+        self.circle_instanced_shader = circle_instanced_shader;
+        self.hud_shader = hud_shader;
+        self.potential_shader = potential_shader;
+        self.pipeline = pipelines.pipeline;
+        self.line_pipeline = pipelines.line_pipeline;
+        self.line_strip_pipeline = pipelines.line_strip_pipeline;
+        self.circle_pipeline = pipelines.circle_pipeline;
+        self.circle_instanced_pipeline = pipelines.circle_instanced_pipeline;
+        self.hud_pipeline = pipelines.hud_pipeline;
+        self.potential_pipeline = pipelines.potential_pipeline;
+
+        Ok(())
+    }
+
+    /// 获取颜色缓冲与深度缓冲均支持的、不超过`requested`的最大采样数
+    fn supported_sample_count(adapter: &Adapter, format: TextureFormat, requested: u32)-> u32 {
+        let color = adapter.get_texture_format_features(format).flags;
+        let depth = adapter.get_texture_format_features(DEPTH_FORMAT).flags;
+
+        let count = [8, 4, 2, 1]
+            .into_iter()
+            .filter(|c| *c <= requested)
+            .find(|c| color.sample_count_supported(*c) && depth.sample_count_supported(*c))
+            .unwrap_or(1);
+
+        if count != requested {
+            log::warn!("MSAA sample count {} is not supported, falling back to {}", requested, count);
+        }
+
+        count
+    }
+
+    /// 创建与surface大小相同的多重采样颜色缓冲
+    fn create_msaa_texture(device: &Device, config: &SurfaceConfiguration, sample_count: u32)-> Option<(Texture, TextureView)> {
+        if sample_count == 1 {
+            return None;
+        }
+
+        let texture = device.create_texture(&TextureDescriptor {
+            label: Some("MSAA texture"),
+            size: Extent3d {
+                width: config.width,
+                height: config.height,
+                depth_or_array_layers: 1,
+            },
+            mip_level_count: 1,
+            sample_count,
+            dimension: TextureDimension::D2,
+            format: config.format,
+            usage: TextureUsages::RENDER_ATTACHMENT,
+            view_formats: &[],
+        });
+        let view = texture.create_view(&TextureViewDescriptor::default());
+
+        Some((texture, view))
+    }
+
+    /// 获取绘制到`view`上时使用的颜色附件
+    ///
+    /// 使用MSAA时绘制到多重采样颜色缓冲上，并解析到`view`
+    pub fn color_attachment<'a>(&'a self, view: &'a TextureView, load: LoadOp<Color>)-> RenderPassColorAttachment<'a> {
+        let ops = Operations {
+            load,
+            store: true,
+        };
+
+        match &self.msaa_texture {
+            Some((_, msaa_view)) => RenderPassColorAttachment {
+                view: msaa_view,
+                resolve_target: Some(view),
+                ops,
+            },
+
+            None => RenderPassColorAttachment {
+                view,
+                resolve_target: None,
+                ops,
+            },
+        }
+    }
+
+    /// 创建与surface大小相同的深度缓冲
+    fn create_depth_texture(device: &Device, size: (u32, u32), sample_count: u32)-> (Texture, TextureView) {
+        let texture = device.create_texture(&TextureDescriptor {
+            label: Some("Depth texture"),
+            size: Extent3d {
+                width: size.0,
+                height: size.1,
+                depth_or_array_layers: 1,
+            },
+            mip_level_count: 1,
+            sample_count,
+            dimension: TextureDimension::D2,
+            format: DEPTH_FORMAT,
+            usage: TextureUsages::RENDER_ATTACHMENT,
+            view_formats: &[],
+        });
+        let view = texture.create_view(&TextureViewDescriptor::default());
+
+        (texture, view)
+    }
+
+    /// 创建一个绘制到`view`上的`RenderContext`
+    pub fn render_context<'a>(&'a self, view: &'a TextureView)-> RenderContext<'a> {
+        RenderContext {
+            view,
+            renderer: self,
+            encoder: Some(self.device.create_command_encoder(&CommandEncoderDescriptor::default())),
+        }
+    }
+
+    /// 绘制一组物体
+    ///
+    /// 可以表示为圆形的物体会在最后通过`Instances`一次性绘制，其余物体按顺序逐个绘制
+    pub fn draw_items(&self, view: &TextureView, items: &[&dyn Drawable]) {
+        let mut instances = Instances::default();
+
+        for i in items {
+            match (i.as_circle(self), self.circle_segments) {
+                (Some(circle), Some(segments)) => circle.draw_mesh(self.render_context(view), segments),
+                (Some(circle), None) => instances.circles.push(circle),
+                (None, _) => i.draw(self.render_context(view)),
+            }
+        }
+
+        instances.draw(self.render_context(view));
+    }
+
+    /// 获取窗口的下一帧
+    ///
+    /// Panics:
+    /// 以`new_headless`创建的渲染器没有窗口，调用时会触发panic
+    pub fn get_current_texture(&self)-> std::result::Result<SurfaceTexture, SurfaceError> {
+        self.surface
+            .as_ref()
+            .expect("Headless renderer has no surface")
+            .get_current_texture()
+    }
+
+    /// 清空画面，然后依次绘制星空背景、`items`与HUD
+    pub fn render_frame(&self, view: &TextureView, items: &[&dyn Drawable]) {
+        let mut encoder = self.device.create_command_encoder(&CommandEncoderDescriptor::default());
+        let render_pass = encoder.begin_render_pass(&RenderPassDescriptor {
+            label: Some("Clear render pass"),
+            color_attachments: &[Some(self.color_attachment(view, LoadOp::Clear(self.clear_color)))],
+            depth_stencil_attachment: Some(RenderPassDepthStencilAttachment {
+                view: &self.depth_view,
+                depth_ops: Some(Operations {
+                    load: LoadOp::Clear(1.0),
+                    store: true,
+                }),
+                stencil_ops: None,
+            }),
+        });
+        drop(render_pass);
+        self.queue.submit(std::iter::once(encoder.finish()));
+
+        self.draw_starfield(self.render_context(view));
+        self.draw_items(view, items);
+        self.draw_hud(self.render_context(view));
+    }
+
+    /// 将`items`绘制到与surface大小相同的离屏纹理上，并读回为RGBA像素
+    ///
+    /// 绘制内容与`render_frame`相同，surface的格式不是8位RGBA或BGRA时返回错误
+    pub fn capture_frame(&self, items: &[&dyn Drawable])-> Result<Screenshot> {
+        let format = self.config.format;
+        let bgra = match format {
+            TextureFormat::Rgba8Unorm | TextureFormat::Rgba8UnormSrgb => false,
+            TextureFormat::Bgra8Unorm | TextureFormat::Bgra8UnormSrgb => true,
+            _ => return Err(anyhow!("Unsupported surface format for capturing: {:?}", format)),
+        };
+
+        let (width, height) = (self.config.width, self.config.height);
+        let size = Extent3d {
+            width,
+            height,
+            depth_or_array_layers: 1,
+        };
+
+        let texture = self.device.create_texture(&TextureDescriptor {
+            label: Some("Capture texture"),
+            size,
+            mip_level_count: 1,
+            sample_count: 1,
+            dimension: TextureDimension::D2,
+            format,
+            usage: TextureUsages::RENDER_ATTACHMENT | TextureUsages::COPY_SRC,
+            view_formats: &[],
+        });
+        let view = texture.create_view(&TextureViewDescriptor::default());
+        self.render_frame(&view, items);
+
+        let bytes_per_row = padded_bytes_per_row(width);
+        let buffer = self.device.create_buffer(&BufferDescriptor {
+            label: Some("Capture buffer"),
+            size: (bytes_per_row * height) as BufferAddress,
+            usage: BufferUsages::COPY_DST | BufferUsages::MAP_READ,
+            mapped_at_creation: false,
+        });
+
+        let mut encoder = self.device.create_command_encoder(&CommandEncoderDescriptor::default());
+        encoder.copy_texture_to_buffer(
+            texture.as_image_copy(),
+            ImageCopyBuffer {
+                buffer: &buffer,
+                layout: ImageDataLayout {
+                    offset: 0,
+                    bytes_per_row: Some(bytes_per_row),
+                    rows_per_image: Some(height),
+                },
+            },
+            size,
+        );
+        self.queue.submit(std::iter::once(encoder.finish()));
+
+        let slice = buffer.slice(..);
+        let (sender, receiver) = std::sync::mpsc::channel();
+        slice.map_async(MapMode::Read, move |result| {
+            let _ = sender.send(result);
+        });
+        self.device.poll(Maintain::Wait);
+        receiver.recv()??;
+
+        let mut rgba = unpad_rows(&slice.get_mapped_range(), width, height);
+        buffer.unmap();
+
+        if bgra {
+            for pixel in rgba.chunks_exact_mut(BYTES_PER_PIXEL as usize) {
+                pixel.swap(0, 2);
+            }
+        }
+
+        Ok(Screenshot {
+            width,
+            height,
+            rgba,
+        })
+    }
+
+    /// 用`basic_bind_group_data`更新整个`BasicUniform`
+    fn update_buffer(&self) {
+        self.queue.write_buffer(
+            &self.basic_bind_group_buffer,
+            0,
+            cast_slice(&[self.basic_bind_group_data]),
+        );
+    }
+
+    /// 修改surface的大小，并同步`BasicUniform`
+    pub fn resize(&mut self, new_size: (u32, u32)) {
+        if new_size.0 > 0 && new_size.1 > 0 {
+            self.size = new_size;
+            self.config.width = new_size.0;
+            self.config.height = new_size.1;
+            if let Some(surface) = &self.surface {
+                surface.configure(&self.device, &self.config);
+            }
+
+            (self.depth_texture, self.depth_view) = Self::create_depth_texture(&self.device, new_size, self.sample_count);
+            self.msaa_texture = Self::create_msaa_texture(&self.device, &self.config, self.sample_count);
+
+            self.basic_bind_group_data.aspect_ratio = new_size.0 as f32 / new_size.1 as f32;
+            self.update_buffer();
+        }
+    }
+
+    /// 复制当前的相机状态
+    pub fn camera(&self)-> Camera {
+        Camera {
+            scale_base: self.scale_base,
+            scale: self.scale,
+            size: self.size,
+            coord: self.basic_bind_group_data.camera_coord,
+        }
+    }
+
+    /// 使用`camera`的缩放比例与相机坐标，窗口大小只能通过`resize`修改
+    pub fn set_camera(&mut self, camera: Camera) {
+        self.scale_base = camera.scale_base;
+        self.scale = camera.scale;
+        self.basic_bind_group_data.scale = camera.scale.to_f32();
+        self.basic_bind_group_data.camera_coord = camera.coord;
+        self.update_buffer();
+    }
+
+    pub fn scale_from_array3(&self, s: [BigFloat; 3])-> [f32; 3] {
+        self.camera().scale_from_array3(s)
+    }
+
+    pub fn scale_from_point(&self, p: Point)-> [f32; 3] {
+        self.camera().scale_from_point(p)
+    }
+
+    /// 见`Camera::ndc_from_point`
+    pub fn ndc_from_point(&self, p: Point)-> [f32; 2] {
+        self.camera().ndc_from_point(p)
+    }
+
+    /// 见`Camera::screen_from_point`
+    pub fn screen_from_point(&self, p: Point)-> (f32, f32) {
+        self.camera().screen_from_point(p)
+    }
+
+    /// 见`Camera::label_anchor`
+    pub fn label_anchor(&self, center: Point, radius: BigFloat)-> (f32, f32) {
+        self.camera().label_anchor(center, radius)
+    }
+
+    /// 见`Camera::point_from_screen`
+    pub fn point_from_screen(&self, screen: PhysicalPosition<f64>)-> Point {
+        self.camera().point_from_screen(screen)
+    }
+
+    /// 见`Camera::fit_bounds`
+    pub fn fit_bounds(&mut self, min: Point, max: Point) {
+        let mut camera = self.camera();
+        camera.fit_bounds(min, max);
+        self.set_camera(camera);
+    }
+
+    /// 见`Camera::center_on`
+    pub fn center_on(&mut self, p: Point) {
+        let mut camera = self.camera();
+        camera.center_on(p);
+        self.set_camera(camera);
+    }
+
+    /// 按与坐标相同的比例缩放一段长度
+    pub fn scale_length(&self, length: BigFloat)-> f32 {
+        self.camera().scale_length(length)
+    }
+
+    /// 缩放视图
+    pub fn scale(&mut self, scale: BigFloat) {
+        self.scale = scale;
+        self.basic_bind_group_data.scale = scale.to_f32();
+        self.update_buffer();
+    }
+
+    /// 移动相机到指定坐标
+    pub fn move_camera(&mut self, new_coord: [f32; 3]) {
+        self.basic_bind_group_data.camera_coord = new_coord;
+        self.update_buffer();
+    }
+
+    /// 绘制星空背景，应在绘制物体之前调用
+    ///
+    /// 仅在`starfield`为`true`时绘制
+    pub fn draw_starfield(&self, ctx: RenderContext<'_>) {
+        if self.starfield {
+            self.stars.draw(ctx);
+        }
+    }
+
+    /// 在窗口左上角绘制相机坐标、缩放比例、时间流逝速度等信息
+    ///
+    /// 仅在`debug.enabled`为`true`时绘制
+    pub fn draw_hud(&self, ctx: RenderContext<'_>) {
+        if let Some(lines) = self.debug.lines(&self.camera()) {
+            HudText::new(lines, [0.9, 0.9, 0.9, 1.0]).draw(ctx);
+        }
+    }
+}
+
+
+
+#[repr(C)]
+#[derive(Debug, Pod, Zeroable, Clone, Copy)]
+pub struct Vertex {
+    pub position: [f32; 3],
+    pub color: [f32; 4],
+}
+
+impl Vertex {
+    pub const LAYOUT: VertexBufferLayout<'_> = VertexBufferLayout {
+        array_stride: size_of::<Self>() as u64,
+        step_mode: VertexStepMode::Vertex,
+        attributes: &[
+            VertexAttribute {
+                offset: 0,
+                shader_location: 0,
+                format: VertexFormat::Float32x3,
+            },
+
+            VertexAttribute {
+                offset: size_of::<[f32; 3]>() as u64,
                 shader_location: 1,
                 format: VertexFormat::Float32x4,
             },
@@ -345,6 +1309,7 @@ impl Drop for RenderContext<'_> {
 
 
 /// 绘制一个矩形
+#[derive(Debug, Clone, Copy)]
 pub struct Rectangle {
     pub vertices: [Vertex; 4],
 }
@@ -357,6 +1322,17 @@ impl Rectangle {
             vertices: [p1, p2, p3, p4],
         }
     }
+
+    /// 创建以`center`为中心、边长为`2 * half_size`的纯色正方形
+    pub fn square(center: [f32; 3], half_size: f32, color: [f32; 4])-> Self {
+        let [x, y, z] = center;
+        let vertex = |dx: f32, dy: f32| Vertex {
+            position: [x + dx * half_size, y + dy * half_size, z],
+            color,
+        };
+
+        Self::new(vertex(-1.0, -1.0), vertex(1.0, -1.0), vertex(1.0, 1.0), vertex(-1.0, 1.0))
+    }
 }
 
 impl Drawable for Rectangle {
@@ -385,6 +1361,40 @@ impl Drawable for Rectangle {
     }
 }
 
+/// 绘制一条线段
+///
+/// 使用`LineList`拓扑绘制，线宽固定为1像素(WebGPU不支持其他线宽)
+#[derive(Debug, Clone, Copy)]
+pub struct Line {
+    pub start: Vertex,
+    pub end: Vertex,
+}
+
+impl Line {
+    pub fn new(start: Vertex, end: Vertex)-> Self {
+        Self {
+            start,
+            end,
+        }
+    }
+}
+
+impl Drawable for Line {
+    fn draw(&self, mut ctx: RenderContext<'_>) {
+        let vertices = ctx.renderer.device.create_buffer_init(&BufferInitDescriptor {
+            label: Some("Line vertex buffer"),
+            contents: cast_slice(&[self.start, self.end]),
+            usage: BufferUsages::VERTEX,
+        });
+
+        let mut render_pass = load_render_pass_from_render_context!(ctx);
+
+        render_pass.set_pipeline(&ctx.renderer.line_pipeline);
+        render_pass.set_vertex_buffer(0, vertices.slice(..));
+        render_pass.draw(0..2, 0..1);
+    }
+}
+
 /// 绘制一个圆形
 /// 顶点着色器默认，片段着色器使用`circle_fs`
 #[repr(C)]
@@ -393,34 +1403,232 @@ pub struct Circle {
     pub center: [f32; 3],
     pub radius: f32,
     pub fill_color: [f32; 4],
-}
 
-impl Drawable for Circle {
-    fn draw(&self, mut ctx: RenderContext<'_>) {
-        let r = self.radius;
-        let mut points = [self.center, self.center, self.center, self.center];
-
-        points[0][0] -= r;
-        points[0][1] += r;
-        points[1][0] -= r;
-        points[1][1] -= r;
-        points[2][0] += r;
-        points[2][1] -= r;
-        points[3][0] += r;
-        points[3][1] += r;
-
-        let mut vertices_vec = Vec::new();
-        points.into_iter()
-            .for_each(|i| vertices_vec.push(Vertex {
-                position: i,
-                color: self.fill_color.clone()
-        }));
+    /// 轮廓的颜色
+    pub outline_color: [f32; 4],
 
-        let vertices = ctx.renderer.device.create_buffer_init(
-            &BufferInitDescriptor {
-                label: Some("Circle vertex buffer"),
-                contents: cast_slice(vertices_vec.as_slice()),
-                usage: BufferUsages::VERTEX,
+    /// 轮廓的宽度，与`radius`使用相同的单位，为0时不绘制轮廓
+    pub outline_width: f32,
+}
+
+/// 单独绘制圆形时传给`circle_fs`的uniform，字段顺序与对齐与`circle.wgsl`中的`CircleData`一致
+#[repr(C)]
+#[derive(Debug, Clone, Copy, Pod, Zeroable)]
+pub struct CircleUniform {
+    pub center: [f32; 3],
+    pub radius: f32,
+    pub outline_color: [f32; 4],
+    pub outline_width: f32,
+    pub _padding: [f32; 3],
+}
+
+impl Circle {
+    /// 创建一个没有轮廓的圆形
+    pub fn new(center: [f32; 3], radius: f32, fill_color: [f32; 4])-> Self {
+        Self {
+            center,
+            radius,
+            fill_color,
+            outline_color: [0.0; 4],
+            outline_width: 0.0,
+        }
+    }
+
+    /// 创建内外半径分别为`inner`与`outer`的圆环
+    ///
+    /// 以填充色完全透明、宽度为`outer - inner`的轮廓表示，着色器会丢弃轮廓以内的片段
+    pub fn ring(center: [f32; 3], inner: f32, outer: f32, color: [f32; 4])-> Self {
+        Self::new(center, outer, [0.0; 4]).with_outline(color, outer - inner)
+    }
+
+    /// 为圆形加上宽度为`width`的轮廓
+    pub fn with_outline(self, color: [f32; 4], width: f32)-> Self {
+        Self {
+            outline_color: color,
+            outline_width: width,
+            ..self
+        }
+    }
+
+    /// 单独绘制该圆形时使用的uniform
+    pub fn uniform(&self)-> CircleUniform {
+        CircleUniform {
+            center: self.center,
+            radius: self.radius,
+            outline_color: self.outline_color,
+            outline_width: self.outline_width,
+            _padding: [0.0; 3],
+        }
+    }
+
+    /// 批量绘制时所用正方形的四个角，以半径为单位，顺序与`Rectangle::INDICES`对应
+    pub const QUAD_CORNERS: [[f32; 2]; 4] = [[-1.0, 1.0], [-1.0, -1.0], [1.0, -1.0], [1.0, 1.0]];
+
+    pub const QUAD_LAYOUT: VertexBufferLayout<'_> = VertexBufferLayout {
+        array_stride: size_of::<[f32; 2]>() as u64,
+        step_mode: VertexStepMode::Vertex,
+        attributes: &[
+            VertexAttribute {
+                offset: 0,
+                shader_location: 0,
+                format: VertexFormat::Float32x2,
+            },
+        ],
+    };
+
+    /// 批量绘制时每个圆形作为一个实例的布局
+    pub const INSTANCE_LAYOUT: VertexBufferLayout<'_> = VertexBufferLayout {
+        array_stride: size_of::<Self>() as u64,
+        step_mode: VertexStepMode::Instance,
+        attributes: &[
+            VertexAttribute {
+                offset: 0,
+                shader_location: 1,
+                format: VertexFormat::Float32x3,
+            },
+
+            VertexAttribute {
+                offset: size_of::<[f32; 3]>() as u64,
+                shader_location: 2,
+                format: VertexFormat::Float32,
+            },
+
+            VertexAttribute {
+                offset: size_of::<[f32; 4]>() as u64,
+                shader_location: 3,
+                format: VertexFormat::Float32x4,
+            },
+
+            VertexAttribute {
+                offset: size_of::<[f32; 8]>() as u64,
+                shader_location: 4,
+                format: VertexFormat::Float32x4,
+            },
+
+            VertexAttribute {
+                offset: size_of::<[f32; 12]>() as u64,
+                shader_location: 5,
+                format: VertexFormat::Float32,
+            },
+        ],
+    };
+}
+
+impl<T: Renderable> Drawable for T {
+    fn draw(&self, ctx: RenderContext<'_>) {
+        match primitive(self, &ctx.renderer.camera()) {
+            Primitive::Circle(circle) => circle.draw(ctx),
+            Primitive::Rectangle(rectangle) => rectangle.draw(ctx),
+        }
+    }
+
+    /// 只有形状为圆的物体可以批量绘制
+    fn as_circle(&self, renderer: &Renderer)-> Option<Circle> {
+        match (self.shape(), primitive(self, &renderer.camera())) {
+            (Shape::Circle, Primitive::Circle(circle)) => Some(circle),
+            _ => None,
+        }
+    }
+}
+
+/// 按`camera`缩放`item`的坐标与大小，并按其形状转换为图元
+pub fn primitive<R: Renderable + ?Sized>(item: &R, camera: &Camera)-> Primitive {
+    let center = camera.scale_from_point(item.center());
+
+    match item.shape() {
+        Shape::Circle => {
+            let circle = Circle::new(center, camera.scale_length(item.radius()), item.color());
+            Primitive::Circle(match item.outline() {
+                Some((color, width)) => circle.with_outline(color, camera.scale_length(width)),
+                None => circle,
+            })
+        },
+
+        Shape::Square => {
+            Primitive::Rectangle(Rectangle::square(center, camera.scale_length(item.radius()), item.color()))
+        },
+
+        Shape::Ring { inner, outer } => {
+            Primitive::Circle(Circle::ring(center, camera.scale_length(inner), camera.scale_length(outer), item.color()))
+        },
+    }
+}
+
+impl Circle {
+    /// 单独绘制时覆盖该圆形的正方形的四个顶点，顺序与`QUAD_CORNERS`相同，以`Rectangle::INDICES`绘制
+    ///
+    /// 正方形内圆形以外的片段由`circle_fs`丢弃
+    pub fn quad(&self)-> [Vertex; 4] {
+        let [x, y, z] = self.center;
+        Self::QUAD_CORNERS.map(|[dx, dy]| Vertex {
+            position: [x + dx * self.radius, y + dy * self.radius, z],
+            color: self.fill_color,
+        })
+    }
+
+    /// 生成以三角形扇近似该圆形的网格，顶点坐标与`center`处于同一坐标系
+    ///
+    /// 网格不包含轮廓。第一个顶点为圆心，其后`segments`个顶点均匀分布在圆周上，`segments`小于3时按3处理
+    pub fn as_mesh(&self, segments: u16)-> (Vec<Vertex>, Vec<u16>) {
+        let segments = segments.max(3);
+        let mut vertices = Vec::with_capacity(segments as usize + 1);
+        let mut indices = Vec::with_capacity(segments as usize * 3);
+
+        vertices.push(Vertex {
+            position: self.center,
+            color: self.fill_color,
+        });
+
+        for i in 0..segments {
+            let angle = i as f32 / segments as f32 * std::f32::consts::TAU;
+            let (sin, cos) = angle.sin_cos();
+            vertices.push(Vertex {
+                position: [self.center[0] + self.radius * cos, self.center[1] + self.radius * sin, self.center[2]],
+                color: self.fill_color,
+            });
+
+            indices.extend_from_slice(&[0, i + 1, (i + 1) % segments + 1]);
+        }
+
+        (vertices, indices)
+    }
+
+    /// 以`as_mesh`生成的网格绘制该圆形
+    pub fn draw_mesh(&self, mut ctx: RenderContext<'_>, segments: u16) {
+        let (vertices_vec, indices_vec) = self.as_mesh(segments);
+
+        let vertices = ctx.renderer.device.create_buffer_init(&BufferInitDescriptor {
+            label: Some("Circle mesh vertex buffer"),
+            contents: cast_slice(vertices_vec.as_slice()),
+            usage: BufferUsages::VERTEX,
+        });
+
+        let indices = ctx.renderer.device.create_buffer_init(&BufferInitDescriptor {
+            label: Some("Circle mesh index buffer"),
+            contents: cast_slice(indices_vec.as_slice()),
+            usage: BufferUsages::INDEX,
+        });
+
+        let mut render_pass = load_render_pass_from_render_context!(ctx);
+
+        render_pass.set_pipeline(&ctx.renderer.pipeline);
+        render_pass.set_vertex_buffer(0, vertices.slice(..));
+        render_pass.set_index_buffer(indices.slice(..), IndexFormat::Uint16);
+        render_pass.draw_indexed(0..indices_vec.len() as u32, 0, 0..1);
+    }
+}
+
+impl Drawable for Circle {
+    fn as_circle(&self, _renderer: &Renderer)-> Option<Circle> {
+        Some(*self)
+    }
+
+    fn draw(&self, mut ctx: RenderContext<'_>) {
+        let vertices = ctx.renderer.device.create_buffer_init(
+            &BufferInitDescriptor {
+                label: Some("Circle vertex buffer"),
+                contents: cast_slice(&self.quad()),
+                usage: BufferUsages::VERTEX,
             }
         );
 
@@ -432,29 +1640,10 @@ impl Drawable for Circle {
             }
         );
 
-        let circle_bind_group_layout = ctx.renderer.device.create_bind_group_layout(
-            &BindGroupLayoutDescriptor {
-                label: Some("Circle bind group layout"),
-                entries: &[
-                    BindGroupLayoutEntry {
-                        binding: 0,
-                        visibility: ShaderStages::FRAGMENT,
-                        count: None,
-                        ty: BindingType::Buffer {
-                            min_binding_size: None,
-                            has_dynamic_offset: false,
-                            ty: BufferBindingType::Uniform,
-                        },
-                    },
-                ],
-            }
-        );
-
-        let circle_data = [self.center[0], self.center[1], self.center[2], self.radius];
         let circle_bind_group_buffer = ctx.renderer.device.create_buffer_init(
             &BufferInitDescriptor {
                 label: Some("Circle bind group buffer"),
-                contents: cast_slice(&circle_data),
+                contents: cast_slice(&[self.uniform()]),
                 usage: BufferUsages::UNIFORM | BufferUsages::COPY_DST,
             }
         );
@@ -462,7 +1651,7 @@ impl Drawable for Circle {
         let circle_bind_group = ctx.renderer.device.create_bind_group(
             &BindGroupDescriptor {
                 label: Some("Circle bind group"),
-                layout: &circle_bind_group_layout,
+                layout: &ctx.renderer.circle_bind_group_layout,
                 entries: &[
                     BindGroupEntry {
                         binding: 0,
@@ -476,71 +1665,882 @@ impl Drawable for Circle {
             }
         );
 
-        let circle_pipeline_layout = ctx.renderer.device.create_pipeline_layout(
-            &PipelineLayoutDescriptor {
-                label: Some("Circle pipeline layout"),
-                bind_group_layouts: &[
-                    &ctx.renderer.basic_bind_group_layout,
-                    &circle_bind_group_layout,
-                ],
-                push_constant_ranges: &[],
+        let mut render_pass = load_render_pass_from_render_context!(ctx);
+
+        render_pass.set_pipeline(&ctx.renderer.circle_pipeline);
+        render_pass.set_vertex_buffer(0, vertices.slice(..));
+        render_pass.set_index_buffer(indices.slice(..), IndexFormat::Uint16);
+        render_pass.set_bind_group(1, &circle_bind_group, &[]);
+        render_pass.draw_indexed(0..6, 0, 0..1);
+    }
+}
+
+/// 物体的轨迹
+///
+/// 保存物体最近经过的若干个位置，绘制为一条折线，越旧的位置越透明
+pub struct OrbitTrail {
+    points: VecDeque<Point>,
+    max_len: usize,
+
+    /// 相邻两个记录点之间的最小距离，以m为单位
+    ///
+    /// 物理计算的步长通常很小，逐步记录会使轨迹过短
+    pub min_distance: BigFloat,
+    pub color: [f32; 4],
+}
+
+impl OrbitTrail {
+    pub fn new(max_len: usize, min_distance: BigFloat, color: [f32; 4])-> Self {
+        Self {
+            points: VecDeque::with_capacity(max_len),
+            max_len,
+            min_distance,
+            color,
+        }
+    }
+
+    /// 记录一个新的位置，超出最大长度时丢弃最旧的位置
+    ///
+    /// 与上一个记录点的距离小于`min_distance`时忽略
+    pub fn push(&mut self, point: Point) {
+        if self.max_len == 0 {
+            return;
+        }
+
+        if let Some(last) = self.points.back() {
+            if last.distance(&point) < self.min_distance {
+                return;
             }
-        );
+        }
 
-        let circle_pipeline = ctx.renderer.device.create_render_pipeline(
-            &RenderPipelineDescriptor {
-                label: Some("Circle render pipeline"),
-                layout: Some(&circle_pipeline_layout),
-                vertex: VertexState {
-                    module: &ctx.renderer.shader,
-                    entry_point: "vs_main",
-                    buffers: &[Vertex::LAYOUT],
-                },
-                fragment: Some(FragmentState {
-                    module: &ctx.renderer.circle_shader,
-                    entry_point: "circle_fs",
-                    targets: &[Some(ColorTargetState {
-                        format: ctx.renderer.config.format,
-                        blend: Some(BlendState {
-                            color: BlendComponent {
-                                src_factor: BlendFactor::SrcAlpha,
-                                dst_factor: BlendFactor::OneMinusSrcAlpha,
-                                operation: BlendOperation::Add,
-                            },
-                            alpha: BlendComponent {
-                                src_factor: BlendFactor::One,
-                                dst_factor: BlendFactor::OneMinusSrcAlpha,
-                                operation: BlendOperation::Add,
-                            },
-                        }),
-                        write_mask: ColorWrites::ALL,
-                    })],
+        if self.points.len() == self.max_len {
+            self.points.pop_front();
+        }
+
+        self.points.push_back(point);
+    }
+
+    pub fn clear(&mut self) {
+        self.points.clear();
+    }
+
+    pub fn len(&self)-> usize {
+        self.points.len()
+    }
+
+    pub fn is_empty(&self)-> bool {
+        self.points.is_empty()
+    }
+}
+
+impl Drawable for OrbitTrail {
+    fn draw(&self, mut ctx: RenderContext<'_>) {
+        // 只绘制已记录的位置，少于两个点时无法构成线段
+        if self.points.len() < 2 {
+            return;
+        }
+
+        let n = self.points.len();
+        let vertices_vec: Vec<Vertex> = self.points
+            .iter()
+            .enumerate()
+            .map(|(i, p)| {
+                let mut color = self.color;
+                color[3] *= (i + 1) as f32 / n as f32;
+                Vertex {
+                    position: ctx.renderer.scale_from_point(*p),
+                    color,
+                }
+            })
+            .collect();
+
+        let vertices = ctx.renderer.device.create_buffer_init(&BufferInitDescriptor {
+            label: Some("Orbit trail vertex buffer"),
+            contents: cast_slice(vertices_vec.as_slice()),
+            usage: BufferUsages::VERTEX,
+        });
+
+        let mut render_pass = load_render_pass_from_render_context!(ctx);
+
+        render_pass.set_pipeline(&ctx.renderer.line_strip_pipeline);
+        render_pass.set_vertex_buffer(0, vertices.slice(..));
+        render_pass.draw(0..n as u32, 0..1);
+    }
+}
+
+impl Drawable for HudText {
+    fn draw(&self, ctx: RenderContext<'_>) {
+        let (vertices, indices) = self.vertices(ctx.renderer.size);
+        draw_screen_space(ctx, &vertices, &indices);
+    }
+}
+
+impl Drawable for Starfield {
+    fn draw(&self, ctx: RenderContext<'_>) {
+        let (vertices, indices) = self.vertices(ctx.renderer.size);
+        draw_screen_space(ctx, &vertices, &indices);
+    }
+}
+
+/// 将`value`在`[min, max]`中的位置映射为由蓝到红的颜色
+///
+/// `value`为`min`时为蓝色，为`max`时为红色，其间线性插值，超出范围时取端点的颜色。
+/// `max`不大于`min`时返回中间的颜色
+pub fn colormap(value: f64, min: f64, max: f64)-> [f32; 4] {
+    const LOW: [f32; 3] = [0.2, 0.35, 1.0];
+    const HIGH: [f32; 3] = [1.0, 0.25, 0.2];
+
+    let t = if max > min {
+        ((value - min) / (max - min)).clamp(0.0, 1.0) as f32
+    } else {
+        0.5
+    };
+
+    [
+        LOW[0] + (HIGH[0] - LOW[0]) * t,
+        LOW[1] + (HIGH[1] - LOW[1]) * t,
+        LOW[2] + (HIGH[2] - LOW[2]) * t,
+        1.0,
+    ]
+}
+
+/// 使用HUD管线绘制顶点坐标为标准化设备坐标的三角形
+fn draw_screen_space(mut ctx: RenderContext<'_>, vertices_vec: &[Vertex], indices_vec: &[u32]) {
+    if indices_vec.is_empty() {
+        return;
+    }
+
+    let vertices = ctx.renderer.device.create_buffer_init(&BufferInitDescriptor {
+        label: Some("HUD vertex buffer"),
+        contents: cast_slice(vertices_vec),
+        usage: BufferUsages::VERTEX,
+    });
+
+    let indices = ctx.renderer.device.create_buffer_init(&BufferInitDescriptor {
+        label: Some("HUD index buffer"),
+        contents: cast_slice(indices_vec),
+        usage: BufferUsages::INDEX,
+    });
+
+    let mut render_pass = load_render_pass_from_render_context!(ctx);
+
+    render_pass.set_pipeline(&ctx.renderer.hud_pipeline);
+    render_pass.set_vertex_buffer(0, vertices.slice(..));
+    render_pass.set_index_buffer(indices.slice(..), IndexFormat::Uint32);
+    render_pass.draw_indexed(0..indices_vec.len() as u32, 0, 0..1);
+}
+
+/// 从某点出发的箭头，用于绘制物体的速度、受力等向量
+///
+/// 箭身由`start`指向`start + vector * scale`，末端有两条短线构成的箭头
+#[derive(Debug, Clone, Copy)]
+pub struct Arrow {
+    pub start: Point,
+    pub vector: Vector,
+
+    /// 向量与箭头长度(以m为单位)的比例
+    ///
+    /// 对于速度即为时长，箭头的长度为物体以当前速度运动`scale`秒所经过的距离
+    pub scale: BigFloat,
+    pub color: [f32; 4],
+}
+
+impl Arrow {
+    /// 箭头两侧短线的长度与箭头总长度之比
+    const HEAD_RATIO: f32 = 0.2;
+
+    /// 箭头两侧短线与箭身的夹角，以弧度为单位
+    const HEAD_ANGLE: f32 = 0.45;
+
+    pub fn new(start: Point, vector: Vector, scale: BigFloat, color: [f32; 4])-> Self {
+        Self {
+            start,
+            vector,
+            scale,
+            color,
+        }
+    }
+
+    /// 计算使一组向量中最长的一个对应的箭头长度为`length`的比例
+    ///
+    /// 所有向量均为零向量(或为空)时返回`None`
+    pub fn normalized_scale(vectors: &[Vector], length: BigFloat)-> Option<BigFloat> {
+        let max = vectors
+            .iter()
+            .map(|v| v.model())
+            .fold(BigFloat::from(0), |a, b| a.max(&b));
+
+        if max == BigFloat::from(0) {
+            None
+        } else {
+            Some(length / max)
+        }
+    }
+
+    /// 获取构成箭头的三条线段，向量为0时返回空
+    pub fn lines(&self, renderer: &Renderer)-> Vec<Line> {
+        let start = renderer.scale_from_point(self.start);
+        let end = renderer.scale_from_point(self.start + self.vector * self.scale);
+
+        let (dx, dy) = (end[0] - start[0], end[1] - start[1]);
+        if dx == 0.0 && dy == 0.0 {
+            return Vec::new();
+        }
+
+        let vertex = |position: [f32; 3]| Vertex { position, color: self.color };
+
+        // 将由终点指向起点的方向旋转±HEAD_ANGLE，得到箭头两侧短线的端点
+        let head = |angle: f32| {
+            let (sin, cos) = angle.sin_cos();
+            let bx = -dx * Self::HEAD_RATIO;
+            let by = -dy * Self::HEAD_RATIO;
+            [end[0] + bx * cos - by * sin, end[1] + bx * sin + by * cos, end[2]]
+        };
+
+        vec![
+            Line::new(vertex(start), vertex(end)),
+            Line::new(vertex(end), vertex(head(Self::HEAD_ANGLE))),
+            Line::new(vertex(end), vertex(head(-Self::HEAD_ANGLE))),
+        ]
+    }
+}
+
+impl Drawable for Arrow {
+    fn draw(&self, ctx: RenderContext<'_>) {
+        for line in self.lines(ctx.renderer) {
+            line.draw(ctx.renderer.render_context(ctx.view));
+        }
+    }
+}
+
+/// xy平面上的参考网格与穿过原点的坐标轴
+///
+/// 网格线的间距随缩放比例变化，取1、2、5乘以10的整数次幂中使屏幕上的间距不小于`Grid::MIN_SPACING`的最小值。
+/// x轴为红色，y轴为绿色；视线沿z轴方向，z轴投影为原点，以原点处的蓝色十字表示。
+/// 应在天体之前绘制，使天体覆盖网格
+#[derive(Debug, Clone, Copy)]
+pub struct Grid {
+    /// 网格线的颜色
+    pub color: [f32; 4],
+}
+
+impl Grid {
+    /// 相邻网格线在屏幕上的最小间距，以窗口半宽为单位
+    pub const MIN_SPACING: f64 = 0.15;
+
+    /// 原点处表示z轴的十字的半长，以窗口半宽为单位
+    const Z_MARK_SIZE: f32 = 0.02;
+
+    /// 每个方向最多绘制的网格线数，避免间距低于浮点精度时生成过多的线
+    const MAX_LINES: i64 = 256;
+
+    pub fn new(color: [f32; 4])-> Self {
+        Self {
+            color,
+        }
+    }
+
+    /// 计算给定缩放比例下相邻网格线之间的距离，以m为单位
+    ///
+    /// 世界坐标中长度为`l`的线段在屏幕上的长度为`l / scale_base * scale^2`(以窗口半宽为单位)，
+    /// 返回使该长度不小于`MIN_SPACING`的最小的`{1, 2, 5} * 10^n`
+    pub fn spacing(scale: f64, scale_base: f64)-> f64 {
+        let min = Self::MIN_SPACING * scale_base / (scale * scale);
+        let magnitude = 10f64.powf(min.log10().floor());
+
+        [1.0, 2.0, 5.0, 10.0]
+            .into_iter()
+            .map(|m| m * magnitude)
+            .find(|&s| s >= min)
+            .unwrap_or(10.0 * magnitude)
+    }
+
+    /// 覆盖`lo..=hi`的网格线坐标，按整数序号计算，最多`MAX_LINES + 1`条
+    fn positions(lo: f64, hi: f64, spacing: f64)-> impl Iterator<Item = f64> {
+        let first = (lo / spacing).floor() as i64;
+        let last = ((hi / spacing).ceil() as i64).min(first.saturating_add(Self::MAX_LINES));
+
+        (first..=last).map(move |i| i as f64 * spacing)
+    }
+
+    /// 获取覆盖当前视野的网格线与坐标轴
+    pub fn lines(&self, renderer: &Renderer)-> Vec<Line> {
+        let spacing = Self::spacing(renderer.scale.to_f64(), renderer.scale_base.to_f64());
+
+        // 窗口左上角与右下角对应的世界坐标
+        let top_left = renderer.point_from_screen(PhysicalPosition::new(0.0, 0.0));
+        let bottom_right = renderer.point_from_screen(PhysicalPosition::new(renderer.size.0 as f64, renderer.size.1 as f64));
+        let (x0, x1) = (top_left.x.to_f64(), bottom_right.x.to_f64());
+        let (y0, y1) = (bottom_right.y.to_f64(), top_left.y.to_f64());
+
+        let line = |from: (f64, f64), to: (f64, f64), color: [f32; 4]| {
+            let vertex = |(x, y): (f64, f64)| Vertex {
+                position: renderer.scale_from_point(Point {
+                    x: BigFloat::from(x),
+                    y: BigFloat::from(y),
+                    z: BigFloat::from(0),
                 }),
-                primitive: PrimitiveState {
-                    topology: PrimitiveTopology::TriangleList,
-                    strip_index_format: None,
-                    front_face: FrontFace::Ccw,
-                    cull_mode: None,
-                    unclipped_depth: false,
-                    polygon_mode: PolygonMode::Fill,
-                    conservative: false,
-                },
-                multisample: MultisampleState {
-                    count: 1,
-                    mask: !0,
-                    alpha_to_coverage_enabled: false,
+                color,
+            };
+            Line::new(vertex(from), vertex(to))
+        };
+
+        let mut lines = Vec::new();
+        for x in Self::positions(x0, x1, spacing) {
+            lines.push(line((x, y0), (x, y1), self.color));
+        }
+        for y in Self::positions(y0, y1, spacing) {
+            lines.push(line((x0, y), (x1, y), self.color));
+        }
+
+        lines.push(line((x0, 0.0), (x1, 0.0), [0.9, 0.3, 0.3, 1.0]));
+        lines.push(line((0.0, y0), (0.0, y1), [0.3, 0.9, 0.3, 1.0]));
+
+        // 十字的大小不随缩放比例变化
+        let half = Self::Z_MARK_SIZE as f64 * renderer.scale_base.to_f64() / renderer.scale.to_f64().powi(2);
+        let blue = [0.3, 0.5, 0.95, 1.0];
+        lines.push(line((-half, -half), (half, half), blue));
+        lines.push(line((-half, half), (half, -half), blue));
+
+        lines
+    }
+}
+
+impl Drawable for Grid {
+    fn draw(&self, ctx: RenderContext<'_>) {
+        for line in self.lines(ctx.renderer) {
+            line.draw(ctx.renderer.render_context(ctx.view));
+        }
+    }
+}
+
+/// 以背景色表示所有天体产生的引力势，势阱越深颜色越暗
+///
+/// 每个像素的颜色由各天体的`m / r`之和决定，`r`为在屏幕上的距离，因此与缩放比例无关。
+/// 着色器中最多计入`MAX_POTENTIAL_BODIES`个天体，超出时只计入质量最大的天体。
+/// 覆盖整个窗口，应在其他物体之前绘制
+#[derive(Debug, Clone, Default)]
+pub struct PotentialField {
+    /// 各天体的位置与质量
+    pub bodies: Vec<(Point, BigFloat)>,
+}
+
+/// 传给`potential.wgsl`的uniform，字段顺序与对齐与其中的`PotentialData`一致
+#[repr(C)]
+#[derive(Debug, Clone, Copy, Pod, Zeroable)]
+pub struct PotentialUniform {
+    /// 各天体在标准化设备坐标中的位置`[x, y]`、按总质量归一化的质量与一个未使用的分量
+    pub bodies: [[f32; 4]; MAX_POTENTIAL_BODIES],
+    pub count: u32,
+
+    /// 软化长度，以窗口半宽为单位，避免天体中心处颜色发散
+    pub softening: f32,
+    pub _padding: [f32; 2],
+}
+
+impl PotentialField {
+    /// 软化长度，以窗口半宽为单位
+    const SOFTENING: f32 = 0.01;
+
+    pub fn new(bodies: impl IntoIterator<Item = (Point, BigFloat)>)-> Self {
+        Self {
+            bodies: bodies.into_iter().collect(),
+        }
+    }
+
+    /// 将天体的数据打包为着色器使用的uniform
+    ///
+    /// 总质量不为正数时所有天体的权重为0
+    pub fn uniform(&self, camera: &Camera)-> PotentialUniform {
+        let total = self.bodies
+            .iter()
+            .fold(BigFloat::from(0), |acc, (_, m)| acc + *m);
+
+        let mut heaviest: Vec<&(Point, BigFloat)> = self.bodies.iter().collect();
+        heaviest.sort_by(|a, b| b.1.partial_cmp(&a.1).unwrap_or(std::cmp::Ordering::Equal));
+        heaviest.truncate(MAX_POTENTIAL_BODIES);
+
+        let mut uniform = PotentialUniform {
+            bodies: [[0.0; 4]; MAX_POTENTIAL_BODIES],
+            count: heaviest.len() as u32,
+            softening: Self::SOFTENING,
+            _padding: [0.0; 2],
+        };
+
+        for (slot, (center, mass)) in uniform.bodies.iter_mut().zip(heaviest) {
+            let [x, y] = camera.ndc_from_point(*center);
+            let weight = if total > BigFloat::from(0) { (*mass / total).to_f32() } else { 0.0 };
+            *slot = [x, y, weight, 0.0];
+        }
+
+        uniform
+    }
+}
+
+impl Drawable for PotentialField {
+    fn draw(&self, mut ctx: RenderContext<'_>) {
+        let buffer = ctx.renderer.device.create_buffer_init(&BufferInitDescriptor {
+            label: Some("Potential bind group buffer"),
+            contents: cast_slice(&[self.uniform(&ctx.renderer.camera())]),
+            usage: BufferUsages::UNIFORM,
+        });
+
+        let bind_group = ctx.renderer.device.create_bind_group(&BindGroupDescriptor {
+            label: Some("Potential bind group"),
+            layout: &ctx.renderer.potential_bind_group_layout,
+            entries: &[
+                BindGroupEntry {
+                    binding: 0,
+                    resource: BindingResource::Buffer(BufferBinding {
+                        buffer: &buffer,
+                        offset: 0,
+                        size: None,
+                    }),
                 },
-                depth_stencil: None,
-                multiview: None,
-            }
-        );
+            ],
+        });
 
         let mut render_pass = load_render_pass_from_render_context!(ctx);
 
-        render_pass.set_pipeline(&circle_pipeline);
-        render_pass.set_vertex_buffer(0, vertices.slice(..));
-        render_pass.set_index_buffer(indices.slice(..), IndexFormat::Uint16);
-        render_pass.set_bind_group(1, &circle_bind_group, &[]);
+        render_pass.set_pipeline(&ctx.renderer.potential_pipeline);
+        render_pass.set_vertex_buffer(0, ctx.renderer.quad_vertex_buffer.slice(..));
+        render_pass.set_index_buffer(ctx.renderer.quad_index_buffer.slice(..), IndexFormat::Uint16);
+        render_pass.set_bind_group(1, &bind_group, &[]);
         render_pass.draw_indexed(0..6, 0, 0..1);
     }
 }
+
+/// 批量绘制多个圆形
+///
+/// 所有圆形上传到同一个实例缓冲区中，只需一次绘制调用
+#[derive(Debug, Default, Clone)]
+pub struct Instances {
+    pub circles: Vec<Circle>,
+}
+
+impl Drawable for Instances {
+    fn draw(&self, mut ctx: RenderContext<'_>) {
+        if self.circles.is_empty() {
+            return;
+        }
+
+        let instances = ctx.renderer.device.create_buffer_init(&BufferInitDescriptor {
+            label: Some("Circle instance buffer"),
+            contents: cast_slice(self.circles.as_slice()),
+            usage: BufferUsages::VERTEX,
+        });
+
+        let mut render_pass = load_render_pass_from_render_context!(ctx);
+
+        render_pass.set_pipeline(&ctx.renderer.circle_instanced_pipeline);
+        render_pass.set_vertex_buffer(0, ctx.renderer.quad_vertex_buffer.slice(..));
+        render_pass.set_vertex_buffer(1, instances.slice(..));
+        render_pass.set_index_buffer(ctx.renderer.quad_index_buffer.slice(..), IndexFormat::Uint16);
+        render_pass.draw_indexed(0..6, 0, 0..self.circles.len() as u32);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// 创建用于测试坐标变换的无窗口渲染器，没有可用的适配器时返回`None`
+    /// 窗口半宽对应3.8e8 m、未缩放、位于原点的相机
+    fn camera(size: (u32, u32))-> Camera {
+        Camera {
+            scale_base: "3.8e8".parse().unwrap(),
+            scale: BigFloat::from(1),
+            size,
+            coord: [0.0; 3],
+        }
+    }
+
+    /// 需要GPU的测试默认忽略，以`cargo test -- --ignored`运行，没有可用的适配器时失败
+    fn gpu_renderer(size: (u32, u32))-> Renderer {
+        Renderer::new_headless(size, 1).block_on().expect("No GPU adapter available for an ignored GPU test")
+    }
+
+    #[test]
+    fn grid_spacing_follows_zoom() {
+        assert_eq!(Grid::spacing(1.0, 3.8e8), 1e8);
+        assert_eq!(Grid::spacing(0.3, 3.8e8), 1e9);
+        assert_eq!(Grid::spacing(4.0, 1.0), 0.01);
+        assert_eq!(Grid::spacing(2.0, 1.0), 0.05);
+    }
+
+    #[test]
+    fn grid_positions_are_bounded() {
+        let positions: Vec<f64> = Grid::positions(-1.0, 1.0, 0.5).collect();
+        assert_eq!(positions, vec![-1.0, -0.5, 0.0, 0.5, 1.0]);
+
+        // 间距低于浮点精度时仍然能够结束
+        let count = Grid::positions(1e12, 1e12 + 1.0, 1e-6).count();
+        assert_eq!(count as i64, Grid::MAX_LINES + 1);
+        assert!(Grid::positions(1e30, 1e30 + 1e15, 1e-3).count() as i64 <= Grid::MAX_LINES + 1);
+    }
+
+    /// 所有后端中渲染管线占用过的存储槽数，管线被释放后槽位保留为空闲，因此只增不减
+    #[cfg(target_os = "linux")]
+    fn render_pipeline_slots(instance: &Instance)-> usize {
+        let report = instance.generate_report();
+        [report.vulkan, report.gl]
+            .into_iter()
+            .flatten()
+            .map(|hub| hub.render_pipelines.num_occupied + hub.render_pipelines.num_vacant)
+            .sum()
+    }
+
+    #[cfg(target_os = "linux")]
+    #[test]
+    #[ignore = "requires a GPU adapter"]
+    fn drawing_circles_does_not_create_pipelines() {
+        let instance = Instance::new(InstanceDescriptor {
+            backends: Backends::all(),
+            ..Default::default()
+        });
+        let renderer = Renderer::new_headless_on(&instance, (64, 64), 1)
+            .block_on()
+            .expect("No GPU adapter available for an ignored GPU test");
+
+        let texture = renderer.device.create_texture(&TextureDescriptor {
+            label: None,
+            size: Extent3d { width: 64, height: 64, depth_or_array_layers: 1 },
+            mip_level_count: 1,
+            sample_count: 1,
+            dimension: TextureDimension::D2,
+            format: renderer.config.format,
+            usage: TextureUsages::RENDER_ATTACHMENT,
+            view_formats: &[],
+        });
+        let view = texture.create_view(&TextureViewDescriptor::default());
+
+        let before = render_pipeline_slots(&instance);
+        for i in 0..1000 {
+            let circle = Circle::new([i as f32 / 1000.0, 0.0, 0.0], 0.01, [1.0; 4]);
+            circle.draw(renderer.render_context(&view));
+        }
+        renderer.device.poll(Maintain::Wait);
+
+        assert_eq!(render_pipeline_slots(&instance), before);
+    }
+
+    #[test]
+    fn instance_layout_matches_circle_bytes() {
+        let circles = vec![
+            Circle::new([1.0, 2.0, 3.0], 4.0, [0.1, 0.2, 0.3, 0.4]).with_outline([0.5, 0.6, 0.7, 0.8], 0.9),
+            Circle::new([-1.0, -2.0, -3.0], 5.0, [1.0; 4]),
+        ];
+        let floats: &[f32] = cast_slice(circles.as_slice());
+        let stride = Circle::INSTANCE_LAYOUT.array_stride as usize / size_of::<f32>();
+        assert_eq!(floats.len(), stride * circles.len());
+
+        let read = |instance: usize, location: u32| {
+            let attribute = Circle::INSTANCE_LAYOUT.attributes
+                .iter()
+                .find(|a| a.shader_location == location)
+                .unwrap();
+            let start = instance * stride + attribute.offset as usize / size_of::<f32>();
+            let len = attribute.format.size() as usize / size_of::<f32>();
+            floats[start..start + len].to_vec()
+        };
+
+        for (i, circle) in circles.iter().enumerate() {
+            assert_eq!(read(i, 1), circle.center);
+            assert_eq!(read(i, 2), [circle.radius]);
+            assert_eq!(read(i, 3), circle.fill_color);
+            assert_eq!(read(i, 4), circle.outline_color);
+            assert_eq!(read(i, 5), [circle.outline_width]);
+        }
+    }
+
+    #[test]
+    fn screen_and_point_round_trip() {
+        let mut camera = camera((800, 600));
+        camera.scale = BigFloat::from(2.5);
+        camera.center_on(Point { x: BigFloat::from(1.0e8), y: BigFloat::from(-3.0e7), z: BigFloat::from(0) });
+
+        for (x, y) in [(0.0, 0.0), (400.0, 300.0), (123.0, 456.0), (800.0, 600.0)] {
+            let point = camera.point_from_screen(PhysicalPosition::new(x, y));
+            let (sx, sy) = camera.screen_from_point(point);
+            assert!((sx as f64 - x).abs() < 0.01 && (sy as f64 - y).abs() < 0.01, "({}, {}) became ({}, {})", x, y, sx, sy);
+        }
+    }
+
+    #[test]
+    fn fit_bounds_centers_and_fits_region() {
+        let mut camera = camera((800, 400));
+        let min = Point { x: BigFloat::from(-1.0e9), y: BigFloat::from(2.0e8), z: BigFloat::from(0) };
+        let max = Point { x: BigFloat::from(3.0e9), y: BigFloat::from(6.0e8), z: BigFloat::from(0) };
+        camera.fit_bounds(min, max);
+
+        let center = Point { x: BigFloat::from(1.0e9), y: BigFloat::from(4.0e8), z: BigFloat::from(0) };
+        let [cx, cy] = camera.ndc_from_point(center);
+        assert!(cx.abs() < 1e-4 && cy.abs() < 1e-4);
+
+        // 区域较宽，右边缘位于0.9^2处，上边缘在视野之内
+        let [right, _] = camera.ndc_from_point(Point { y: center.y, ..max });
+        let [_, top] = camera.ndc_from_point(Point { x: center.x, ..max });
+        assert!((right - 0.81).abs() < 1e-4, "right edge at {}", right);
+        assert!(top > 0.0 && top < 1.0, "top edge at {}", top);
+
+        // 区域退化为一点时只移动相机
+        let scale = camera.scale;
+        camera.fit_bounds(center, center);
+        assert_eq!(camera.scale, scale);
+    }
+
+    #[test]
+    #[ignore = "requires a GPU adapter"]
+    fn resize_recreates_depth_texture_at_new_size() {
+        let mut renderer = gpu_renderer((320, 240));
+        renderer.resize((640, 360));
+
+        assert_eq!(renderer.size, (640, 360));
+        assert_eq!((renderer.depth_texture.width(), renderer.depth_texture.height()), (640, 360));
+        assert_eq!(renderer.basic_bind_group_data.aspect_ratio, 640.0 / 360.0);
+
+        // 大小为0时忽略
+        renderer.resize((0, 100));
+        assert_eq!(renderer.depth_texture.width(), 640);
+    }
+
+    #[test]
+    fn arrow_scale_normalizes_longest_vector() {
+        let vectors = [
+            Vector::from_f64_array([3.0, 4.0, 0.0]),
+            Vector::from_f64_array([1.0, 0.0, 0.0]),
+        ];
+        assert_eq!(Arrow::normalized_scale(&vectors, BigFloat::from(10)), Some(BigFloat::from(2)));
+        assert_eq!(Arrow::normalized_scale(&[Vector::ZERO, Vector::ZERO], BigFloat::from(10)), None);
+        assert_eq!(Arrow::normalized_scale(&[], BigFloat::from(10)), None);
+    }
+
+    struct Ball {
+        radius: BigFloat,
+        shape: Shape,
+    }
+
+    impl Renderable for Ball {
+        fn center(&self)-> Point {
+            Point { x: BigFloat::from(0), y: BigFloat::from(0), z: BigFloat::from(0) }
+        }
+
+        fn radius(&self)-> BigFloat {
+            self.radius
+        }
+
+        fn color(&self)-> [f32; 4] {
+            [0.1, 0.1, 0.95, 1.0]
+        }
+
+        fn shape(&self)-> Shape {
+            self.shape
+        }
+    }
+
+    #[test]
+    fn renderable_becomes_circle_with_scaled_radius() {
+        let mut camera = camera((64, 64));
+        camera.scale = BigFloat::from(2);
+
+        let ball = Ball { radius: camera.scale_base / BigFloat::from(4), shape: Shape::Circle };
+        let Primitive::Circle(circle) = primitive(&ball, &camera) else {
+            panic!("a circle should become a circle primitive");
+        };
+        assert!((circle.radius - 0.5).abs() < 1e-6, "radius {}", circle.radius);
+        assert_eq!(circle.fill_color, ball.color());
+        assert_eq!(circle.outline_width, 0.0);
+    }
+
+    #[test]
+    fn circle_mesh_counts_and_rim_radius() {
+        let circle = Circle::new([1.0, -2.0, 0.5], 3.0, [1.0; 4]);
+
+        for segments in [3, 16, 64] {
+            let (vertices, indices) = circle.as_mesh(segments);
+            assert_eq!(vertices.len(), segments as usize + 1);
+            assert_eq!(indices.len(), segments as usize * 3);
+            assert_eq!(vertices[0].position, circle.center);
+
+            for v in &vertices[1..] {
+                let r = (v.position[0] - 1.0).hypot(v.position[1] + 2.0);
+                assert!((r - 3.0).abs() < 1e-5, "rim vertex at radius {}", r);
+            }
+        }
+
+        // 少于3段时按3段处理
+        assert_eq!(circle.as_mesh(1).0.len(), 4);
+    }
+
+    #[test]
+    fn circle_uniform_matches_wgsl_layout() {
+        let uniform = Circle::new([1.0, 2.0, 3.0], 4.0, [1.0; 4])
+            .with_outline([0.5, 0.6, 0.7, 0.8], 0.9)
+            .uniform();
+        let floats: &[f32] = cast_slice(std::slice::from_ref(&uniform));
+
+        // CircleData: center(0..3), radius(3), outline_color(4..8), outline_width(8)，总大小按16字节对齐
+        assert_eq!(size_of::<CircleUniform>(), 48);
+        assert_eq!(&floats[0..3], &[1.0, 2.0, 3.0]);
+        assert_eq!(floats[3], 4.0);
+        assert_eq!(&floats[4..8], &[0.5, 0.6, 0.7, 0.8]);
+        assert_eq!(floats[8], 0.9);
+    }
+
+    #[test]
+    #[ignore = "requires a GPU adapter"]
+    fn invalid_shader_is_rejected_on_reload() {
+        let mut renderer = gpu_renderer((32, 32));
+        let circle = Circle::new([0.0, 0.0, 0.0], 0.5, [1.0; 4]);
+        let before = renderer.capture_frame(&[&circle]).unwrap();
+
+        let dir = std::env::temp_dir().join(format!("shaders-{}", uuid::Uuid::new_v4()));
+        std::fs::create_dir(&dir).unwrap();
+        for name in ["generic.wgsl", "circle.wgsl", "circle_instanced.wgsl", "hud.wgsl", "potential.wgsl"] {
+            std::fs::copy(Path::new(SHADER_DIR).join(name), dir.join(name)).unwrap();
+        }
+        renderer.reload_shaders_from(&dir).unwrap();
+
+        std::fs::write(dir.join("circle.wgsl"), "@fragment fn circle_fs() -> @location(0) vec4<f32> { return 1.0; }").unwrap();
+        let result = renderer.reload_shaders_from(&dir);
+        std::fs::remove_dir_all(&dir).unwrap();
+        assert!(result.is_err());
+
+        // 出错后继续使用原有的渲染管线
+        assert_eq!(renderer.capture_frame(&[&circle]).unwrap().rgba, before.rgba);
+    }
+
+    #[test]
+    fn shape_variants_generate_expected_geometry() {
+        let camera = camera((64, 64));
+        let radius = camera.scale_base / BigFloat::from(2);
+        let convert = |shape: Shape| primitive(&Ball { radius, shape }, &camera);
+        let extent = |primitive: Primitive| {
+            let vertices = match primitive {
+                Primitive::Circle(circle) => circle.quad(),
+                Primitive::Rectangle(rectangle) => rectangle.vertices,
+            };
+            vertices.map(|v| [v.position[0], v.position[1]])
+        };
+        let corners = [[-0.5, 0.5], [-0.5, -0.5], [0.5, -0.5], [0.5, 0.5]];
+
+        // 每种形状都以四个顶点、`Rectangle::INDICES`中的两个三角形绘制
+        assert_eq!(Rectangle::INDICES.len(), 6);
+
+        let circle = convert(Shape::Circle);
+        assert!(matches!(circle, Primitive::Circle(c) if c.outline_width == 0.0 && c.fill_color[3] == 1.0));
+        assert_eq!(extent(circle), corners);
+
+        let square = convert(Shape::Square);
+        assert!(matches!(square, Primitive::Rectangle(_)));
+        assert_eq!(extent(square), [[-0.5, -0.5], [0.5, -0.5], [0.5, 0.5], [-0.5, 0.5]]);
+
+        // 圆环以外半径绘制，轮廓宽度为内外半径之差，填充透明
+        let ring = convert(Shape::Ring { inner: radius / BigFloat::from(2), outer: radius });
+        assert!(matches!(ring, Primitive::Circle(c) if c.outline_width == 0.25 && c.fill_color[3] == 0.0));
+        assert_eq!(extent(ring), corners);
+    }
+
+    #[test]
+    #[ignore = "requires a GPU adapter"]
+    fn shape_variants_cover_expected_pixels() {
+        let renderer = gpu_renderer((64, 64));
+        // 半径为半宽的一半，即16像素
+        let radius = renderer.scale_base / BigFloat::from(2);
+        let pixel = |shot: &Screenshot, x: usize, y: usize| shot.rgba[(y * 64 + x) * 4..][..4].to_vec();
+        let capture = |shape: Shape| {
+            let ball = Ball { radius, shape };
+            renderer.capture_frame(&[&ball]).unwrap()
+        };
+
+        let circle = capture(Shape::Circle);
+        let background = pixel(&circle, 0, 0);
+        assert_ne!(pixel(&circle, 32, 32), background);
+        assert_eq!(pixel(&circle, 45, 45), background);
+
+        // 正方形覆盖圆形之外的角落
+        let square = capture(Shape::Square);
+        assert_ne!(pixel(&square, 32, 32), background);
+        assert_ne!(pixel(&square, 45, 45), background);
+        assert_eq!(pixel(&square, 50, 32), background);
+
+        // 圆环中间是空的
+        let ring = capture(Shape::Ring { inner: radius / BigFloat::from(2), outer: radius });
+        assert_eq!(pixel(&ring, 32, 32), background);
+        assert_ne!(pixel(&ring, 32 + 12, 32), background);
+        assert_eq!(pixel(&ring, 50, 32), background);
+    }
+
+    #[test]
+    fn potential_uniform_keeps_heaviest_bodies() {
+        let camera = camera((32, 32));
+        let origin = Point { x: BigFloat::from(0), y: BigFloat::from(0), z: BigFloat::from(0) };
+
+        let pair = PotentialField::new([(origin, BigFloat::from(3)), (origin, BigFloat::from(1))]);
+        let uniform = pair.uniform(&camera);
+        assert_eq!(uniform.count, 2);
+        assert_eq!(uniform.bodies[0], [0.0, 0.0, 0.75, 0.0]);
+        assert_eq!(uniform.bodies[1], [0.0, 0.0, 0.25, 0.0]);
+        assert_eq!(uniform.bodies[2], [0.0; 4]);
+
+        // 位置为标准化设备坐标
+        let right = Point { x: camera.scale_base / BigFloat::from(2), ..origin };
+        let uniform = PotentialField::new([(right, BigFloat::from(1))]).uniform(&camera);
+        assert_eq!(uniform.bodies[0], [0.5, 0.0, 1.0, 0.0]);
+
+        // 超出上限时只保留质量最大的天体
+        let crowd = PotentialField::new((1..=MAX_POTENTIAL_BODIES + 10).map(|m| (origin, BigFloat::from(m as f64))));
+        let uniform = crowd.uniform(&camera);
+        assert_eq!(uniform.count as usize, MAX_POTENTIAL_BODIES);
+        let lightest = uniform.bodies.iter().map(|b| b[2]).fold(f32::INFINITY, f32::min);
+        let total: usize = (1..=MAX_POTENTIAL_BODIES + 10).sum();
+        assert!((lightest - 11.0 / total as f32).abs() < 1e-6, "lightest weight {}", lightest);
+
+        let massless = PotentialField::new([(origin, BigFloat::from(0)), (origin, BigFloat::from(0))]);
+        let uniform = massless.uniform(&camera);
+        assert_eq!(uniform.count, 2);
+        assert!(uniform.bodies.iter().all(|b| b[2] == 0.0));
+    }
+
+    #[test]
+    fn colormap_endpoints_and_midpoint() {
+        let close = |a: [f32; 4], b: [f32; 4]| a.iter().zip(b).all(|(x, y)| (x - y).abs() < 1e-6);
+        let low = [0.2, 0.35, 1.0, 1.0];
+        let mid = [0.6, 0.3, 0.6, 1.0];
+        let high = [1.0, 0.25, 0.2, 1.0];
+
+        assert!(close(colormap(10.0, 10.0, 20.0), low));
+        assert!(close(colormap(15.0, 10.0, 20.0), mid));
+        assert!(close(colormap(20.0, 10.0, 20.0), high));
+
+        // 超出范围时取端点的颜色
+        assert!(close(colormap(-5.0, 10.0, 20.0), low));
+        assert!(close(colormap(50.0, 10.0, 20.0), high));
+
+        // 范围为空时取中间的颜色
+        assert!(close(colormap(3.0, 1.0, 1.0), mid));
+        assert!(close(colormap(3.0, 2.0, 1.0), mid));
+    }
+
+    #[test]
+    fn label_anchor_sits_right_of_body() {
+        let mut camera = camera((100, 100));
+        let base = camera.scale_base;
+        let at = |x: f64, y: f64| Point { x: base * BigFloat::from(x), y: base * BigFloat::from(y), z: BigFloat::from(0) };
+        let radius = base * BigFloat::from(0.1);
+        let close = |(x, y): (f32, f32), (ex, ey): (f32, f32)| (x - ex).abs() < 1e-3 && (y - ey).abs() < 1e-3;
+
+        // 半径为5像素，标签再向右4像素
+        assert!(close(camera.label_anchor(at(0.0, 0.0), radius), (59.0, 50.0)));
+        assert!(close(camera.label_anchor(at(0.5, 0.5), radius), (84.0, 25.0)));
+
+        // 与着色器中的绘制结果一致，scale为2时半径为20像素
+        camera.scale = BigFloat::from(2);
+        assert!(close(camera.label_anchor(at(0.0, 0.0), radius), (74.0, 50.0)));
+
+        // 相机移动时锚点随天体移动
+        camera.scale = BigFloat::from(1);
+        camera.center_on(at(0.5, 0.0));
+        assert!(close(camera.label_anchor(at(0.5, 0.0), radius), (59.0, 50.0)));
+    }
+}
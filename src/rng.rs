@@ -0,0 +1,40 @@
+/// 使用xorshift64*算法的伪随机数生成器
+///
+/// 相同的种子总是产生相同的序列，用于生成可复现的星空与初始条件
+#[derive(Clone, Debug)]
+pub struct Xorshift64 {
+    state: u64,
+}
+
+
+
+impl Xorshift64 {
+    /// 种子为0时无法产生非0值，替换为一个固定的非0值
+    pub fn new(seed: u64)-> Self {
+        Self {
+            state: if seed == 0 { 0x9E37_79B9_7F4A_7C15 } else { seed },
+        }
+    }
+
+    pub fn next_u64(&mut self)-> u64 {
+        self.state ^= self.state >> 12;
+        self.state ^= self.state << 25;
+        self.state ^= self.state >> 27;
+        self.state.wrapping_mul(0x2545_F491_4F6C_DD1D)
+    }
+
+    /// 取高24位，映射到[0, 1)
+    pub fn next_f32(&mut self)-> f32 {
+        (self.next_u64() >> 40) as f32 / (1u64 << 24) as f32
+    }
+
+    /// 取高53位，映射到[0, 1)
+    pub fn next_f64(&mut self)-> f64 {
+        (self.next_u64() >> 11) as f64 / (1u64 << 53) as f64
+    }
+
+    /// 在[`min`, `max`)中均匀取值
+    pub fn range_f64(&mut self, min: f64, max: f64)-> f64 {
+        min + self.next_f64() * (max - min)
+    }
+}
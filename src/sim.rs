@@ -0,0 +1,198 @@
+use crate::physics::{ BodySnapshot, TimeStep };
+use crate::world::World;
+use anyhow::{ Result, anyhow };
+use std::time::Duration;
+use std::path::{ Path, PathBuf };
+use std::io::{ BufWriter, Write };
+use std::fs::File;
+
+
+
+/// 无窗口模式的参数，由命令行`--headless --steps N --dt MS`指定
+///
+/// 可以通过`--csv PATH`与`--json PATH`将轨迹数据导出到文件，通过`--record DIR`将每一步的画面保存到目录
+#[derive(Debug, Clone, PartialEq)]
+pub struct HeadlessOptions {
+    /// 执行的步数
+    pub steps: usize,
+
+    /// 每一步的时长，为负时时间倒流
+    pub dt: TimeStep,
+
+    /// 导出CSV格式轨迹数据的文件
+    pub csv: Option<PathBuf>,
+
+    /// 导出JSON格式轨迹数据的文件
+    pub json: Option<PathBuf>,
+
+    /// 保存每一步画面的目录
+    pub record: Option<PathBuf>,
+}
+
+
+
+impl HeadlessOptions {
+    /// 默认每一步的时长与窗口模式相同
+    pub const DEFAULT_DT: TimeStep = TimeStep {
+        duration: Duration::from_millis(30),
+        backward: false,
+    };
+
+    /// 从命令行参数(不含程序名)解析
+    ///
+    /// 没有`--headless`时返回`None`，此时应以窗口模式运行
+    pub fn from_args<I>(args: I)-> Result<Option<Self>>
+        where I: IntoIterator<Item = String>
+    {
+        let mut headless = false;
+        let mut steps = None;
+        let mut dt = Self::DEFAULT_DT;
+        let mut csv = None;
+        let mut json = None;
+        let mut record = None;
+
+        let mut args = args.into_iter();
+        while let Some(arg) = args.next() {
+            match arg.as_str() {
+                "--headless" => headless = true,
+
+                "--steps" => {
+                    let value = args.next().ok_or_else(|| anyhow!("Missing value for --steps"))?;
+                    steps = Some(value.parse()?);
+                },
+
+                "--dt" => {
+                    let value = args.next().ok_or_else(|| anyhow!("Missing value for --dt"))?;
+                    let millis: f64 = value.parse()?;
+                    if !millis.is_finite() {
+                        return Err(anyhow!("Invalid value for --dt: {}", value));
+                    }
+                    dt = TimeStep::from_millis_f64(millis);
+                },
+
+                "--csv" => {
+                    csv = Some(args.next().ok_or_else(|| anyhow!("Missing value for --csv"))?.into());
+                },
+
+                "--json" => {
+                    json = Some(args.next().ok_or_else(|| anyhow!("Missing value for --json"))?.into());
+                },
+
+                "--record" => {
+                    record = Some(args.next().ok_or_else(|| anyhow!("Missing value for --record"))?.into());
+                },
+
+                _ => return Err(anyhow!("Unknown argument: {}", arg)),
+            }
+        }
+
+        if !headless {
+            return Ok(None);
+        }
+
+        Ok(Some(Self {
+            steps: steps.ok_or_else(|| anyhow!("--headless requires --steps"))?,
+            dt,
+            csv,
+            json,
+            record,
+        }))
+    }
+}
+
+/// 不打开窗口、不使用GPU，直接执行`steps`步物理计算
+///
+/// 返回每一步执行后所有物体状态的快照，共`steps`帧
+pub fn run_headless(world: &mut dyn World, steps: usize, dt: TimeStep)-> Vec<Vec<BodySnapshot>> {
+    run_headless_with(world, steps, dt, |_| Ok(())).unwrap()
+}
+
+/// 与`run_headless`相同，但每一步执行后以执行后的世界调用`on_step`
+///
+/// `on_step`返回错误时立即停止并返回该错误
+pub fn run_headless_with<F>(world: &mut dyn World, steps: usize, dt: TimeStep, mut on_step: F)-> Result<Vec<Vec<BodySnapshot>>>
+    where F: FnMut(&dyn World)-> Result<()>
+{
+    let mut frames = Vec::with_capacity(steps);
+
+    for _ in 0..steps {
+        world.execute(dt);
+        frames.push(world.snapshot());
+        on_step(&*world)?;
+    }
+
+    Ok(frames)
+}
+
+/// 将轨迹数据以CSV格式导出到文件
+///
+/// 每一步的每个物体占一行，列依次为`step, uid, x, y, z, vx, vy, vz`，`step`从0开始。
+/// 数值使用`BigFloat`的字符串形式，保留全部精度
+pub fn export_csv(path: &Path, frames: &[Vec<BodySnapshot>])-> Result<()> {
+    let mut writer = BufWriter::new(File::create(path)?);
+    writeln!(writer, "step,uid,x,y,z,vx,vy,vz")?;
+
+    for (step, frame) in frames.iter().enumerate() {
+        for body in frame {
+            let c = &body.attributes.center;
+            let v = &body.attributes.velocity;
+            writeln!(writer, "{},{},{},{},{},{},{},{}", step, body.uid, c.x, c.y, c.z, v.x, v.y, v.z)?;
+        }
+    }
+
+    writer.flush()?;
+    Ok(())
+}
+
+/// 将轨迹数据以JSON格式导出到文件
+///
+/// 顶层为数组，每个元素为一步执行后所有物体的快照
+#[cfg(feature = "serde")]
+pub fn export_json(path: &Path, frames: &[Vec<BodySnapshot>])-> Result<()> {
+    let writer = BufWriter::new(File::create(path)?);
+    serde_json::to_writer(writer, frames)?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::world::EarthMoonWorld;
+    use num_bigfloat::BigFloat;
+
+    #[test]
+    fn headless_two_body_run_records_every_step() {
+        let mut world = EarthMoonWorld::default();
+        let frames = run_headless(&mut world, 5, HeadlessOptions::DEFAULT_DT);
+
+        assert_eq!(frames.len(), 5);
+        assert!(frames.iter().all(|frame| frame.len() == 2));
+        assert_ne!(frames[0][1].attributes.center, frames[4][1].attributes.center);
+    }
+
+    #[test]
+    fn exported_csv_parses_back() {
+        let mut world = EarthMoonWorld::default();
+        let frames = run_headless(&mut world, 3, HeadlessOptions::DEFAULT_DT);
+        let path = std::env::temp_dir().join(format!("trajectory-{}.csv", uuid::Uuid::new_v4()));
+
+        export_csv(&path, &frames).unwrap();
+        let csv = std::fs::read_to_string(&path);
+        std::fs::remove_file(&path).unwrap();
+        let csv = csv.unwrap();
+
+        let mut lines = csv.lines();
+        assert_eq!(lines.next(), Some("step,uid,x,y,z,vx,vy,vz"));
+
+        let rows: Vec<Vec<&str>> = lines.map(|l| l.split(',').collect()).collect();
+        assert_eq!(rows.len(), 3 * 2);
+        for row in rows {
+            let step: usize = row[0].parse().unwrap();
+            let body = frames[step].iter().find(|b| b.uid.to_string() == row[1]).unwrap();
+            let x: BigFloat = row[2].parse().unwrap();
+            let vy: BigFloat = row[6].parse().unwrap();
+            assert_eq!(x, body.attributes.center.x);
+            assert_eq!(vy, body.attributes.velocity.y);
+        }
+    }
+}
@@ -0,0 +1,1499 @@
+use crate::physics::*;
+use crate::render::*;
+use crate::rng::Xorshift64;
+use uuid::Uuid;
+use num_bigfloat::{ BigFloat, ZERO, ONE };
+use std::time::{ Duration, Instant };
+use std::collections::HashMap;
+#[cfg(feature = "serde")]
+use anyhow::Result;
+#[cfg(feature = "serde")]
+use std::path::Path;
+#[cfg(feature = "serde")]
+use serde::Deserialize;
+
+
+
+pub trait World {
+    /// 返回可绘制的所有物体
+    fn get_drawable_items<'items, 'this: 'items>(&'this self)-> Vec<&'items dyn Drawable>;
+
+    /// 执行物理计算
+    fn execute(&mut self, time: TimeStep);
+
+    /// 连续执行`count`步物理计算
+    ///
+    /// 默认逐步调用`execute`，世界可以覆盖此方法以在各步之间复用`Objects`
+    fn execute_many(&mut self, time: TimeStep, count: usize) {
+        for _ in 0..count {
+            self.execute(time);
+        }
+    }
+
+    /// 获取自世界创建以来经过的模拟时间
+    ///
+    /// 时间倒流时相应减少，不会小于0
+    fn elapsed(&self)-> Duration;
+
+    /// 获取计算引力使用的执行器
+    fn executor(&self)-> &SpaceExecutor;
+
+    /// 从当前状态出发预测之后`steps`步中每个天体的位置，不修改世界
+    fn predict(&self, steps: usize, dt: TimeStep)-> HashMap<Uuid, Vec<Point>> {
+        let mut state = self.snapshot();
+        let objects = Objects::new(
+            state.iter_mut().map(|s| s as &mut dyn PhysicalObject).collect()
+        );
+        self.executor().predict(&objects, steps, dt)
+    }
+
+    /// 获取默认显示比例的底
+    fn get_default_scale_base(&self)-> BigFloat {
+        "4.0e8".parse().unwrap()
+    }
+
+    /// 获取世界中的所有天体
+    fn bodies(&self)-> &[Body];
+
+    /// 获取世界中所有天体的可变引用
+    ///
+    /// 可用于在运行时添加或移除天体，下一次`execute`时生效
+    fn bodies_mut(&mut self)-> &mut Vec<Body>;
+
+    /// 对UID为`uid`的天体施加冲量，天体不存在时返回`false`
+    fn apply_impulse(&mut self, uid: Uuid, impulse: Vector)-> bool {
+        match self.bodies_mut().iter_mut().find(|b| b.uid == uid) {
+            Some(body) => {
+                body.phyattr.apply_impulse(impulse);
+                true
+            },
+            None => false,
+        }
+    }
+
+    /// 移除UID为`uid`的天体，天体不存在时返回`false`
+    ///
+    /// 执行器在每一步重新收集天体，因此下一次`execute`起不再计入被移除的天体
+    fn remove(&mut self, uid: Uuid)-> bool {
+        let bodies = self.bodies_mut();
+        let len = bodies.len();
+        bodies.retain(|b| b.uid != uid);
+        bodies.len() != len
+    }
+
+    /// 获取包围所有天体重心的最小长方体，返回其两个对角`(min, max)`
+    ///
+    /// 世界中没有天体时返回`None`
+    fn bounds(&self)-> Option<(Point, Point)> {
+        bounding_box(self.bodies().iter().map(|b| b.phyattr.center))
+    }
+
+    /// 复制绘制当前状态所需的数据
+    fn draw_frame(&self)-> DrawFrame {
+        DrawFrame {
+            bodies: self.bodies()
+                .iter()
+                .map(|b| DrawState {
+                    uid: b.uid,
+                    center: b.phyattr.center,
+                    velocity: b.phyattr.velocity,
+                    force: b.phyattr.force,
+                    mass: b.phyattr.mass,
+                    radius: b.radius,
+                    color: b.color,
+                    outline: b.outline,
+                    shape: b.shape,
+                    label: b.label.clone(),
+                })
+                .collect(),
+            elapsed: self.elapsed(),
+        }
+    }
+
+    /// 获取世界中所有物体当前状态的快照
+    fn snapshot(&self)-> Vec<BodySnapshot> {
+        self.bodies()
+            .iter()
+            .map(|b| BodySnapshot { uid: b.uid, attributes: b.phyattr.clone() })
+            .collect()
+    }
+
+    /// 从快照恢复所有物体的状态
+    ///
+    /// 快照中物体的顺序应与`snapshot`返回的顺序一致
+    fn restore(&mut self, snapshot: Vec<BodySnapshot>) {
+        let bodies = self.bodies_mut();
+        if snapshot.len() != bodies.len() {
+            log::warn!("Snapshot has {} bodies but the world has {}", snapshot.len(), bodies.len());
+        }
+
+        for (body, s) in bodies.iter_mut().zip(snapshot) {
+            body.uid = s.uid;
+            body.phyattr = s.attributes;
+        }
+    }
+
+    /// 将世界的快照以JSON格式保存到文件
+    #[cfg(feature = "serde")]
+    fn save_to(&self, path: &Path)-> Result<()> {
+        let json = serde_json::to_string_pretty(&self.snapshot())?;
+        std::fs::write(path, json)?;
+        Ok(())
+    }
+
+    /// 从JSON格式的快照文件恢复世界
+    #[cfg(feature = "serde")]
+    fn load_from(&mut self, path: &Path)-> Result<()> {
+        let json = std::fs::read_to_string(path)?;
+        self.restore(serde_json::from_str(&json)?);
+        Ok(())
+    }
+}
+
+/// 使装箱的世界(如`Box<dyn World + Send>`)也可以作为世界使用，以便在运行时选择世界
+impl<W: World + ?Sized> World for Box<W> {
+    fn get_drawable_items<'items, 'this: 'items>(&'this self)-> Vec<&'items dyn Drawable> {
+        (**self).get_drawable_items()
+    }
+
+    fn execute(&mut self, time: TimeStep) {
+        (**self).execute(time)
+    }
+
+    fn execute_many(&mut self, time: TimeStep, count: usize) {
+        (**self).execute_many(time, count)
+    }
+
+    fn elapsed(&self)-> Duration {
+        (**self).elapsed()
+    }
+
+    fn executor(&self)-> &SpaceExecutor {
+        (**self).executor()
+    }
+
+    fn predict(&self, steps: usize, dt: TimeStep)-> HashMap<Uuid, Vec<Point>> {
+        (**self).predict(steps, dt)
+    }
+
+    fn get_default_scale_base(&self)-> BigFloat {
+        (**self).get_default_scale_base()
+    }
+
+    fn bodies(&self)-> &[Body] {
+        (**self).bodies()
+    }
+
+    fn bodies_mut(&mut self)-> &mut Vec<Body> {
+        (**self).bodies_mut()
+    }
+
+    fn apply_impulse(&mut self, uid: Uuid, impulse: Vector)-> bool {
+        (**self).apply_impulse(uid, impulse)
+    }
+
+    fn remove(&mut self, uid: Uuid)-> bool {
+        (**self).remove(uid)
+    }
+
+    fn bounds(&self)-> Option<(Point, Point)> {
+        (**self).bounds()
+    }
+
+    fn draw_frame(&self)-> DrawFrame {
+        (**self).draw_frame()
+    }
+
+    fn snapshot(&self)-> Vec<BodySnapshot> {
+        (**self).snapshot()
+    }
+
+    fn restore(&mut self, snapshot: Vec<BodySnapshot>) {
+        (**self).restore(snapshot)
+    }
+
+    #[cfg(feature = "serde")]
+    fn save_to(&self, path: &Path)-> Result<()> {
+        (**self).save_to(path)
+    }
+
+    #[cfg(feature = "serde")]
+    fn load_from(&mut self, path: &Path)-> Result<()> {
+        (**self).load_from(path)
+    }
+}
+
+
+
+/// 一个通用的天体
+///
+/// 以圆形绘制，`radius`为显示时的半径
+pub struct Body {
+    uid: Uuid,
+    phyattr: PhysicalAttributes,
+
+    /// 显示半径，以m为单位，与坐标使用相同的缩放
+    pub radius: BigFloat,
+
+    /// 填充颜色(RGBA)
+    pub color: [f32; 4],
+
+    /// 轮廓的颜色与宽度，宽度以m为单位，为`None`时不绘制轮廓
+    pub outline: Option<([f32; 4], BigFloat)>,
+
+    /// 绘制时的形状，默认为圆
+    pub shape: Shape,
+
+    /// 在天体旁显示的名称，为`None`时不显示
+    pub label: Option<String>,
+}
+
+/// 绘制一个天体所需的状态
+///
+/// 由物理线程在每一步之后从世界中复制，绘制时无需锁住世界
+#[derive(Clone, PartialEq, Debug)]
+pub struct DrawState {
+    pub uid: Uuid,
+    pub center: Point,
+    pub velocity: Vector,
+    pub force: Vector,
+    pub mass: BigFloat,
+
+    /// 显示半径，以m为单位
+    pub radius: BigFloat,
+    pub color: [f32; 4],
+    pub outline: Option<([f32; 4], BigFloat)>,
+    pub shape: Shape,
+    pub label: Option<String>,
+}
+
+/// 绘制一帧所需的世界状态
+#[derive(Clone, PartialEq, Debug, Default)]
+pub struct DrawFrame {
+    pub bodies: Vec<DrawState>,
+
+    /// 世界已经过的模拟时间
+    pub elapsed: Duration,
+}
+
+/// 物理线程最近写入的两帧及其写入时刻
+///
+/// 绘制时在这两帧之间按经过的时间线性插值，使步率低于帧率时天体的运动仍然平滑。
+/// 插值使显示的状态比最新的状态晚至多一步
+#[derive(Clone, Debug)]
+pub struct FrameHistory {
+    pub previous: DrawFrame,
+    pub previous_at: Instant,
+    pub current: DrawFrame,
+    pub current_at: Instant,
+}
+
+/// 从配置文件加载的世界
+///
+/// 配置文件为JSON格式，所有数值以字符串表示以保留精度，例如：
+///
+/// ```json
+/// {
+///     "scale_base": "4.0e8",
+///     "bodies": [
+///         {
+///             "mass": "5.965e24",
+///             "center": ["0", "0", "0"],
+///             "velocity": ["0", "0", "0"],
+///             "radius": "6.371e6",
+///             "color": [0.1, 0.1, 0.95, 1.0]
+///         }
+///     ]
+/// }
+/// ```
+///
+/// `scale_base`、`g`、`softening`与每个物体的`display_radius`、`charge`、`label`可省略
+#[cfg(feature = "serde")]
+pub struct ConfigWorld {
+    executor: SpaceExecutor,
+    bodies: Vec<Body>,
+    scale_base: BigFloat,
+    elapsed: Duration,
+}
+
+/// 配置文件的内容
+#[cfg(feature = "serde")]
+#[derive(Deserialize)]
+struct WorldConfig {
+    scale_base: Option<String>,
+    g: Option<String>,
+    softening: Option<String>,
+    bodies: Vec<BodyConfig>,
+}
+
+/// 配置文件中的一个物体
+#[cfg(feature = "serde")]
+#[derive(Deserialize)]
+struct BodyConfig {
+    mass: String,
+    center: [String; 3],
+    velocity: [String; 3],
+    radius: String,
+    display_radius: Option<String>,
+    charge: Option<String>,
+    color: [f32; 4],
+    label: Option<String>,
+}
+
+/// 太阳与六颗行星组成的太阳系
+///
+/// 行星均以圆轨道初始化，且为便于观察放大了显示半径
+pub struct SolarSystemWorld {
+    executor: SpaceExecutor,
+    bodies: Vec<Body>,
+    elapsed: Duration,
+}
+
+/// 三个等质量天体沿"8"字形轨道相互追逐的三体周期解
+///
+/// 使用Chenciner与Montgomery给出的初始条件，取`G = 1`、质量为1的自然单位制，周期约为6.33s。
+/// 该轨道对误差较敏感，因此使用速度Verlet积分
+pub struct FigureEightWorld {
+    executor: VerletExecutor,
+    bodies: Vec<Body>,
+    elapsed: Duration,
+}
+
+/// 由种子确定的随机N体星团
+///
+/// 取`G = 1`的自然单位制，天体随机分布在半径为1的球内，总质量为1。
+/// 相同的`n`与种子总是生成完全相同的初始条件，便于复现与比较。
+/// 天体数量较多，因此使用`F64Executor`计算
+pub struct RandomClusterWorld {
+    executor: F64Executor,
+    bodies: Vec<Body>,
+    elapsed: Duration,
+}
+
+/// 由开普勒轨道的解析解给出位置的二体世界，用于验证数值积分的结果
+///
+/// `execute`不进行数值积分，只推进时钟，并由初始状态直接计算两天体在当前时刻的精确位置与速度。
+/// `restore`与`apply_impulse`以修改后的状态作为新的初始状态；世界只容纳这两个天体，
+/// `remove`总是失败，运行时添加的天体在下一次`execute`时被移除
+pub struct AnalyticTwoBodyWorld {
+    executor: SpaceExecutor,
+    bodies: Vec<Body>,
+    elapsed: Duration,
+
+    /// 两天体在`epoch`时刻的状态
+    initial: [PhysicalAttributes; 2],
+
+    /// 取得`initial`时的模拟时间
+    epoch: Duration,
+}
+
+/// 地球与月球组成的世界
+///
+/// 前两个天体为地球与月球，运行时可通过`bodies_mut`添加更多天体。
+/// 天体相撞时合并为一个，被合并的天体在该步之后从世界中移除
+pub struct EarthMoonWorld {
+    executor: CollisionExecutor,
+    bodies: Vec<Body>,
+
+    elapsed: Duration,
+}
+
+
+
+impl Body {
+    /// 创建一个天体
+    ///
+    /// `radius`同时作为物理半径(用于碰撞检测)和显示半径，质量不为正数或半径为负数时返回错误
+    pub fn new(mass: BigFloat, center: Point, velocity: Vector, radius: BigFloat, color: [f32; 4])-> Result<Self, PhysicsError> {
+        Ok(Self {
+            uid: Uuid::new_v4(),
+            phyattr: PhysicalAttributes::new(center, velocity, mass, radius)?,
+            radius,
+            color,
+            outline: None,
+            shape: Shape::Circle,
+            label: None,
+        })
+    }
+
+    /// 创建一个绕主星做圆周运动的天体
+    ///
+    /// 天体位于`primary_center + radius_vector`处，速度大小为`sqrt(G * M / r)`，
+    /// 方向在xy平面内与`radius_vector`垂直(逆时针)。主星视为静止，不考虑天体自身的质量。
+    /// 与`new`相同，质量不为正数或半径为负数时返回错误
+    ///
+    /// Panics:
+    /// `radius_vector`在xy平面上的投影为0时会触发panic，因为速度方向不确定
+    pub fn on_circular_orbit(
+        primary_mass: BigFloat,
+        primary_center: Point,
+        radius_vector: Vector,
+        g: BigFloat,
+        mass: BigFloat,
+        radius: BigFloat,
+        color: [f32; 4],
+    )-> Result<Self, PhysicsError> {
+        let speed = orbital_velocity(primary_mass, radius_vector.model(), g);
+
+        let tangent = Vector {
+            x: -radius_vector.y,
+            y: radius_vector.x,
+            z: ZERO,
+        };
+        let velocity = tangent * (speed / tangent.model());
+
+        Self::new(mass, primary_center + radius_vector, velocity, radius, color)
+    }
+}
+
+impl PhysicalObject for Body {
+    fn get_uid(&self)-> Uuid {
+        self.uid
+    }
+
+    fn get_physical_attributes(&self)-> &PhysicalAttributes {
+        &self.phyattr
+    }
+
+    fn get_physical_attributes_mut(&mut self)-> &mut PhysicalAttributes {
+        &mut self.phyattr
+    }
+}
+
+impl Renderable for Body {
+    fn center(&self)-> Point {
+        self.phyattr.center
+    }
+
+    fn radius(&self)-> BigFloat {
+        self.radius
+    }
+
+    fn color(&self)-> [f32; 4] {
+        self.color
+    }
+
+    fn outline(&self)-> Option<([f32; 4], BigFloat)> {
+        self.outline
+    }
+
+    fn shape(&self)-> Shape {
+        self.shape
+    }
+}
+
+impl DrawFrame {
+    /// 在自身与`next`之间线性插值，`t`为0时为自身，为1时为`next`
+    ///
+    /// 只对两帧中都存在的天体插值位置、速度与受力，其余天体与其他状态取`next`中的值
+    pub fn lerp(&self, next: &DrawFrame, t: f64)-> DrawFrame {
+        let t = BigFloat::from(t);
+        let bodies = next.bodies
+            .iter()
+            .map(|b| match self.bodies.iter().find(|p| p.uid == b.uid) {
+                Some(p) => DrawState {
+                    center: p.center + (b.center - p.center) * t,
+                    velocity: p.velocity + (b.velocity - p.velocity) * t,
+                    force: p.force + (b.force - p.force) * t,
+                    ..b.clone()
+                },
+                None => b.clone(),
+            })
+            .collect();
+
+        DrawFrame {
+            bodies,
+            elapsed: next.elapsed,
+        }
+    }
+
+    /// 按质量为所有天体重新着色，质量最小的为蓝色，最大的为红色
+    ///
+    /// 天体的质量常常相差多个数量级，因此按质量的对数在当前所有天体中的范围映射颜色，不改变透明度
+    pub fn color_by_mass(&mut self) {
+        let log_masses: Vec<f64> = self.bodies.iter().map(|b| b.mass.to_f64().log10()).collect();
+        let min = log_masses.iter().copied().fold(f64::INFINITY, f64::min);
+        let max = log_masses.iter().copied().fold(f64::NEG_INFINITY, f64::max);
+
+        for (body, m) in self.bodies.iter_mut().zip(log_masses) {
+            let [r, g, b, _] = colormap(m, min, max);
+            body.color = [r, g, b, body.color[3]];
+        }
+    }
+}
+
+impl FrameHistory {
+    /// 两帧的间隔超过该时长时不插值，避免暂停后单步执行等情况下画面缓慢滑向新的状态
+    pub const MAX_INTERVAL: Duration = Duration::from_millis(250);
+
+    pub fn new(frame: DrawFrame)-> Self {
+        let now = Instant::now();
+        Self {
+            previous: frame.clone(),
+            previous_at: now,
+            current: frame,
+            current_at: now,
+        }
+    }
+
+    /// 记录物理线程在`at`时刻写入的新一帧
+    pub fn push(&mut self, frame: DrawFrame, at: Instant) {
+        self.previous = std::mem::replace(&mut self.current, frame);
+        self.previous_at = std::mem::replace(&mut self.current_at, at);
+    }
+
+    /// 丢弃之前的帧，只保留`frame`
+    ///
+    /// 用于重置、读取存档等使世界状态发生跳变的操作，之后不在跳变前后插值
+    pub fn reset(&mut self, frame: DrawFrame) {
+        *self = Self::new(frame);
+    }
+
+    /// 在`now`时刻绘制时使用的插值比例
+    pub fn fraction(&self, now: Instant)-> f64 {
+        interpolation_fraction(self.previous_at, self.current_at, now)
+    }
+
+    /// 在`now`时刻绘制时使用的插值后的帧
+    pub fn interpolated(&self, now: Instant)-> DrawFrame {
+        let t = self.fraction(now);
+        if t >= 1.0 {
+            self.current.clone()
+        } else {
+            self.previous.lerp(&self.current, t)
+        }
+    }
+}
+
+/// 计算插值比例
+///
+/// 新一帧在`current_at`写入后，用与上一次写入间隔相同的时长从`previous_at`的状态过渡到`current_at`的状态，
+/// 返回`now`时刻的进度，范围为`[0, 1]`。两帧间隔为0或超过`FrameHistory::MAX_INTERVAL`时返回1
+pub fn interpolation_fraction(previous_at: Instant, current_at: Instant, now: Instant)-> f64 {
+    let interval = current_at.saturating_duration_since(previous_at);
+    if interval.is_zero() || interval > FrameHistory::MAX_INTERVAL {
+        return 1.0;
+    }
+
+    let since = now.saturating_duration_since(current_at);
+    (since.as_secs_f64() / interval.as_secs_f64()).min(1.0)
+}
+
+impl Renderable for DrawState {
+    fn center(&self)-> Point {
+        self.center
+    }
+
+    fn radius(&self)-> BigFloat {
+        self.radius
+    }
+
+    fn color(&self)-> [f32; 4] {
+        self.color
+    }
+
+    fn outline(&self)-> Option<([f32; 4], BigFloat)> {
+        self.outline
+    }
+
+    fn shape(&self)-> Shape {
+        self.shape
+    }
+}
+
+impl Default for EarthMoonWorld {
+    fn default()-> Self {
+        let earth = Body {
+            // 显示半径放大以便观察
+            radius: "7.6e7".parse().unwrap(),
+            label: Some("Earth".to_string()),
+            ..Body::new(
+                "5.965e24".parse().unwrap(),
+                Point { x: ZERO, y: ZERO, z: ZERO },
+                Vector::ZERO,
+                "6.371e6".parse().unwrap(),
+                [0.1, 0.1, 0.95, 1.0],
+            ).unwrap()
+        };
+
+        // 月球以近地点为起点
+        let moon = Body {
+            radius: "4.56e7".parse().unwrap(),
+            label: Some("Moon".to_string()),
+            ..Body::new(
+                "7.35e22".parse().unwrap(),
+                Point {
+                    x: ZERO,
+                    y: "3.57e8".parse().unwrap(),
+                    z: ZERO
+                },
+                Vector {
+                    x: BigFloat::from(1022),
+                    y: ZERO,
+                    z: ZERO,
+                },
+                "1.737e6".parse().unwrap(),
+                [0.25, 0.25, 0.25, 1.0],
+            ).unwrap()
+        };
+
+        let mut world = Self {
+            executor: CollisionExecutor::default(),
+            bodies: vec![earth, moon],
+            elapsed: Duration::ZERO,
+        };
+
+        // 地球初速度为0时质心会随月球漂移，变换到质心参考系使整个系统保持在画面中央
+        let mut objects = Objects::new(
+            world.bodies.iter_mut().map(|b| b as &mut dyn PhysicalObject).collect()
+        );
+        world.executor.gravity.recenter_to_com(&mut objects);
+        drop(objects);
+
+        world
+    }
+}
+
+impl Default for SolarSystemWorld {
+    fn default()-> Self {
+        let executor = SpaceExecutor::default();
+        let sun_mass: BigFloat = "1.989e30".parse().unwrap();
+
+        let sun = Body {
+            radius: "4.0e10".parse().unwrap(),
+            label: Some("Sun".to_string()),
+            ..Body::new(
+                sun_mass,
+                Point::ZERO,
+                Vector::ZERO,
+                "6.957e8".parse().unwrap(),
+                [0.95, 0.75, 0.1, 1.0],
+            ).unwrap()
+        };
+
+        // 名称、质量、轨道半径、物理半径、显示半径、颜色
+        let planets = [
+            ("Mercury", "3.301e23", "5.79e10", "2.440e6", "1.2e10", [0.6, 0.6, 0.6, 1.0]),
+            ("Venus", "4.867e24", "1.082e11", "6.052e6", "1.8e10", [0.9, 0.8, 0.5, 1.0]),
+            ("Earth", "5.972e24", "1.496e11", "6.371e6", "1.8e10", [0.1, 0.1, 0.95, 1.0]),
+            ("Mars", "6.417e23", "2.279e11", "3.390e6", "1.4e10", [0.8, 0.3, 0.1, 1.0]),
+            ("Jupiter", "1.898e27", "7.785e11", "6.991e7", "3.2e10", [0.8, 0.6, 0.4, 1.0]),
+            ("Saturn", "5.683e26", "1.434e12", "5.823e7", "2.8e10", [0.9, 0.8, 0.6, 1.0]),
+        ];
+
+        let mut bodies = vec![sun];
+        for (i, (name, mass, orbit, radius, display_radius, color)) in planets.iter().enumerate() {
+            let orbit: BigFloat = orbit.parse().unwrap();
+
+            // 各行星依次相隔90度放置，避免初始时排成一条直线
+            let radius_vector = match i % 4 {
+                0 => Vector { x: orbit, y: ZERO, z: ZERO },
+                1 => Vector { x: ZERO, y: orbit, z: ZERO },
+                2 => Vector { x: -orbit, y: ZERO, z: ZERO },
+                _ => Vector { x: ZERO, y: -orbit, z: ZERO },
+            };
+
+            bodies.push(Body {
+                radius: display_radius.parse().unwrap(),
+                label: Some(name.to_string()),
+                ..Body::on_circular_orbit(
+                    sun_mass,
+                    Point::ZERO,
+                    radius_vector,
+                    executor.g,
+                    mass.parse().unwrap(),
+                    radius.parse().unwrap(),
+                    *color,
+                ).unwrap()
+            });
+        }
+
+        Self {
+            executor,
+            bodies,
+            elapsed: Duration::ZERO,
+        }
+    }
+}
+
+impl Default for FigureEightWorld {
+    fn default()-> Self {
+        let x: BigFloat = "0.97000436".parse().unwrap();
+        let y: BigFloat = "-0.24308753".parse().unwrap();
+        let vx: BigFloat = "-0.93240737".parse().unwrap();
+        let vy: BigFloat = "-0.86473146".parse().unwrap();
+        let half = BigFloat::from(0.5);
+
+        // 位置、速度、颜色
+        let states = [
+            (Point { x, y, z: ZERO }, Vector { x: -vx * half, y: -vy * half, z: ZERO }, [0.9, 0.3, 0.3, 1.0]),
+            (Point { x: -x, y: -y, z: ZERO }, Vector { x: -vx * half, y: -vy * half, z: ZERO }, [0.3, 0.9, 0.3, 1.0]),
+            (Point::ZERO, Vector { x: vx, y: vy, z: ZERO }, [0.3, 0.5, 0.95, 1.0]),
+        ];
+
+        let bodies = states
+            .into_iter()
+            .map(|(center, velocity, color)| Body::new(ONE, center, velocity, "0.04".parse().unwrap(), color).unwrap())
+            .collect();
+
+        Self {
+            executor: VerletExecutor::new(SpaceExecutor::with_g(ONE)),
+            bodies,
+            elapsed: Duration::ZERO,
+        }
+    }
+}
+
+impl RandomClusterWorld {
+    /// 星团的半径
+    pub const RADIUS: f64 = 1.0;
+
+    /// 用种子`seed`生成`n`个天体的星团
+    ///
+    /// 各天体的质量在平均质量的0.5~1.5倍之间均匀取值，位置在球内均匀分布，
+    /// 速度方向随机且大小略低于位力平衡所需，星团会先收缩再弛豫。
+    /// 生成后变换到质心参考系，使星团整体不发生漂移
+    pub fn new(n: usize, seed: u64)-> Self {
+        let mut rng = Xorshift64::new(seed);
+        let mean_mass = 1.0 / n.max(1) as f64;
+        // 位力平衡时速度的上限约为`sqrt(G * M / R)`
+        let max_speed = 0.7 * (1.0 / Self::RADIUS).sqrt();
+
+        let mut bodies: Vec<Body> = (0..n)
+            .map(|_| {
+                let mass = mean_mass * rng.range_f64(0.5, 1.5);
+                let center = random_in_ball(&mut rng, Self::RADIUS);
+                let velocity = random_in_ball(&mut rng, max_speed);
+                let brightness = rng.range_f64(0.7, 1.0) as f32;
+
+                Body::new(
+                    BigFloat::from(mass),
+                    Point::from_f64_array(center),
+                    Vector::from_f64_array(velocity),
+                    "0.015".parse().unwrap(),
+                    [brightness, brightness * 0.9, 0.6 + brightness * 0.4, 1.0],
+                ).unwrap()
+            })
+            .collect();
+
+        // 天体密集，使用软化长度避免近距离相遇时速度发散
+        let mut gravity = SpaceExecutor::with_g(ONE);
+        gravity.softening = "0.05".parse().unwrap();
+        let mut executor = F64Executor::new(gravity);
+        let mut objects = Objects::new(
+            bodies.iter_mut().map(|b| b as &mut dyn PhysicalObject).collect()
+        );
+        executor.gravity.recenter_to_com(&mut objects);
+
+        Self {
+            executor,
+            bodies,
+            elapsed: Duration::ZERO,
+        }
+    }
+}
+
+/// 在半径为`radius`的球内均匀地取一点
+///
+/// 在外接立方体中取点并舍弃球外的点
+fn random_in_ball(rng: &mut Xorshift64, radius: f64)-> [f64; 3] {
+    loop {
+        let point = [
+            rng.range_f64(-1.0, 1.0),
+            rng.range_f64(-1.0, 1.0),
+            rng.range_f64(-1.0, 1.0),
+        ];
+        if point.iter().map(|c| c * c).sum::<f64>() <= 1.0 {
+            return point.map(|c| c * radius);
+        }
+    }
+}
+
+impl World for RandomClusterWorld {
+    fn get_drawable_items<'items, 'this: 'items>(&'this self)-> Vec<&'items dyn Drawable> {
+        self.bodies.iter().map(|b| b as &dyn Drawable).collect()
+    }
+
+    fn execute(&mut self, time: TimeStep) {
+        let mut objects = Objects::new(
+            self.bodies.iter_mut().map(|b| b as &mut dyn PhysicalObject).collect()
+        );
+        self.executor.step(&mut objects, time);
+        self.elapsed = time.advance(self.elapsed);
+    }
+
+    fn get_default_scale_base(&self)-> BigFloat {
+        BigFloat::from(Self::RADIUS * 1.5)
+    }
+
+    fn elapsed(&self)-> Duration {
+        self.elapsed
+    }
+
+    fn executor(&self)-> &SpaceExecutor {
+        &self.executor.gravity
+    }
+
+    fn bodies(&self)-> &[Body] {
+        &self.bodies
+    }
+
+    fn bodies_mut(&mut self)-> &mut Vec<Body> {
+        &mut self.bodies
+    }
+}
+
+impl World for FigureEightWorld {
+    fn get_drawable_items<'items, 'this: 'items>(&'this self)-> Vec<&'items dyn Drawable> {
+        self.bodies.iter().map(|b| b as &dyn Drawable).collect()
+    }
+
+    fn execute(&mut self, time: TimeStep) {
+        let mut objects = Objects::new(
+            self.bodies.iter_mut().map(|b| b as &mut dyn PhysicalObject).collect()
+        );
+        self.executor.step(&mut objects, time);
+        self.elapsed = time.advance(self.elapsed);
+    }
+
+    fn get_default_scale_base(&self)-> BigFloat {
+        "1.3".parse().unwrap()
+    }
+
+    fn elapsed(&self)-> Duration {
+        self.elapsed
+    }
+
+    fn executor(&self)-> &SpaceExecutor {
+        &self.executor.gravity
+    }
+
+    fn bodies(&self)-> &[Body] {
+        &self.bodies
+    }
+
+    fn bodies_mut(&mut self)-> &mut Vec<Body> {
+        &mut self.bodies
+    }
+}
+
+impl World for SolarSystemWorld {
+    fn get_drawable_items<'items, 'this: 'items>(&'this self)-> Vec<&'items dyn Drawable> {
+        self.bodies.iter().map(|b| b as &dyn Drawable).collect()
+    }
+
+    fn execute(&mut self, time: TimeStep) {
+        let mut objects = Objects::new(
+            self.bodies.iter_mut().map(|b| b as &mut dyn PhysicalObject).collect()
+        );
+        self.executor.step(&mut objects, time);
+        self.elapsed = time.advance(self.elapsed);
+    }
+
+    fn get_default_scale_base(&self)-> BigFloat {
+        "1.6e12".parse().unwrap()
+    }
+
+    fn elapsed(&self)-> Duration {
+        self.elapsed
+    }
+
+    fn executor(&self)-> &SpaceExecutor {
+        &self.executor
+    }
+
+    fn bodies(&self)-> &[Body] {
+        &self.bodies
+    }
+
+    fn bodies_mut(&mut self)-> &mut Vec<Body> {
+        &mut self.bodies
+    }
+}
+
+impl World for EarthMoonWorld {
+    fn get_drawable_items<'items, 'this: 'items>(&'this self)-> Vec<&'items dyn Drawable> {
+        self.bodies.iter().map(|b| b as &dyn Drawable).collect()
+    }
+
+    fn execute(&mut self, time: TimeStep) {
+        let mut objects = Objects::new(
+            self.bodies.iter_mut().map(|b| b as &mut dyn PhysicalObject).collect()
+        );
+        self.executor.step(&mut objects, time);
+        drop(objects);
+
+        for uid in self.executor.take_removed() {
+            self.remove(uid);
+        }
+        self.elapsed = time.advance(self.elapsed);
+    }
+
+    fn get_default_scale_base(&self)-> BigFloat {
+        "3.80e8".parse().unwrap()
+    }
+
+    fn elapsed(&self)-> Duration {
+        self.elapsed
+    }
+
+    fn executor(&self)-> &SpaceExecutor {
+        &self.executor.gravity
+    }
+
+    fn bodies(&self)-> &[Body] {
+        &self.bodies
+    }
+
+    fn bodies_mut(&mut self)-> &mut Vec<Body> {
+        &mut self.bodies
+    }
+}
+
+impl AnalyticTwoBodyWorld {
+    /// 以`primary`与`satellite`的当前状态为初始状态创建世界，`g`为引力常数
+    ///
+    /// 两者的相对运动不是椭圆轨道时返回`None`
+    pub fn new(primary: Body, satellite: Body, g: BigFloat)-> Option<Self> {
+        let initial = [primary.phyattr.clone(), satellite.phyattr.clone()];
+        if !is_elliptic(&initial, g) {
+            return None;
+        }
+
+        Some(Self {
+            executor: SpaceExecutor::with_g(g),
+            bodies: vec![primary, satellite],
+            elapsed: Duration::ZERO,
+            initial,
+            epoch: Duration::ZERO,
+        })
+    }
+
+    /// 以两天体的当前状态作为新的初始状态
+    ///
+    /// 当前状态不是椭圆轨道时返回`false`，此时保持原来的初始状态不变
+    fn rebase(&mut self)-> bool {
+        let current = [self.bodies[0].phyattr.clone(), self.bodies[1].phyattr.clone()];
+        if !is_elliptic(&current, self.executor.g) {
+            return false;
+        }
+
+        self.initial = current;
+        self.epoch = self.elapsed;
+        true
+    }
+
+    /// 计算两天体在初始状态之后`t`(以s为单位)时的位置与速度
+    pub fn state_at(&self, t: BigFloat)-> [(Point, Vector); 2] {
+        let [p, s] = &self.initial;
+        let total = p.mass + s.mass;
+
+        // 质心做匀速直线运动，两天体相对于质心的位移与相对位移成比例
+        let com_velocity = (p.velocity * p.mass + s.velocity * s.mass) / total;
+        let com = Point::ZERO + (p.center.as_vector() * p.mass + s.center.as_vector() * s.mass) / total + com_velocity * t;
+
+        let (r, v) = kepler_propagate(
+            p.center.vector_to(&s.center),
+            s.velocity - p.velocity,
+            self.executor.g * total,
+            t,
+        ).expect("The orbit was checked to be elliptic on creation");
+
+        [
+            (com + r * (-s.mass / total), com_velocity + v * (-s.mass / total)),
+            (com + r * (p.mass / total), com_velocity + v * (p.mass / total)),
+        ]
+    }
+}
+
+impl Default for AnalyticTwoBodyWorld {
+    /// 与`EarthMoonWorld`相同的地球与月球
+    fn default()-> Self {
+        let mut bodies = EarthMoonWorld::default().bodies.into_iter();
+        let earth = bodies.next().unwrap();
+        let moon = bodies.next().unwrap();
+
+        Self::new(earth, moon, SpaceExecutor::default().g).unwrap()
+    }
+}
+
+impl World for AnalyticTwoBodyWorld {
+    fn get_drawable_items<'items, 'this: 'items>(&'this self)-> Vec<&'items dyn Drawable> {
+        self.bodies.iter().map(|b| b as &dyn Drawable).collect()
+    }
+
+    fn execute(&mut self, time: TimeStep) {
+        if self.bodies.len() > 2 {
+            log::warn!("The analytic two-body world drives only two bodies, dropping {} spawned", self.bodies.len() - 2);
+            self.bodies.truncate(2);
+        }
+        self.elapsed = time.advance(self.elapsed);
+
+        let t = BigFloat::from((self.elapsed - self.epoch).as_nanos()) / BigFloat::from(1e9);
+        let states = self.state_at(t);
+        for (body, (center, velocity)) in self.bodies.iter_mut().zip(states) {
+            body.phyattr.center = center;
+            body.phyattr.velocity = velocity;
+        }
+    }
+
+    fn get_default_scale_base(&self)-> BigFloat {
+        "3.80e8".parse().unwrap()
+    }
+
+    fn elapsed(&self)-> Duration {
+        self.elapsed
+    }
+
+    fn executor(&self)-> &SpaceExecutor {
+        &self.executor
+    }
+
+    fn bodies(&self)-> &[Body] {
+        &self.bodies
+    }
+
+    fn bodies_mut(&mut self)-> &mut Vec<Body> {
+        &mut self.bodies
+    }
+
+    fn apply_impulse(&mut self, uid: Uuid, impulse: Vector)-> bool {
+        let Some(index) = self.bodies.iter().position(|b| b.uid == uid) else {
+            return false;
+        };
+
+        let previous = self.bodies[index].phyattr.clone();
+        self.bodies[index].phyattr.apply_impulse(impulse);
+        if !self.rebase() {
+            log::warn!("The impulse would break the elliptic orbit, ignoring it");
+            self.bodies[index].phyattr = previous;
+            return false;
+        }
+        true
+    }
+
+    fn remove(&mut self, _uid: Uuid)-> bool {
+        log::warn!("The analytic two-body world cannot remove either of its bodies");
+        false
+    }
+
+    fn restore(&mut self, snapshot: Vec<BodySnapshot>) {
+        if snapshot.len() != 2 {
+            log::warn!("Snapshot has {} bodies but the analytic world needs exactly 2, ignoring it", snapshot.len());
+            return;
+        }
+
+        let previous = self.snapshot();
+        for (body, s) in self.bodies.iter_mut().zip(snapshot) {
+            body.uid = s.uid;
+            body.phyattr = s.attributes;
+        }
+        if !self.rebase() {
+            log::warn!("Snapshot is not an elliptic orbit, ignoring it");
+            for (body, s) in self.bodies.iter_mut().zip(previous) {
+                body.uid = s.uid;
+                body.phyattr = s.attributes;
+            }
+        }
+    }
+}
+
+/// 判断两天体的相对运动是否为椭圆轨道
+fn is_elliptic(bodies: &[PhysicalAttributes; 2], g: BigFloat)-> bool {
+    kepler_propagate(
+        bodies[0].center.vector_to(&bodies[1].center),
+        bodies[1].velocity - bodies[0].velocity,
+        g * (bodies[0].mass + bodies[1].mass),
+        ZERO,
+    ).is_some()
+}
+
+#[cfg(feature = "serde")]
+impl ConfigWorld {
+    /// 从JSON格式的配置文件加载世界
+    pub fn load(path: &Path)-> Result<Self> {
+        Self::from_json(&std::fs::read_to_string(path)?)
+    }
+
+    /// 从JSON字符串加载世界
+    pub fn from_json(json: &str)-> Result<Self> {
+        let config: WorldConfig = serde_json::from_str(json)?;
+
+        let mut executor = SpaceExecutor::default();
+        if let Some(g) = &config.g {
+            executor.g = parse_bigfloat(g)?;
+        }
+        if let Some(softening) = &config.softening {
+            executor.softening = parse_bigfloat(softening)?;
+        }
+
+        let scale_base = match &config.scale_base {
+            Some(s) => parse_bigfloat(s)?,
+            None => "4.0e8".parse().unwrap(),
+        };
+
+        let bodies = config.bodies
+            .iter()
+            .map(|b| {
+                let mut body = Body::new(
+                    parse_bigfloat(&b.mass)?,
+                    Point {
+                        x: parse_bigfloat(&b.center[0])?,
+                        y: parse_bigfloat(&b.center[1])?,
+                        z: parse_bigfloat(&b.center[2])?,
+                    },
+                    Vector {
+                        x: parse_bigfloat(&b.velocity[0])?,
+                        y: parse_bigfloat(&b.velocity[1])?,
+                        z: parse_bigfloat(&b.velocity[2])?,
+                    },
+                    parse_bigfloat(&b.radius)?,
+                    b.color,
+                )?;
+
+                if let Some(r) = &b.display_radius {
+                    body.radius = check_radius(parse_bigfloat(r)?)?;
+                }
+                if let Some(q) = &b.charge {
+                    body.phyattr.charge = parse_bigfloat(q)?;
+                }
+                body.label = b.label.clone();
+
+                Ok(body)
+            })
+            .collect::<Result<Vec<_>>>()?;
+
+        Ok(Self {
+            executor,
+            bodies,
+            scale_base,
+            elapsed: Duration::ZERO,
+        })
+    }
+}
+
+#[cfg(feature = "serde")]
+impl World for ConfigWorld {
+    fn get_drawable_items<'items, 'this: 'items>(&'this self)-> Vec<&'items dyn Drawable> {
+        self.bodies.iter().map(|b| b as &dyn Drawable).collect()
+    }
+
+    fn execute(&mut self, time: TimeStep) {
+        let mut objects = Objects::new(
+            self.bodies.iter_mut().map(|b| b as &mut dyn PhysicalObject).collect()
+        );
+        self.executor.step(&mut objects, time);
+        self.elapsed = time.advance(self.elapsed);
+    }
+
+    fn get_default_scale_base(&self)-> BigFloat {
+        self.scale_base
+    }
+
+    fn elapsed(&self)-> Duration {
+        self.elapsed
+    }
+
+    fn executor(&self)-> &SpaceExecutor {
+        &self.executor
+    }
+
+    fn bodies(&self)-> &[Body] {
+        &self.bodies
+    }
+
+    fn bodies_mut(&mut self)-> &mut Vec<Body> {
+        &mut self.bodies
+    }
+}
+
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn collision_removes_absorbed_body_from_world() {
+        let mut world = EarthMoonWorld::default();
+        let earth = world.bodies()[0].get_physical_attributes().center;
+        let moon = world.bodies()[1].uid;
+
+        // 放在地球内部的小天体在下一步被地球吸收
+        let debris = Body::new(BigFloat::from(1000), earth, Vector::ZERO, ONE, [1.0; 4]).unwrap();
+        let debris_uid = debris.uid;
+        world.bodies_mut().push(debris);
+
+        world.execute(TimeStep::from_millis_f64(30.0));
+
+        assert_eq!(world.bodies().len(), 2);
+        assert!(world.bodies().iter().all(|b| b.uid != debris_uid));
+        assert!(world.bodies().iter().any(|b| b.uid == moon));
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn save_step_load_restores_positions() {
+        let path = std::env::temp_dir().join(format!("world-{}.json", Uuid::new_v4()));
+        let mut world = EarthMoonWorld::default();
+        let saved: Vec<Point> = world.bodies().iter().map(|b| b.get_physical_attributes().center).collect();
+
+        world.save_to(&path).unwrap();
+        world.execute(TimeStep::from_millis_f64(30.0));
+        assert_ne!(world.bodies()[1].get_physical_attributes().center, saved[1]);
+
+        let loaded = world.load_from(&path);
+        std::fs::remove_file(&path).unwrap();
+        loaded.unwrap();
+
+        let restored: Vec<Point> = world.bodies().iter().map(|b| b.get_physical_attributes().center).collect();
+        assert_eq!(restored, saved);
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn config_world_loads_two_bodies() {
+        let world = ConfigWorld::from_json(r#"{
+            "g": "1",
+            "bodies": [
+                { "mass": "5.965e24", "center": ["0", "0", "0"], "velocity": ["0", "0", "0"], "radius": "6.371e6", "color": [0.1, 0.1, 0.95, 1.0] },
+                { "mass": "7.349e22", "center": ["3.844e8", "0", "0"], "velocity": ["0", "1023", "0"], "radius": "1.737e6", "color": [0.5, 0.5, 0.5, 1.0] }
+            ]
+        }"#).unwrap();
+
+        let masses: Vec<BigFloat> = world.bodies().iter().map(|b| b.get_physical_attributes().mass).collect();
+        assert_eq!(masses, vec!["5.965e24".parse().unwrap(), "7.349e22".parse::<BigFloat>().unwrap()]);
+        assert_eq!(world.executor().g, ONE);
+    }
+
+    #[test]
+    fn circular_orbit_body_keeps_radius() {
+        let mut primary = Body::new(ONE, Point::ZERO, Vector::ZERO, ZERO, [1.0; 4]).unwrap();
+        let radius_vector = Vector::from_f64_array([0.6, 0.8, 0.0]);
+        let mut satellite = Body::on_circular_orbit(ONE, Point::ZERO, radius_vector, ONE, BigFloat::from(1e-9), ZERO, [1.0; 4]).unwrap();
+
+        let mut executor = SpaceExecutor::with_g(ONE);
+        executor.anchored.insert(primary.uid);
+        let mut objects = Objects::new(vec![&mut primary as &mut dyn PhysicalObject, &mut satellite]);
+        executor.step_many(&mut objects, TimeStep::from_millis_f64(1.0), 2000);
+        drop(objects);
+
+        let r = satellite.get_physical_attributes().center.as_vector().model().to_f64();
+        assert!((r - 1.0).abs() < 0.01, "radius drifted to {}", r);
+    }
+
+    #[test]
+    fn solar_system_has_sun_and_six_planets() {
+        let world = SolarSystemWorld::default();
+        assert_eq!(world.bodies().len(), 7);
+        assert_eq!(world.get_drawable_items().len(), 7);
+    }
+
+    #[test]
+    fn added_body_participates_in_forces() {
+        let step = TimeStep::from_millis_f64(30.0);
+        let mut plain = EarthMoonWorld::default();
+        let mut extended = EarthMoonWorld::default();
+
+        let third = Body::new(
+            "1.0e24".parse().unwrap(),
+            Point { x: ZERO, y: "2.0e8".parse().unwrap(), z: ZERO },
+            Vector::ZERO,
+            ONE,
+            [1.0; 4],
+        ).unwrap();
+        extended.bodies_mut().push(third);
+
+        plain.execute(step);
+        extended.execute(step);
+
+        // 第三个物体受地月的引力而运动，同时改变了地球的受力
+        assert_ne!(extended.bodies()[2].get_physical_attributes().velocity, Vector::ZERO);
+        assert_ne!(
+            extended.bodies()[0].get_physical_attributes().force,
+            plain.bodies()[0].get_physical_attributes().force,
+        );
+    }
+
+    #[test]
+    fn snapshot_restore_preserves_positions() {
+        let mut world = EarthMoonWorld::default();
+        let snapshot = world.snapshot();
+
+        world.execute_many(TimeStep::from_millis_f64(30.0), 10);
+        assert_ne!(world.snapshot(), snapshot);
+
+        world.restore(snapshot.clone());
+        assert_eq!(world.snapshot(), snapshot);
+    }
+
+    #[test]
+    fn frame_history_holds_latest_positions() {
+        let mut world = EarthMoonWorld::default();
+        let mut history = FrameHistory::new(world.draw_frame());
+        let start = Instant::now();
+
+        for i in 1..=3 {
+            world.execute(TimeStep::from_millis_f64(30.0));
+            history.push(world.draw_frame(), start + Duration::from_millis(30 * i));
+        }
+
+        let latest: Vec<Point> = world.bodies().iter().map(|b| b.get_physical_attributes().center).collect();
+        let current: Vec<Point> = history.current.bodies.iter().map(|b| b.center).collect();
+        assert_eq!(current, latest);
+        assert_ne!(history.previous.bodies[1].center, history.current.bodies[1].center);
+        assert_eq!(history.current.elapsed, world.elapsed());
+    }
+
+    #[test]
+    fn boxed_world_selects_type_at_runtime() {
+        // 泛型函数通过`impl World for Box<W>`接受装箱的世界
+        fn run<W: World>(mut world: W)-> (usize, Duration) {
+            world.execute_many(TimeStep::from_millis_f64(30.0), 2);
+            (world.bodies().len(), world.elapsed())
+        }
+
+        let worlds: Vec<Box<dyn World>> = [false, true]
+            .into_iter()
+            .map(|solar| if solar {
+                Box::new(SolarSystemWorld::default()) as Box<dyn World>
+            } else {
+                Box::new(EarthMoonWorld::default())
+            })
+            .collect();
+
+        let results: Vec<_> = worlds.into_iter().map(run).collect();
+        assert_eq!(results, vec![(2, Duration::from_millis(60)), (7, Duration::from_millis(60))]);
+    }
+
+    #[test]
+    fn figure_eight_stays_bounded() {
+        let mut world = FigureEightWorld::default();
+        let step = TimeStep::from_millis_f64(10.0);
+
+        // 约一个周期(6.33)
+        for _ in 0..600 {
+            world.execute(step);
+            assert_eq!(world.bodies().len(), 3);
+            for body in world.bodies() {
+                let r = body.get_physical_attributes().center.as_vector().model().to_f64();
+                assert!(r < 1.2, "body escaped to {} after {:?}", r, world.elapsed());
+            }
+        }
+    }
+
+    #[test]
+    fn random_cluster_is_reproducible_from_seed() {
+        let positions = |world: &RandomClusterWorld| -> Vec<Point> {
+            world.bodies().iter().map(|b| b.get_physical_attributes().center).collect()
+        };
+
+        let a = RandomClusterWorld::new(50, 7);
+        let b = RandomClusterWorld::new(50, 7);
+        let c = RandomClusterWorld::new(50, 8);
+
+        assert_eq!(a.bodies().len(), 50);
+        assert_eq!(positions(&a), positions(&b));
+        assert_ne!(positions(&a), positions(&c));
+    }
+
+    #[test]
+    fn interpolation_fraction_tracks_frame_interval() {
+        let t0 = Instant::now();
+        let ms = Duration::from_millis;
+        let previous = t0;
+        let current = t0 + ms(40);
+
+        assert_eq!(interpolation_fraction(previous, current, current), 0.0);
+        assert_eq!(interpolation_fraction(previous, current, current + ms(10)), 0.25);
+        assert_eq!(interpolation_fraction(previous, current, current + ms(20)), 0.5);
+        assert_eq!(interpolation_fraction(previous, current, current + ms(100)), 1.0);
+
+        // `now`早于`current_at`时从0开始
+        assert_eq!(interpolation_fraction(previous, current, t0), 0.0);
+
+        // 间隔为0或过长时直接显示最新一帧
+        assert_eq!(interpolation_fraction(current, current, current), 1.0);
+        let stalled = previous + FrameHistory::MAX_INTERVAL + ms(1);
+        assert_eq!(interpolation_fraction(previous, stalled, stalled), 1.0);
+    }
+
+    #[test]
+    fn removed_body_no_longer_drawn_or_attracting() {
+        let mut world = EarthMoonWorld::default();
+        let moon = world.bodies()[1].uid;
+        world.execute(TimeStep::from_millis_f64(30.0));
+        assert_ne!(world.bodies()[0].get_physical_attributes().force, Vector::ZERO);
+
+        assert!(world.remove(moon));
+        assert!(!world.remove(moon));
+        assert_eq!(world.get_drawable_items().len(), 1);
+        assert!(world.draw_frame().bodies.iter().all(|b| b.uid != moon));
+
+        // 只剩地球时不再受力
+        world.execute(TimeStep::from_millis_f64(30.0));
+        assert_eq!(world.bodies()[0].get_physical_attributes().force, Vector::ZERO);
+    }
+
+    #[test]
+    fn analytic_two_body_matches_fine_numeric_run() {
+        let primary = Body::new(ONE, Point::ZERO, Vector::ZERO, ZERO, [1.0; 4]).unwrap();
+        let satellite = Body::new(
+            BigFloat::from(0.01),
+            Point::from_f64_array([1.0, 0.0, 0.0]),
+            Vector::from_f64_array([0.0, 1.1, 0.0]),
+            ZERO,
+            [1.0; 4],
+        ).unwrap();
+
+        let mut numeric: Vec<BodySnapshot> = [&primary, &satellite]
+            .into_iter()
+            .map(|b| BodySnapshot { uid: b.uid, attributes: b.phyattr.clone() })
+            .collect();
+        let mut analytic = AnalyticTwoBodyWorld::new(primary, satellite, ONE).unwrap();
+
+        // 偏心轨道约半个周期，每100步比较一次
+        let dt = TimeStep::from_millis_f64(10.0);
+        let mut rk4 = Rk4Executor::new(SpaceExecutor::with_g(ONE));
+        for checkpoint in 1..=3 {
+            analytic.execute_many(dt, 100);
+            let mut objects = Objects::new(numeric.iter_mut().map(|b| b as &mut dyn PhysicalObject).collect());
+            rk4.step_many(&mut objects, dt, 100);
+            drop(objects);
+
+            for (a, n) in analytic.bodies().iter().zip(&numeric) {
+                let d = a.phyattr.center.distance(&n.attributes.center).to_f64();
+                assert!(d < 1e-6, "analytic and numeric positions differ by {} after {} steps", d, checkpoint * 100);
+            }
+        }
+        assert!(analytic.bodies()[1].phyattr.center.distance(&Point::from_f64_array([1.0, 0.0, 0.0])).to_f64() > 0.5);
+    }
+
+    #[test]
+    fn analytic_two_body_follows_restored_state() {
+        let dt = TimeStep::from_millis_f64(30.0);
+        let mut reference = AnalyticTwoBodyWorld::default();
+        reference.execute_many(dt, 200);
+
+        let mut world = AnalyticTwoBodyWorld::default();
+        world.execute_many(dt, 100);
+        let saved = world.snapshot();
+        world.execute_many(dt, 50);
+        world.restore(saved.clone());
+        assert_eq!(world.snapshot(), saved);
+
+        // 恢复后从快照的状态继续运动，而不是回到原来的轨迹
+        world.execute_many(dt, 100);
+        for (a, b) in world.bodies().iter().zip(reference.bodies()) {
+            let d = a.phyattr.center.distance(&b.phyattr.center).to_f64();
+            assert!(d < 1e-3, "restored world drifted {} m from the reference", d);
+        }
+
+        let [earth, moon] = [world.bodies()[0].uid, world.bodies()[1].uid];
+        assert!(!world.remove(earth));
+        let debris = Body::new(ONE, Point::from_f64_array([1e9, 0.0, 0.0]), Vector::ZERO, ONE, [1.0; 4]).unwrap();
+        world.bodies_mut().push(debris);
+        world.execute(dt);
+        assert_eq!(world.bodies().iter().map(|b| b.uid).collect::<Vec<_>>(), vec![earth, moon]);
+    }
+}
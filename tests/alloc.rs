@@ -0,0 +1,72 @@
+//! 检查物理计算复用缓冲的效果
+//!
+//! 替换全局分配器会影响同一测试程序中的所有测试，因此单独放在一个测试程序中
+
+use gravitation_simulator::physics::*;
+use num_bigfloat::{ ONE, ZERO };
+use uuid::Uuid;
+use std::alloc::{ GlobalAlloc, Layout, System };
+use std::cell::Cell;
+
+/// 统计当前线程的堆分配次数
+struct CountingAllocator;
+
+thread_local! {
+    static ALLOCATIONS: Cell<usize> = const { Cell::new(0) };
+}
+
+unsafe impl GlobalAlloc for CountingAllocator {
+    unsafe fn alloc(&self, layout: Layout)-> *mut u8 {
+        let _ = ALLOCATIONS.try_with(|n| n.set(n.get() + 1));
+        unsafe { System.alloc(layout) }
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        unsafe { System.dealloc(ptr, layout) }
+    }
+}
+
+#[global_allocator]
+static ALLOCATOR: CountingAllocator = CountingAllocator;
+
+fn allocations_during(f: impl FnOnce())-> usize {
+    let before = ALLOCATIONS.with(|n| n.get());
+    f();
+    ALLOCATIONS.with(|n| n.get()) - before
+}
+
+/// 两个质量为1、相距1的物体绕质心做圆周运动(G = 1)
+fn circular_pair()-> Vec<BodySnapshot> {
+    let v = 0.5f64.sqrt();
+    [(0.5, v), (-0.5, -v)]
+        .into_iter()
+        .map(|(x, vy)| BodySnapshot {
+            uid: Uuid::new_v4(),
+            attributes: PhysicalAttributes::new(
+                Point::from_f64_array([x, 0.0, 0.0]),
+                Vector::from_f64_array([0.0, vy, 0.0]),
+                ONE,
+                ZERO,
+            ).unwrap(),
+        })
+        .collect()
+}
+
+#[test]
+fn warm_force_scratch_does_not_allocate() {
+    let mut state = circular_pair();
+    let mut objects = Objects::new(state.iter_mut().map(|s| s as &mut dyn PhysicalObject).collect());
+    let dt = TimeStep::from_millis_f64(10.0);
+    let mut executor = SpaceExecutor::with_g(ONE);
+
+    let cold = allocations_during(|| executor.execute_force(&mut objects, dt));
+    let warm = allocations_during(|| {
+        for _ in 0..10 {
+            executor.reset_forces(&mut objects);
+            executor.execute_force(&mut objects, dt);
+        }
+    });
+
+    assert!(cold > 0);
+    assert_eq!(warm, 0);
+}